@@ -0,0 +1,34 @@
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::bodies::Shape;
+use rust_g_engine::camera::Camera;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::Sphere;
+use rust_g_engine::scene::body_configurations;
+use rust_g_engine::scene::HeadlessRunner;
+use rust_g_engine::scene::Simulation;
+
+fn main() {
+    let camera: Camera = Camera::new(1760, 960);
+    let mut simulation: Simulation = Simulation::new_headless(camera);
+    simulation.diagnostics.enabled = true;
+    simulation.set_timestep_hz(10.0);
+
+    let system = body_configurations::orbiting_system2(Vector3D::new(0.0, 0.0, 0.0));
+    simulation.extend_objects(system);
+
+    let position: Vector3D = Vector3D::new(2_000_000.0, 0.0, 0.0);
+    let mut sphere = Sphere::new(50_000.0, 10, 10);
+    sphere.set_offset(position.x, position.y, position.z);
+
+    let mut body = Shape::new(sphere.get_triangle_mesh(), None);
+    body.physics_mut()
+        .set_position(position.x, position.y, position.z);
+    body.physics_mut().set_mass(1_000_000.0);
+    body.physics_mut().set_velocity(-5_000.0, 0.0, 0.0);
+    simulation.add_object(BodyType::Shape(body));
+
+    let mut runner: HeadlessRunner = HeadlessRunner::new(simulation);
+    runner.run_for(60.0);
+    runner.report();
+}
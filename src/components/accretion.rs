@@ -0,0 +1,335 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::color::RGBA;
+use crate::components::contact::ContactManifold;
+use crate::components::material::PhysicsMaterial;
+use crate::components::physics::Physics;
+use crate::components::shape::Shape;
+use crate::components::vectors::Vector3D;
+use crate::components::vertices::Sphere;
+
+// Spreads the fragment directions evenly over the hemisphere.
+const GOLDEN_ANGLE: f64 = 2.399_963_229_728_653;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CollisionOutcome {
+    Bounce,
+    Merge,
+    Shatter,
+}
+
+/// Decides what happens to two colliding bodies from their impact speed,
+/// the mutual escape velocity and the specific impact energy.
+#[derive(Clone, Debug)]
+pub struct AccretionModel {
+    pub merge_factor: f64,
+    pub shatter_energy: f64,
+    pub fragment_count: usize,
+    pub fragment_exponent: f64,
+    pub min_fragment_mass: f64,
+    pub ejecta_factor: f64,
+    pub mesh_resolution: usize,
+    pub merge_count: usize,
+    pub shatter_count: usize,
+}
+
+impl AccretionModel {
+    pub fn new(shatter_energy: f64, min_fragment_mass: f64) -> AccretionModel {
+        AccretionModel {
+            merge_factor: 1.0,
+            shatter_energy,
+            fragment_count: 6,
+            fragment_exponent: 1.0,
+            min_fragment_mass,
+            ejecta_factor: 0.3,
+            mesh_resolution: 10,
+            merge_count: 0,
+            shatter_count: 0,
+        }
+    }
+
+    pub fn set_shatter_energy(&mut self, shatter_energy: f64) {
+        self.shatter_energy = shatter_energy.max(0.0);
+    }
+
+    pub fn set_fragment_count(&mut self, fragment_count: usize) {
+        self.fragment_count = fragment_count;
+    }
+
+    // Slow impacts below the mutual escape velocity stay bound and merge,
+    // fast ones shatter the smaller body once the impact energy per unit of
+    // total mass passes the threshold.
+    pub fn get_outcome(&self, a: &Physics, b: &Physics) -> CollisionOutcome {
+        if a.is_stationary || b.is_stationary {
+            return CollisionOutcome::Bounce;
+        }
+
+        let speed: f64 = b.velocity.subtract_vector(&a.velocity).get_length();
+        let total_mass: f64 = a.mass + b.mass;
+        let radius: f64 = a.get_bounding_radius() + b.get_bounding_radius();
+        let escape_speed: f64 = (2.0 * a.g_const * total_mass / radius).sqrt();
+        if speed < self.merge_factor * escape_speed {
+            return CollisionOutcome::Merge;
+        }
+
+        let reduced_mass: f64 = a.mass * b.mass / total_mass;
+        let impact_energy: f64 = 0.5 * reduced_mass * speed.powi(2) / total_mass;
+        let smaller_mass: f64 = a.mass.min(b.mass);
+        if impact_energy > self.shatter_energy && self.get_fragment_masses(smaller_mass).len() > 1 {
+            return CollisionOutcome::Shatter;
+        }
+        CollisionOutcome::Bounce
+    }
+
+    // Power law masses with the largest fragment first, fragments below the
+    // minimum mass are folded back into the others.
+    fn get_fragment_masses(&self, mass: f64) -> Vec<f64> {
+        let mut count: usize = self.fragment_count;
+        while count > 1 {
+            let weights: Vec<f64> = (0..count)
+                .map(|k| (k as f64 + 1.0).powf(-self.fragment_exponent))
+                .collect();
+            let total: f64 = weights.iter().sum();
+            let masses: Vec<f64> = weights.iter().map(|w| mass * w / total).collect();
+            if masses[count - 1] >= self.min_fragment_mass {
+                return masses;
+            }
+            count -= 1;
+        }
+        vec![mass]
+    }
+
+    fn get_color(physics: &Physics) -> RGBA {
+        match physics.mesh.polygons.first() {
+            Some(polygon) => polygon.get_color(),
+            None => RGBA::from_random(),
+        }
+    }
+
+    fn get_sphere(
+        &self,
+        position: Vector3D,
+        radius: f64,
+        mass: f64,
+        color: RGBA,
+        material: PhysicsMaterial,
+    ) -> BodyType {
+        let resolution: usize = self.mesh_resolution;
+        let mut sphere: Sphere = Sphere::new(radius, resolution, resolution);
+        sphere.set_offset(position.x, position.y, position.z);
+        sphere.set_color(color);
+        sphere.set_shader(RGBA::from_rgb(0.5, 0.5, 0.5));
+        let mesh = sphere.get_triangle_mesh();
+
+        let mut body: Shape = Shape::new(mesh, None);
+        let physics: &mut Physics = body.physics_mut();
+        physics.set_position(position.x, position.y, position.z);
+        physics.material = material;
+        physics.set_mass(mass);
+        BodyType::Shape(body)
+    }
+
+    /// Combines two bodies into one sphere of their total volume, keeping
+    /// the mass, the momentum and the angular momentum about the common
+    /// center of mass.
+    pub fn merge(&mut self, a: &Physics, b: &Physics) -> BodyType {
+        let total_mass: f64 = a.mass + b.mass;
+        let position: Vector3D = a
+            .get_position()
+            .multiply(a.mass)
+            .add_vector(&b.get_position().multiply(b.mass))
+            .divide(total_mass);
+        let velocity: Vector3D = a
+            .velocity
+            .multiply(a.mass)
+            .add_vector(&b.velocity.multiply(b.mass))
+            .divide(total_mass);
+
+        let mut angular_momentum: Vector3D = a
+            .get_angular_momentum()
+            .add_vector(&b.get_angular_momentum());
+        for physics in [a, b] {
+            let offset: Vector3D = physics.get_position().subtract_vector(&position);
+            let relative_velocity: Vector3D = physics.velocity.subtract_vector(&velocity);
+            let orbital: Vector3D = offset.cross_product(&relative_velocity.multiply(physics.mass));
+            angular_momentum = angular_momentum.add_vector(&orbital);
+        }
+
+        let larger: &Physics = if a.mass >= b.mass { a } else { b };
+        let radius: f64 =
+            (a.get_bounding_radius().powi(3) + b.get_bounding_radius().powi(3)).cbrt();
+        let color: RGBA = Self::get_color(larger);
+        let mut body: BodyType =
+            self.get_sphere(position, radius, total_mass, color, larger.material);
+
+        let physics: &mut Physics = body.physics_mut();
+        physics.set_velocity(velocity.x, velocity.y, velocity.z);
+        let spin: Vector3D = physics
+            .inverse_inertia_world
+            .multiply_vector(&angular_momentum);
+        physics.set_angular_velocity(spin.x, spin.y, spin.z);
+
+        self.merge_count += 1;
+        body
+    }
+
+    /// Breaks the smaller body into fragments thrown off away from the
+    /// larger one. Both share the center of mass velocity of the impact, the
+    /// momentum carried off by the fragments is taken from the larger body
+    /// and the angular momentum the impact loses spins it up. The new
+    /// velocity and angular velocity of the larger body are returned with
+    /// the fragments.
+    pub fn shatter(
+        &mut self,
+        larger: &Physics,
+        smaller: &Physics,
+    ) -> (Vector3D, Vector3D, Vec<BodyType>) {
+        let total_mass: f64 = larger.mass + smaller.mass;
+        let center: Vector3D = larger
+            .get_position()
+            .multiply(larger.mass)
+            .add_vector(&smaller.get_position().multiply(smaller.mass))
+            .divide(total_mass);
+        let get_orbital = |position: &Vector3D, velocity: &Vector3D, mass: f64| -> Vector3D {
+            position
+                .subtract_vector(&center)
+                .cross_product(&velocity.multiply(mass))
+        };
+        let mut angular_momentum: Vector3D = Vector3D::default(0.0);
+        for physics in [larger, smaller] {
+            let orbital: Vector3D =
+                get_orbital(&physics.get_position(), &physics.velocity, physics.mass);
+            angular_momentum = angular_momentum
+                .add_vector(&physics.get_angular_momentum())
+                .add_vector(&orbital);
+        }
+
+        let center_velocity: Vector3D = larger
+            .velocity
+            .multiply(larger.mass)
+            .add_vector(&smaller.velocity.multiply(smaller.mass))
+            .divide(total_mass);
+        let impact_speed: f64 = smaller
+            .velocity
+            .subtract_vector(&larger.velocity)
+            .get_length();
+        let dispersal_speed: f64 = self.ejecta_factor * impact_speed;
+
+        let offset: Vector3D = smaller
+            .get_position()
+            .subtract_vector(&larger.get_position());
+        let away: Vector3D = if offset.get_length() > 0.0 {
+            offset.normalize()
+        } else {
+            Vector3D::new(0.0, 1.0, 0.0)
+        };
+        let (tangent1, tangent2): (Vector3D, Vector3D) = ContactManifold::get_tangents(&away);
+
+        let masses: Vec<f64> = self.get_fragment_masses(smaller.mass);
+        let radius: f64 = smaller.get_bounding_radius();
+        let color: RGBA = Self::get_color(smaller);
+        let mut placed: Vec<(Vector3D, f64)> = Vec::new();
+        let mut fragments: Vec<BodyType> = Vec::new();
+        let mut dispersal_momentum: Vector3D = Vector3D::default(0.0);
+
+        for (k, mass) in masses.iter().enumerate() {
+            let fragment_radius: f64 = radius * (mass / smaller.mass).cbrt();
+            let height: f64 = (k as f64 + 0.5) / masses.len() as f64;
+            let ring: f64 = (1.0 - height * height).sqrt();
+            let angle: f64 = k as f64 * GOLDEN_ANGLE;
+            let direction: Vector3D = away
+                .multiply(height)
+                .add_vector(&tangent1.multiply(ring * angle.cos()))
+                .add_vector(&tangent2.multiply(ring * angle.sin()));
+
+            // Fragments start inside the old body and are pushed outwards
+            // until they no longer overlap the ones placed before them.
+            let mut distance: f64 = radius - fragment_radius;
+            let mut position: Vector3D = smaller
+                .get_position()
+                .add_vector(&direction.multiply(distance));
+            while placed
+                .iter()
+                .any(|(other, r)| other.get_distance(&position) < r + fragment_radius)
+            {
+                distance += fragment_radius * 0.5;
+                position = smaller
+                    .get_position()
+                    .add_vector(&direction.multiply(distance));
+            }
+            placed.push((position, fragment_radius));
+
+            let velocity: Vector3D =
+                center_velocity.add_vector(&direction.multiply(dispersal_speed));
+            dispersal_momentum =
+                dispersal_momentum.add_vector(&direction.multiply(dispersal_speed * mass));
+
+            let mut fragment: BodyType =
+                self.get_sphere(position, fragment_radius, *mass, color, smaller.material);
+            let physics: &mut Physics = fragment.physics_mut();
+            physics.set_velocity(velocity.x, velocity.y, velocity.z);
+            let spin: Vector3D = smaller.angular_velocity;
+            physics.set_angular_velocity(spin.x, spin.y, spin.z);
+            angular_momentum = angular_momentum
+                .subtract_vector(&get_orbital(&position, &velocity, *mass))
+                .subtract_vector(&physics.get_angular_momentum());
+            fragments.push(fragment);
+        }
+
+        self.shatter_count += 1;
+        let velocity: Vector3D =
+            center_velocity.subtract_vector(&dispersal_momentum.divide(larger.mass));
+        angular_momentum = angular_momentum
+            .subtract_vector(&get_orbital(&larger.get_position(), &velocity, larger.mass))
+            .subtract_vector(&larger.get_angular_momentum());
+        let spin: Vector3D = larger.angular_velocity.add_vector(
+            &larger
+                .inverse_inertia_world
+                .multiply_vector(&angular_momentum),
+        );
+        (velocity, spin, fragments)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum CollisionPolicy {
+    Bounce,
+    Accretion(AccretionModel),
+}
+
+impl CollisionPolicy {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            CollisionPolicy::Bounce => "Bounce",
+            CollisionPolicy::Accretion(_) => "Accretion",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CollisionPolicy> {
+        match name.to_lowercase().as_str() {
+            "bounce" | "restitution" => Some(CollisionPolicy::Bounce),
+            "accretion" | "merge" => Some(CollisionPolicy::get_accretion()),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> CollisionPolicy {
+        match self {
+            CollisionPolicy::Bounce => CollisionPolicy::get_accretion(),
+            CollisionPolicy::Accretion(_) => CollisionPolicy::Bounce,
+        }
+    }
+
+    /// The thresholds suit the orbiting systems, where the small bodies
+    /// weigh a few thousand units.
+    pub fn get_accretion() -> CollisionPolicy {
+        CollisionPolicy::Accretion(AccretionModel::new(100_000.0, 200.0))
+    }
+
+    pub fn get_outcome(&self, a: &Physics, b: &Physics) -> CollisionOutcome {
+        match self {
+            CollisionPolicy::Bounce => CollisionOutcome::Bounce,
+            CollisionPolicy::Accretion(model) => model.get_outcome(a, b),
+        }
+    }
+}
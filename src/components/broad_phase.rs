@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+pub type AABB = ([f64; 3], [f64; 3]);
+
+const MAX_CELLS_PER_AXIS: f64 = 1024.0;
+
+pub trait BroadPhase {
+    fn get_name(&self) -> &'static str;
+    fn get_candidate_pairs(&mut self, aabbs: &[AABB]) -> Vec<(usize, usize)>;
+}
+
+fn aabbs_overlap(a: &AABB, b: &AABB) -> bool {
+    let (min_a, max_a): &([f64; 3], [f64; 3]) = a;
+    let (min_b, max_b): &([f64; 3], [f64; 3]) = b;
+
+    for i in 0..3 {
+        if min_a[i] > max_b[i] || min_b[i] > max_a[i] {
+            return false;
+        }
+    }
+    true
+}
+
+fn ordered_pair(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AllPairs;
+
+impl AllPairs {
+    pub fn new() -> AllPairs {
+        AllPairs
+    }
+}
+
+impl BroadPhase for AllPairs {
+    fn get_name(&self) -> &'static str {
+        "All Pairs"
+    }
+
+    fn get_candidate_pairs(&mut self, aabbs: &[AABB]) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+
+        for i in 0..aabbs.len() {
+            for j in (i + 1)..aabbs.len() {
+                pairs.push((i, j));
+            }
+        }
+        pairs
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SweepAndPrune {
+    sorted_indices: Vec<usize>,
+}
+
+impl SweepAndPrune {
+    pub fn new() -> SweepAndPrune {
+        SweepAndPrune {
+            sorted_indices: Vec::new(),
+        }
+    }
+
+    fn get_sweep_axis(aabbs: &[AABB]) -> usize {
+        let count: f64 = aabbs.len() as f64;
+        let mut sum: [f64; 3] = [0.0; 3];
+        let mut sum_squared: [f64; 3] = [0.0; 3];
+
+        for (min, max) in aabbs {
+            for i in 0..3 {
+                let center: f64 = (min[i] + max[i]) / 2.0;
+                sum[i] += center;
+                sum_squared[i] += center * center;
+            }
+        }
+
+        let mut axis: usize = 0;
+        let mut max_variance: f64 = f64::MIN;
+        for i in 0..3 {
+            let variance: f64 = sum_squared[i] / count - (sum[i] / count).powi(2);
+            if variance > max_variance {
+                max_variance = variance;
+                axis = i;
+            }
+        }
+        axis
+    }
+
+    fn sort_indices(&mut self, aabbs: &[AABB], axis: usize) {
+        if self.sorted_indices.len() != aabbs.len() {
+            self.sorted_indices = (0..aabbs.len()).collect();
+        }
+
+        // The order barely changes between steps, so an insertion sort over
+        // the previous order is close to linear.
+        let indices: &mut Vec<usize> = &mut self.sorted_indices;
+        for i in 1..indices.len() {
+            let mut j: usize = i;
+            while j > 0 && aabbs[indices[j - 1]].0[axis] > aabbs[indices[j]].0[axis] {
+                indices.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+}
+
+impl BroadPhase for SweepAndPrune {
+    fn get_name(&self) -> &'static str {
+        "Sweep and Prune"
+    }
+
+    fn get_candidate_pairs(&mut self, aabbs: &[AABB]) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        if aabbs.len() < 2 {
+            return pairs;
+        }
+
+        let axis: usize = Self::get_sweep_axis(aabbs);
+        self.sort_indices(aabbs, axis);
+
+        let mut active: Vec<usize> = Vec::new();
+        for &idx in &self.sorted_indices {
+            let min: f64 = aabbs[idx].0[axis];
+            active.retain(|&other| aabbs[other].1[axis] >= min);
+
+            for &other in &active {
+                if aabbs_overlap(&aabbs[idx], &aabbs[other]) {
+                    pairs.push(ordered_pair(idx, other));
+                }
+            }
+            active.push(idx);
+        }
+
+        pairs.sort();
+        pairs
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+    pub cell_size: Option<f64>,
+    pub max_cells_per_body: usize,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: Option<f64>) -> SpatialHash {
+        SpatialHash {
+            cell_size,
+            max_cells_per_body: 512,
+        }
+    }
+
+    fn get_cell_size(&self, aabbs: &[AABB]) -> f64 {
+        if let Some(cell_size) = self.cell_size {
+            return cell_size;
+        }
+
+        let mut extents: Vec<f64> = aabbs
+            .iter()
+            .map(|(min, max)| (max[0] - min[0]).max(max[1] - min[1]).max(max[2] - min[2]))
+            .collect();
+        extents.sort_by(|a, b| a.total_cmp(b));
+
+        let median: f64 = extents[extents.len() / 2];
+
+        // Point-like bodies have no extent, the cell size is kept to a fraction
+        // of the scene so the cell indices stay small.
+        let mut scene_min: [f64; 3] = [f64::MAX; 3];
+        let mut scene_max: [f64; 3] = [f64::MIN; 3];
+        for (min, max) in aabbs {
+            for i in 0..3 {
+                scene_min[i] = scene_min[i].min(min[i]);
+                scene_max[i] = scene_max[i].max(max[i]);
+            }
+        }
+        let scene_extent: f64 = (0..3)
+            .map(|i| scene_max[i] - scene_min[i])
+            .fold(0.0, f64::max);
+        let min_cell_size: f64 = scene_extent / MAX_CELLS_PER_AXIS;
+
+        let cell_size: f64 = (median * 2.0).max(min_cell_size);
+        if cell_size.is_finite() && cell_size > 0.0 {
+            cell_size
+        } else {
+            1.0
+        }
+    }
+
+    fn get_cell(value: f64, cell_size: f64) -> i64 {
+        (value / cell_size).floor() as i64
+    }
+}
+
+impl BroadPhase for SpatialHash {
+    fn get_name(&self) -> &'static str {
+        "Spatial Hash"
+    }
+
+    fn get_candidate_pairs(&mut self, aabbs: &[AABB]) -> Vec<(usize, usize)> {
+        let mut pairs: HashSet<(usize, usize)> = HashSet::new();
+        if aabbs.len() < 2 {
+            return Vec::new();
+        }
+
+        let cell_size: f64 = self.get_cell_size(aabbs);
+        let mut cells: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+        let mut oversized: Vec<usize> = Vec::new();
+
+        for (idx, (min, max)) in aabbs.iter().enumerate() {
+            let min_cell: [i64; 3] = [
+                Self::get_cell(min[0], cell_size),
+                Self::get_cell(min[1], cell_size),
+                Self::get_cell(min[2], cell_size),
+            ];
+            let max_cell: [i64; 3] = [
+                Self::get_cell(max[0], cell_size),
+                Self::get_cell(max[1], cell_size),
+                Self::get_cell(max[2], cell_size),
+            ];
+
+            let cell_count: i64 = (0..3)
+                .map(|i| max_cell[i].saturating_sub(min_cell[i]).saturating_add(1))
+                .fold(1, i64::saturating_mul);
+
+            // Bodies much larger than a cell would be inserted into thousands
+            // of cells, they are tested against everything instead.
+            if cell_count > self.max_cells_per_body as i64 {
+                oversized.push(idx);
+                continue;
+            }
+
+            for x in min_cell[0]..=max_cell[0] {
+                for y in min_cell[1]..=max_cell[1] {
+                    for z in min_cell[2]..=max_cell[2] {
+                        cells.entry((x, y, z)).or_default().push(idx);
+                    }
+                }
+            }
+        }
+
+        for bucket in cells.values() {
+            for i in 0..bucket.len() {
+                for j in (i + 1)..bucket.len() {
+                    let pair: (usize, usize) = ordered_pair(bucket[i], bucket[j]);
+                    if !pairs.contains(&pair) && aabbs_overlap(&aabbs[pair.0], &aabbs[pair.1]) {
+                        pairs.insert(pair);
+                    }
+                }
+            }
+        }
+
+        for &idx in &oversized {
+            for other in 0..aabbs.len() {
+                if other == idx {
+                    continue;
+                }
+                let pair: (usize, usize) = ordered_pair(idx, other);
+                if aabbs_overlap(&aabbs[pair.0], &aabbs[pair.1]) {
+                    pairs.insert(pair);
+                }
+            }
+        }
+
+        let mut pairs: Vec<(usize, usize)> = pairs.into_iter().collect();
+        pairs.sort();
+        pairs
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BroadPhaseType {
+    AllPairs(AllPairs),
+    SweepAndPrune(SweepAndPrune),
+    SpatialHash(SpatialHash),
+}
+
+impl BroadPhaseType {
+    pub fn from_name(name: &str) -> Option<BroadPhaseType> {
+        match name.to_lowercase().as_str() {
+            "all-pairs" | "all_pairs" => Some(BroadPhaseType::AllPairs(AllPairs::new())),
+            "sweep-and-prune" | "sap" => Some(BroadPhaseType::SweepAndPrune(SweepAndPrune::new())),
+            "spatial-hash" | "grid" => Some(BroadPhaseType::SpatialHash(SpatialHash::new(None))),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> BroadPhaseType {
+        match self {
+            BroadPhaseType::AllPairs(_) => BroadPhaseType::SweepAndPrune(SweepAndPrune::new()),
+            BroadPhaseType::SweepAndPrune(_) => BroadPhaseType::SpatialHash(SpatialHash::new(None)),
+            BroadPhaseType::SpatialHash(_) => BroadPhaseType::AllPairs(AllPairs::new()),
+        }
+    }
+}
+
+impl BroadPhase for BroadPhaseType {
+    fn get_name(&self) -> &'static str {
+        match self {
+            BroadPhaseType::AllPairs(b) => b.get_name(),
+            BroadPhaseType::SweepAndPrune(b) => b.get_name(),
+            BroadPhaseType::SpatialHash(b) => b.get_name(),
+        }
+    }
+
+    fn get_candidate_pairs(&mut self, aabbs: &[AABB]) -> Vec<(usize, usize)> {
+        match self {
+            BroadPhaseType::AllPairs(b) => b.get_candidate_pairs(aabbs),
+            BroadPhaseType::SweepAndPrune(b) => b.get_candidate_pairs(aabbs),
+            BroadPhaseType::SpatialHash(b) => b.get_candidate_pairs(aabbs),
+        }
+    }
+}
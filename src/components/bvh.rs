@@ -1,12 +1,97 @@
+use crate::components::gjk;
 use crate::components::polygons::Polygon;
+use crate::components::vectors::Matrix3;
 use crate::components::vectors::Vector3D;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NarrowPhase {
+    SeparatingAxis,
+    GJK,
+}
+
+impl NarrowPhase {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            NarrowPhase::SeparatingAxis => "SAT",
+            NarrowPhase::GJK => "GJK/EPA",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<NarrowPhase> {
+        match name.to_lowercase().as_str() {
+            "sat" | "separating-axis" => Some(NarrowPhase::SeparatingAxis),
+            "gjk" | "gjk-epa" => Some(NarrowPhase::GJK),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> NarrowPhase {
+        match self {
+            NarrowPhase::SeparatingAxis => NarrowPhase::GJK,
+            NarrowPhase::GJK => NarrowPhase::SeparatingAxis,
+        }
+    }
+}
+
+// Bins per axis when searching for the cheapest split.
+const SAH_BINS: usize = 12;
+// Cost of visiting a node relative to testing one polygon.
+const TRAVERSAL_COST: f64 = 1.0;
+const MAX_LEAF_SIZE: usize = 4;
+// Refitted trees are rebuilt once their cost grows this much over the cost
+// right after building.
+const REBUILD_THRESHOLD: f64 = 1.5;
+// Node boxes are kept in the offset frame and polygons in world space, so
+// boxes are padded by this much to keep touching polygons from being culled
+// by the rounding between the two.
+const OFFSET_TOLERANCE: f64 = 1e-9;
+
+// One node of the hierarchy, stored depth first. Leaves own `count`
+// polygons of the polygon order from `start`, inner nodes have their first
+// child right after them and their second child at `start`.
+#[derive(Clone, Copy, Debug)]
+struct BVHBranch {
+    aabb: ([f64; 3], [f64; 3]),
+    parent: Option<usize>,
+    start: usize,
+    count: usize,
+}
+
+impl BVHBranch {
+    fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    pub polygon_idx: usize,
+    pub distance: f64,
+    pub position: Vector3D,
+    pub normal: Vector3D,
+}
+
+/// Bounding volume hierarchy over the polygons of a mesh, built with the
+/// surface area heuristic. The vertices and the root box are kept for the
+/// narrow phase, which works on the mesh as a whole.
+///
+/// Moving the mesh refits the node boxes bottom up instead of building the
+/// tree again. Pure translations only move the `offset` the node boxes are
+/// stored relative to.
 #[derive(Clone, Debug)]
 pub struct BVHNode {
     pub polygons: Vec<Polygon>,
     pub vertices: Vec<Vector3D>,
     pub face_normals: Vec<Vector3D>,
+    pub max_leaf_size: usize,
+    pub rebuild_threshold: f64,
     aabb: ([f64; 3], [f64; 3]),
+    tree: Vec<BVHBranch>,
+    polygon_order: Vec<usize>,
+    polygon_leaves: Vec<usize>,
+    offset: Vector3D,
+    transform: Option<(Matrix3, Vector3D)>,
+    build_cost: f64,
 }
 
 impl BVHNode {
@@ -16,12 +101,298 @@ impl BVHNode {
         let vertices: Vec<Vector3D> = vertices.to_vec();
         let face_normals: Vec<Vector3D> = polygons.iter().map(|p| p.get_normal()).collect();
 
-        BVHNode {
+        let mut bvh: BVHNode = BVHNode {
             polygons,
             vertices,
             face_normals,
+            max_leaf_size: MAX_LEAF_SIZE,
+            rebuild_threshold: REBUILD_THRESHOLD,
             aabb,
+            tree: Vec::new(),
+            polygon_order: Vec::new(),
+            polygon_leaves: Vec::new(),
+            offset: Vector3D::default(0.0),
+            transform: None,
+            build_cost: 0.0,
+        };
+        bvh.rebuild();
+        bvh
+    }
+
+    pub fn set_max_leaf_size(&mut self, max_leaf_size: usize) {
+        self.max_leaf_size = max_leaf_size.max(1);
+        self.rebuild();
+    }
+
+    /// Builds the hierarchy again from the current polygons.
+    pub fn rebuild(&mut self) {
+        let bounds: Vec<([f64; 3], [f64; 3])> =
+            self.polygons.iter().map(Self::get_polygon_aabb).collect();
+        let centroids: Vec<[f64; 3]> = bounds
+            .iter()
+            .map(|(min, max)| [0, 1, 2].map(|i| (min[i] + max[i]) / 2.0))
+            .collect();
+
+        self.tree.clear();
+        self.polygon_order = (0..self.polygons.len()).collect();
+        self.polygon_leaves = vec![0; self.polygons.len()];
+        self.offset = Vector3D::default(0.0);
+        if !self.polygons.is_empty() {
+            self.build_branch(&bounds, &centroids, None, 0, self.polygons.len());
+            self.aabb = self.tree[0].aabb;
         }
+        self.build_cost = self.get_tree_cost();
+    }
+
+    fn build_branch(
+        &mut self,
+        bounds: &[([f64; 3], [f64; 3])],
+        centroids: &[[f64; 3]],
+        parent: Option<usize>,
+        start: usize,
+        end: usize,
+    ) {
+        let aabb: ([f64; 3], [f64; 3]) = self.polygon_order[start..end]
+            .iter()
+            .fold(Self::empty_aabb(), |aabb, &idx| {
+                Self::expand_aabb(aabb, bounds[idx])
+            });
+        let node_idx: usize = self.tree.len();
+        self.tree.push(BVHBranch {
+            aabb,
+            parent,
+            start,
+            count: end - start,
+        });
+
+        let order: &mut [usize] = &mut self.polygon_order[start..end];
+        let mid: usize = match Self::get_sah_split(order, bounds, centroids, aabb) {
+            Some((axis, split)) => {
+                let (min, max): (f64, f64) = Self::get_centroid_range(order, centroids, axis);
+                let (left, right): (Vec<usize>, Vec<usize>) = order
+                    .iter()
+                    .partition(|&&idx| Self::get_bin(centroids[idx][axis], min, max) < split);
+                order[..left.len()].copy_from_slice(&left);
+                order[left.len()..].copy_from_slice(&right);
+                start + left.len()
+            }
+            // Too many polygons for a leaf even though no split pays off, so
+            // the median along the widest centroid range halves the node.
+            None if order.len() > self.max_leaf_size => {
+                let extents: Vec<f64> = (0..3)
+                    .map(|axis| {
+                        let (min, max): (f64, f64) =
+                            Self::get_centroid_range(order, centroids, axis);
+                        max - min
+                    })
+                    .collect();
+                let axis: usize = (0..3)
+                    .max_by(|&a, &b| extents[a].total_cmp(&extents[b]))
+                    .unwrap_or(0);
+                order.sort_by(|&a, &b| centroids[a][axis].total_cmp(&centroids[b][axis]));
+                start + order.len() / 2
+            }
+            None => {
+                for &idx in order.iter() {
+                    self.polygon_leaves[idx] = node_idx;
+                }
+                return;
+            }
+        };
+
+        self.build_branch(bounds, centroids, Some(node_idx), start, mid);
+        let second: usize = self.tree.len();
+        self.build_branch(bounds, centroids, Some(node_idx), mid, end);
+        self.tree[node_idx].start = second;
+        self.tree[node_idx].count = 0;
+    }
+
+    fn get_centroid_range(order: &[usize], centroids: &[[f64; 3]], axis: usize) -> (f64, f64) {
+        order.iter().fold((f64::MAX, f64::MIN), |(min, max), &idx| {
+            (min.min(centroids[idx][axis]), max.max(centroids[idx][axis]))
+        })
+    }
+
+    fn get_bin(centroid: f64, min: f64, max: f64) -> usize {
+        let bin: f64 = (centroid - min) / (max - min) * SAH_BINS as f64;
+        (bin as usize).min(SAH_BINS - 1)
+    }
+
+    // Bins the polygon centroids along every axis and returns the axis and
+    // the first bin of the second child for the cheapest split, or None if
+    // keeping the polygons in one leaf is cheaper.
+    fn get_sah_split(
+        order: &[usize],
+        bounds: &[([f64; 3], [f64; 3])],
+        centroids: &[[f64; 3]],
+        aabb: ([f64; 3], [f64; 3]),
+    ) -> Option<(usize, usize)> {
+        if order.len() < 2 {
+            return None;
+        }
+        let mut best_cost: f64 = Self::surface_area(aabb) * order.len() as f64;
+        let mut best_split: Option<(usize, usize)> = None;
+
+        for axis in 0..3 {
+            let (min, max): (f64, f64) = Self::get_centroid_range(order, centroids, axis);
+            if max <= min {
+                continue;
+            }
+            let mut bin_bounds: [([f64; 3], [f64; 3]); SAH_BINS] = [Self::empty_aabb(); SAH_BINS];
+            let mut bin_counts: [usize; SAH_BINS] = [0; SAH_BINS];
+            for &idx in order {
+                let bin: usize = Self::get_bin(centroids[idx][axis], min, max);
+                bin_bounds[bin] = Self::expand_aabb(bin_bounds[bin], bounds[idx]);
+                bin_counts[bin] += 1;
+            }
+
+            // Costs of everything right of each bin boundary.
+            let mut right_costs: [f64; SAH_BINS] = [0.0; SAH_BINS];
+            let mut right_aabb: ([f64; 3], [f64; 3]) = Self::empty_aabb();
+            let mut right_count: usize = 0;
+            for split in (1..SAH_BINS).rev() {
+                right_aabb = Self::expand_aabb(right_aabb, bin_bounds[split]);
+                right_count += bin_counts[split];
+                right_costs[split] = Self::get_branch_cost(right_aabb, right_count);
+            }
+
+            let mut left_aabb: ([f64; 3], [f64; 3]) = Self::empty_aabb();
+            let mut left_count: usize = 0;
+            for split in 1..SAH_BINS {
+                left_aabb = Self::expand_aabb(left_aabb, bin_bounds[split - 1]);
+                left_count += bin_counts[split - 1];
+                if left_count == 0 || left_count == order.len() {
+                    continue;
+                }
+                let cost: f64 = TRAVERSAL_COST * Self::surface_area(aabb)
+                    + Self::get_branch_cost(left_aabb, left_count)
+                    + right_costs[split];
+                if cost < best_cost {
+                    best_cost = cost;
+                    best_split = Some((axis, split));
+                }
+            }
+        }
+        best_split
+    }
+
+    fn get_branch_cost(aabb: ([f64; 3], [f64; 3]), count: usize) -> f64 {
+        if count == 0 {
+            return 0.0;
+        }
+        Self::surface_area(aabb) * count as f64
+    }
+
+    // Box of one node in the offset frame, from its polygons for leaves and
+    // from its children otherwise.
+    fn get_branch_aabb(&self, node_idx: usize) -> ([f64; 3], [f64; 3]) {
+        let node: &BVHBranch = &self.tree[node_idx];
+        if !node.is_leaf() {
+            return Self::expand_aabb(self.tree[node_idx + 1].aabb, self.tree[node.start].aabb);
+        }
+        let offset: Vector3D = self.offset.multiply(-1.0);
+        self.polygon_order[node.start..node.start + node.count]
+            .iter()
+            .fold(Self::empty_aabb(), |aabb, &idx| {
+                let polygon_aabb = Self::get_polygon_aabb(&self.polygons[idx]);
+                Self::expand_aabb(aabb, Self::shift_aabb(polygon_aabb, &offset))
+            })
+    }
+
+    /// Recomputes every node box from the current polygons and rebuilds the
+    /// tree if the refitted boxes overlap too much. Children are stored after
+    /// their parents, so one backwards pass is enough.
+    pub fn refit(&mut self) {
+        self.offset = Vector3D::default(0.0);
+        for node_idx in (0..self.tree.len()).rev() {
+            self.tree[node_idx].aabb = self.get_branch_aabb(node_idx);
+        }
+        if let Some(root) = self.tree.first() {
+            self.aabb = root.aabb;
+        }
+        if self.get_refit_quality() > self.rebuild_threshold {
+            self.rebuild();
+        }
+    }
+
+    /// Refits only the leaves holding the given polygons and their
+    /// ancestors, stopping at the first box that does not change.
+    pub fn refit_polygons(&mut self, polygon_indices: &[usize]) {
+        let mut leaves: Vec<usize> = polygon_indices
+            .iter()
+            .map(|&idx| self.polygon_leaves[idx])
+            .collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+
+        for leaf_idx in leaves {
+            let mut node_idx: Option<usize> = Some(leaf_idx);
+            while let Some(idx) = node_idx {
+                let aabb: ([f64; 3], [f64; 3]) = self.get_branch_aabb(idx);
+                if aabb == self.tree[idx].aabb {
+                    break;
+                }
+                self.tree[idx].aabb = aabb;
+                node_idx = self.tree[idx].parent;
+            }
+        }
+        if let Some(root) = self.tree.first() {
+            self.aabb = Self::shift_aabb(root.aabb, &self.offset);
+        }
+    }
+
+    // Surface area heuristic cost of the tree relative to its root box.
+    fn get_tree_cost(&self) -> f64 {
+        let Some(root) = self.tree.first() else {
+            return 0.0;
+        };
+        let root_area: f64 = Self::surface_area(root.aabb);
+        if root_area <= 0.0 {
+            return 0.0;
+        }
+        let cost: f64 = self
+            .tree
+            .iter()
+            .map(|node| {
+                if node.is_leaf() {
+                    Self::get_branch_cost(node.aabb, node.count)
+                } else {
+                    TRAVERSAL_COST * Self::surface_area(node.aabb)
+                }
+            })
+            .sum();
+        cost / root_area
+    }
+
+    /// Cost of the tree against its cost right after the last build, one for
+    /// a fresh tree and growing as refitted boxes start to overlap.
+    pub fn get_refit_quality(&self) -> f64 {
+        if self.build_cost > 0.0 {
+            self.get_tree_cost() / self.build_cost
+        } else {
+            1.0
+        }
+    }
+
+    pub fn get_node_count(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn get_depth(&self) -> usize {
+        let mut depth: usize = 0;
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        if !self.tree.is_empty() {
+            stack.push((0, 1));
+        }
+        while let Some((node_idx, node_depth)) = stack.pop() {
+            depth = depth.max(node_depth);
+            let node: &BVHBranch = &self.tree[node_idx];
+            if !node.is_leaf() {
+                stack.push((node_idx + 1, node_depth + 1));
+                stack.push((node.start, node_depth + 1));
+            }
+        }
+        depth
     }
 
     pub fn translate_bvh(&mut self, translation: &Vector3D) {
@@ -43,6 +414,11 @@ impl BVHNode {
         let right_aabb: [f64; 3] = right_aabb_v3d.to_array();
 
         self.aabb = (left_aabb, right_aabb);
+
+        self.offset = self.offset.add_vector(translation);
+        if let Some((_, position)) = self.transform.as_mut() {
+            *position = position.add_vector(translation);
+        }
     }
 
     pub fn rotate_bvh(&mut self, axis: &Vector3D, centroid: &Vector3D, angle: f64) {
@@ -60,13 +436,38 @@ impl BVHNode {
             *vertex = vertex.add_vector(&centroid);
         }
 
-        let mut min: Vector3D = self.vertices[0];
-        let mut max: Vector3D = self.vertices[0];
-        for vertex in self.vertices.iter() {
-            min = min.component_min(vertex);
-            max = max.component_max(vertex);
+        self.transform = None;
+        self.refit();
+    }
+
+    // Overwrites this node with a transformed copy of a local space node of
+    // the same mesh, without reallocating. The tree is only refitted if the
+    // rotation changed since the last call, otherwise the offset moves.
+    pub fn set_transformed(&mut self, local: &BVHNode, rotation: &Matrix3, translation: &Vector3D) {
+        for (polygon, local_polygon) in self.polygons.iter_mut().zip(&local.polygons) {
+            *polygon = *local_polygon;
+            polygon.transform(rotation, translation);
+        }
+
+        for (vertex, local_vertex) in self.vertices.iter_mut().zip(&local.vertices) {
+            *vertex = rotation
+                .multiply_vector(local_vertex)
+                .add_vector(translation);
         }
-        self.aabb = (min.to_array(), max.to_array());
+
+        for (normal, local_normal) in self.face_normals.iter_mut().zip(&local.face_normals) {
+            *normal = rotation.multiply_vector(local_normal);
+        }
+
+        match self.transform {
+            Some((previous_rotation, previous_translation)) if previous_rotation == *rotation => {
+                let shift: Vector3D = translation.subtract_vector(&previous_translation);
+                self.offset = self.offset.add_vector(&shift);
+                self.aabb = Self::shift_aabb(self.aabb, &shift);
+            }
+            _ => self.refit(),
+        }
+        self.transform = Some((*rotation, *translation));
     }
 
     pub fn project_onto_axis(&self, axis: &Vector3D) -> (f64, f64) {
@@ -83,9 +484,17 @@ impl BVHNode {
         (min, max)
     }
 
+    pub fn get_bounds(&self) -> ([f64; 3], [f64; 3]) {
+        self.aabb
+    }
+
     pub fn aabb_intersects(&self, other: &BVHNode) -> bool {
-        let (min_a, max_a): ([f64; 3], [f64; 3]) = self.aabb;
-        let (min_b, max_b): ([f64; 3], [f64; 3]) = other.aabb;
+        Self::aabb_overlaps(self.aabb, other.aabb)
+    }
+
+    fn aabb_overlaps(a: ([f64; 3], [f64; 3]), b: ([f64; 3], [f64; 3])) -> bool {
+        let (min_a, max_a): ([f64; 3], [f64; 3]) = a;
+        let (min_b, max_b): ([f64; 3], [f64; 3]) = b;
 
         for i in 0..3 {
             if min_a[i] > max_b[i] || min_b[i] > max_a[i] {
@@ -321,9 +730,7 @@ impl BVHNode {
             }
         }
 
-        if mtv.is_none() || contact_point.is_none() {
-            return None;
-        }
+        mtv?;
 
         let edges_a: Vec<Vector3D> = self.get_edges();
         let edges_b: Vec<Vector3D> = other.get_edges();
@@ -376,11 +783,26 @@ impl BVHNode {
             contact_point = Some(vertex_a.add_vector(&vertex_b).multiply(0.5));
         }
 
-        Some((mtv.unwrap(), contact_point.unwrap()))
+        Some((mtv.unwrap(), contact_point?))
+    }
+
+    fn gjk_intersection(&self, other: &BVHNode) -> Option<(Vector3D, Vector3D)> {
+        gjk::gjk_epa_intersection(&self.vertices, &other.vertices)
+    }
+
+    pub fn is_intersecting(
+        &self,
+        other: &BVHNode,
+        narrow_phase: NarrowPhase,
+    ) -> Option<(Vector3D, Vector3D)> {
+        match narrow_phase {
+            NarrowPhase::SeparatingAxis => self.sat_intersection(other),
+            NarrowPhase::GJK => self.gjk_intersection(other),
+        }
     }
 
-    pub fn is_intersecting(&self, other: &BVHNode) -> Option<(Vector3D, Vector3D)> {
-        self.sat_intersection(other)
+    pub fn get_gjk_distance(&self, other: &BVHNode) -> f64 {
+        gjk::gjk_distance(&self.vertices, &other.vertices)
     }
 
     pub fn get_center(&self) -> Vector3D {
@@ -537,6 +959,26 @@ impl BVHNode {
         (min_corner, max_corner)
     }
 
+    fn shift_aabb(aabb: ([f64; 3], [f64; 3]), shift: &Vector3D) -> ([f64; 3], [f64; 3]) {
+        let (min_corner, max_corner): ([f64; 3], [f64; 3]) = aabb;
+        (
+            Vector3D::from_array(min_corner)
+                .add_vector(shift)
+                .to_array(),
+            Vector3D::from_array(max_corner)
+                .add_vector(shift)
+                .to_array(),
+        )
+    }
+
+    fn pad_aabb(aabb: ([f64; 3], [f64; 3]), margin: f64) -> ([f64; 3], [f64; 3]) {
+        let (min_corner, max_corner): ([f64; 3], [f64; 3]) = aabb;
+        (
+            min_corner.map(|m| m - margin),
+            max_corner.map(|m| m + margin),
+        )
+    }
+
     fn surface_area(aabb: ([f64; 3], [f64; 3])) -> f64 {
         let (min_corner, max_corner): ([f64; 3], [f64; 3]) = aabb;
         let width: f64 = max_corner[0] - min_corner[0];
@@ -567,11 +1009,228 @@ impl BVHNode {
         t_max > t_min.max(0.0)
     }
 
+    // Entry distance of a ray into a box with the slab test, in units of the
+    // ray direction. Zero if the origin is inside the box.
+    fn get_ray_aabb_distance(
+        aabb: ([f64; 3], [f64; 3]),
+        origin: &Vector3D,
+        inv_direction: &[f64; 3],
+    ) -> Option<f64> {
+        let (min_corner, max_corner): ([f64; 3], [f64; 3]) = aabb;
+        let origin: [f64; 3] = origin.to_array();
+        let mut t_min: f64 = 0.0;
+        let mut t_max: f64 = f64::INFINITY;
+
+        for i in 0..3 {
+            let t1: f64 = (min_corner[i] - origin[i]) * inv_direction[i];
+            let t2: f64 = (max_corner[i] - origin[i]) * inv_direction[i];
+            // A NaN comes from an origin on a slab plane of a parallel ray,
+            // min and max skip it.
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+        }
+
+        if t_min <= t_max {
+            Some(t_min)
+        } else {
+            None
+        }
+    }
+
+    fn get_ray_triangle_distance(
+        triangle: [&Vector3D; 3],
+        origin: &Vector3D,
+        direction: &Vector3D,
+    ) -> Option<f64> {
+        let epsilon: f64 = 1e-9;
+        let edge1: Vector3D = triangle[1].subtract_vector(triangle[0]);
+        let edge2: Vector3D = triangle[2].subtract_vector(triangle[0]);
+
+        let h: Vector3D = direction.cross_product(&edge2);
+        let a: f64 = edge1.dot_product(&h);
+        if a.abs() < epsilon {
+            return None;
+        }
+
+        let f: f64 = 1.0 / a;
+        let s: Vector3D = origin.subtract_vector(triangle[0]);
+        let u: f64 = f * s.dot_product(&h);
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q: Vector3D = s.cross_product(&edge1);
+        let v: f64 = f * direction.dot_product(&q);
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t: f64 = f * edge2.dot_product(&q);
+        if t > epsilon {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    fn get_ray_polygon_distance(
+        polygon: &Polygon,
+        origin: &Vector3D,
+        direction: &Vector3D,
+    ) -> Option<f64> {
+        let v: &[Vector3D] = polygon.get_vertices();
+        let first: Option<f64> =
+            Self::get_ray_triangle_distance([&v[0], &v[1], &v[2]], origin, direction);
+        match polygon {
+            Polygon::Triangle(_) => first,
+            Polygon::Quad(_) => first.or_else(|| {
+                Self::get_ray_triangle_distance([&v[0], &v[2], &v[3]], origin, direction)
+            }),
+        }
+    }
+
+    /// Nearest polygon hit by the ray within the maximum distance, both in
+    /// units of the direction. Children are visited nearest first and skipped
+    /// once they start beyond the closest hit so far.
+    pub fn get_ray_hit(
+        &self,
+        origin: &Vector3D,
+        direction: &Vector3D,
+        max_distance: f64,
+    ) -> Option<RayHit> {
+        self.find_ray_hit(origin, direction, max_distance).0
+    }
+
+    /// Number of tree nodes the ray query visits, to measure the traversal cost.
+    pub fn get_ray_visit_count(
+        &self,
+        origin: &Vector3D,
+        direction: &Vector3D,
+        max_distance: f64,
+    ) -> usize {
+        self.find_ray_hit(origin, direction, max_distance).1
+    }
+
+    fn find_ray_hit(
+        &self,
+        origin: &Vector3D,
+        direction: &Vector3D,
+        max_distance: f64,
+    ) -> (Option<RayHit>, usize) {
+        // The node boxes are tested in the offset frame, the polygons in world
+        // space.
+        let tree_origin: Vector3D = origin.subtract_vector(&self.offset);
+        let inv_direction: [f64; 3] = direction.to_array().map(|d| 1.0 / d);
+        let mut max_distance: f64 = max_distance;
+        let mut nearest: Option<(usize, f64)> = None;
+        let mut stack: Vec<usize> = Vec::new();
+        if !self.tree.is_empty() {
+            stack.push(0);
+        }
+
+        let mut visit_count: usize = 0;
+
+        while let Some(node_idx) = stack.pop() {
+            visit_count += 1;
+            let node: &BVHBranch = &self.tree[node_idx];
+            if node.is_leaf() {
+                for &polygon_idx in &self.polygon_order[node.start..node.start + node.count] {
+                    let polygon: &Polygon = &self.polygons[polygon_idx];
+                    let distance = Self::get_ray_polygon_distance(polygon, origin, direction);
+                    if let Some(distance) = distance.filter(|d| *d < max_distance) {
+                        max_distance = distance;
+                        nearest = Some((polygon_idx, distance));
+                    }
+                }
+                continue;
+            }
+
+            let mut children: Vec<(usize, f64)> = [node_idx + 1, node.start]
+                .into_iter()
+                .filter_map(|child| {
+                    Self::get_ray_aabb_distance(self.tree[child].aabb, &tree_origin, &inv_direction)
+                        .filter(|d| *d <= max_distance)
+                        .map(|d| (child, d))
+                })
+                .collect();
+            children.sort_by(|a, b| b.1.total_cmp(&a.1));
+            stack.extend(children.into_iter().map(|(child, _)| child));
+        }
+
+        let hit: Option<RayHit> = nearest.map(|(polygon_idx, distance)| RayHit {
+            polygon_idx,
+            distance,
+            position: origin.add_vector(&direction.multiply(distance)),
+            normal: self.polygons[polygon_idx].get_normal(),
+        });
+        (hit, visit_count)
+    }
+
+    /// Pairs of polygon indices of both meshes whose bounding boxes overlap,
+    /// found by descending the two trees together.
+    pub fn get_overlapping_polygons(&self, other: &BVHNode) -> Vec<(usize, usize)> {
+        // Moves the boxes of the other tree into the offset frame of this one.
+        let shift: Vector3D = other.offset.subtract_vector(&self.offset);
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        if !self.tree.is_empty() && !other.tree.is_empty() {
+            stack.push((0, 0));
+        }
+
+        while let Some((a_idx, b_idx)) = stack.pop() {
+            let (a, b): (&BVHBranch, &BVHBranch) = (&self.tree[a_idx], &other.tree[b_idx]);
+            let b_aabb: ([f64; 3], [f64; 3]) = Self::shift_aabb(b.aabb, &shift);
+            if !Self::aabb_overlaps(a.aabb, Self::pad_aabb(b_aabb, OFFSET_TOLERANCE)) {
+                continue;
+            }
+
+            // Descends into the bigger inner node so both sides shrink evenly.
+            let is_a_split: bool = !a.is_leaf()
+                && (b.is_leaf() || Self::surface_area(a.aabb) >= Self::surface_area(b.aabb));
+            if is_a_split {
+                stack.push((a_idx + 1, b_idx));
+                stack.push((a.start, b_idx));
+            } else if !b.is_leaf() {
+                stack.push((a_idx, b_idx + 1));
+                stack.push((a_idx, b.start));
+            } else {
+                for &i in &self.polygon_order[a.start..a.start + a.count] {
+                    let a_aabb: ([f64; 3], [f64; 3]) = Self::get_polygon_aabb(&self.polygons[i]);
+                    for &j in &other.polygon_order[b.start..b.start + b.count] {
+                        let b_aabb: ([f64; 3], [f64; 3]) =
+                            Self::get_polygon_aabb(&other.polygons[j]);
+                        if Self::aabb_overlaps(a_aabb, b_aabb) {
+                            pairs.push((i, j));
+                        }
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Polygons of every leaf whose box the ray passes through.
     pub fn traverse(&self, origin: &Vector3D, direction: &Vector3D) -> Vec<Polygon> {
+        let tree_origin: Vector3D = origin.subtract_vector(&self.offset);
+        let inv_direction: [f64; 3] = direction.to_array().map(|d| 1.0 / d);
         let mut intersecting_polygons: Vec<Polygon> = vec![];
+        let mut stack: Vec<usize> = Vec::new();
+        if !self.tree.is_empty() {
+            stack.push(0);
+        }
 
-        if self.ray_intersect_aabb(origin, direction) {
-            intersecting_polygons.extend(self.polygons.clone());
+        while let Some(node_idx) = stack.pop() {
+            let node: &BVHBranch = &self.tree[node_idx];
+            if Self::get_ray_aabb_distance(node.aabb, &tree_origin, &inv_direction).is_none() {
+                continue;
+            }
+            if node.is_leaf() {
+                let order: &[usize] = &self.polygon_order[node.start..node.start + node.count];
+                intersecting_polygons.extend(order.iter().map(|&idx| self.polygons[idx]));
+            } else {
+                stack.push(node_idx + 1);
+                stack.push(node.start);
+            }
         }
 
         intersecting_polygons
@@ -0,0 +1,153 @@
+use crate::components::broad_phase::AABB;
+use crate::components::vectors::Vector3D;
+
+#[derive(Clone, Debug)]
+pub struct ContinuousCollision {
+    pub enabled: bool,
+    pub threshold: f64,
+    pub penetration: f64,
+    pub max_substeps: usize,
+    pub impact_count: usize,
+}
+
+impl ContinuousCollision {
+    pub fn new(enabled: bool) -> ContinuousCollision {
+        ContinuousCollision {
+            enabled,
+            threshold: 1.0,
+            penetration: 0.1,
+            max_substeps: 8,
+            impact_count: 0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold.max(0.0);
+    }
+
+    pub fn set_max_substeps(&mut self, max_substeps: usize) {
+        self.max_substeps = max_substeps;
+    }
+
+    /// A body needs swept tests once it moves further than its smallest
+    /// extent within a single step.
+    pub fn is_fast(&self, volume: &SweptVolume) -> bool {
+        let (min, max): ([f64; 3], [f64; 3]) = volume.aabb;
+        let size: f64 = (0..3)
+            .map(|i| max[i] - min[i])
+            .fold(f64::INFINITY, f64::min);
+        volume.displacement.get_length() > size * self.threshold
+    }
+
+    // Impacts are reported once the bounding volumes overlap by a fraction
+    // of the smaller body, so the discrete test at the impact finds the
+    // contact and the pair is left to it for the rest of the step.
+    pub fn get_time_of_impact(&self, a: &SweptVolume, b: &SweptVolume) -> Option<f64> {
+        let depth: f64 = self.penetration * a.radius.min(b.radius);
+        let sphere_time: f64 = a.get_sphere_time_of_impact(b, depth)?;
+        let aabb_time: f64 = a.get_aabb_time_of_impact(b, depth)?;
+        Some(sphere_time.max(aabb_time))
+    }
+}
+
+/// The bounding sphere and box of a body at the start of a step together
+/// with its displacement over the step.
+#[derive(Clone, Copy, Debug)]
+pub struct SweptVolume {
+    pub center: Vector3D,
+    pub radius: f64,
+    pub aabb: AABB,
+    pub displacement: Vector3D,
+}
+
+impl SweptVolume {
+    pub fn new(center: Vector3D, radius: f64, aabb: AABB, displacement: Vector3D) -> SweptVolume {
+        SweptVolume {
+            center,
+            radius,
+            aabb,
+            displacement,
+        }
+    }
+
+    pub fn get_swept_aabb(&self) -> AABB {
+        let (mut min, mut max): ([f64; 3], [f64; 3]) = self.aabb;
+        let displacement: [f64; 3] = self.displacement.to_array();
+        for i in 0..3 {
+            min[i] += displacement[i].min(0.0);
+            max[i] += displacement[i].max(0.0);
+        }
+        (min, max)
+    }
+
+    /// Returns the fraction of the step at which the bounding spheres first
+    /// overlap by the given depth.
+    pub fn get_sphere_time_of_impact(&self, other: &SweptVolume, depth: f64) -> Option<f64> {
+        let offset: Vector3D = self.center.subtract_vector(&other.center);
+        let motion: Vector3D = self.displacement.subtract_vector(&other.displacement);
+        let reach: f64 = (self.radius + other.radius - depth).max(0.0);
+
+        let c: f64 = offset.dot_product(&offset) - reach * reach;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let a: f64 = motion.dot_product(&motion);
+        let b: f64 = 2.0 * offset.dot_product(&motion);
+        let discriminant: f64 = b * b - 4.0 * a * c;
+        if a == 0.0 || b >= 0.0 || discriminant < 0.0 {
+            return None;
+        }
+
+        let time: f64 = (-b - discriminant.sqrt()) / (2.0 * a);
+        if time <= 1.0 {
+            Some(time.max(0.0))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the fraction of the step at which the boxes first overlap by
+    /// the given depth on every axis. The depth is capped per axis so that
+    /// thin boxes can still be reached before they are passed through.
+    pub fn get_aabb_time_of_impact(&self, other: &SweptVolume, depth: f64) -> Option<f64> {
+        let (min_a, max_a): ([f64; 3], [f64; 3]) = self.aabb;
+        let (min_b, max_b): ([f64; 3], [f64; 3]) = other.aabb;
+        let motion: [f64; 3] = self
+            .displacement
+            .subtract_vector(&other.displacement)
+            .to_array();
+
+        let mut entry: f64 = 0.0;
+        let mut exit: f64 = 1.0;
+        for i in 0..3 {
+            let width: f64 = (max_a[i] - min_a[i]) + (max_b[i] - min_b[i]);
+            let depth: f64 = depth.min(width / 2.0);
+            // The boxes overlap by the depth on this axis while
+            // lower <= motion * t <= upper.
+            let lower: f64 = min_b[i] - max_a[i] + depth;
+            let upper: f64 = max_b[i] - min_a[i] - depth;
+
+            if motion[i] == 0.0 {
+                if lower > 0.0 || upper < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+            let (start, end): (f64, f64) = if motion[i] > 0.0 {
+                (lower / motion[i], upper / motion[i])
+            } else {
+                (upper / motion[i], lower / motion[i])
+            };
+            entry = entry.max(start);
+            exit = exit.min(end);
+            if entry > exit {
+                return None;
+            }
+        }
+        Some(entry)
+    }
+}
@@ -0,0 +1,516 @@
+use crate::components::contact::ContactManifold;
+use crate::components::contact::ContactPoint;
+use crate::components::gjk;
+use crate::components::vectors::Matrix3;
+use crate::components::vectors::Vector3D;
+
+// Cross products of nearly parallel box edges are too short to give a
+// reliable separating axis.
+const PARALLEL_TOLERANCE: f64 = 1e-6;
+// Edge axes have to be this much shallower than the best face axis to be
+// used, which keeps resting boxes on their face contacts.
+const EDGE_AXIS_BIAS: f64 = 0.95;
+
+/// Analytic collision geometry, kept apart from the polygons a body is
+/// rendered with. Shapes live in the same space as the mesh carrying them
+/// and follow its transforms. Planes are solid below their normal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CollisionShape {
+    Sphere {
+        center: Vector3D,
+        radius: f64,
+    },
+    OrientedBox {
+        center: Vector3D,
+        axes: [Vector3D; 3],
+        half_extents: Vector3D,
+    },
+    Capsule {
+        start: Vector3D,
+        end: Vector3D,
+        radius: f64,
+    },
+    Plane {
+        normal: Vector3D,
+        offset: f64,
+    },
+    ConvexHull {
+        vertices: Vec<Vector3D>,
+    },
+}
+
+impl CollisionShape {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            CollisionShape::Sphere { .. } => "Sphere",
+            CollisionShape::OrientedBox { .. } => "Box",
+            CollisionShape::Capsule { .. } => "Capsule",
+            CollisionShape::Plane { .. } => "Plane",
+            CollisionShape::ConvexHull { .. } => "Convex Hull",
+        }
+    }
+
+    /// Axis aligned box with the given corners.
+    pub fn from_bounds(min: Vector3D, max: Vector3D) -> CollisionShape {
+        CollisionShape::OrientedBox {
+            center: min.get_midpoint(&max),
+            axes: [
+                Vector3D::new(1.0, 0.0, 0.0),
+                Vector3D::new(0.0, 1.0, 0.0),
+                Vector3D::new(0.0, 0.0, 1.0),
+            ],
+            half_extents: max.subtract_vector(&min).divide(2.0),
+        }
+    }
+
+    /// Plane through the point, solid on the side opposite to the normal.
+    pub fn from_plane(normal: Vector3D, point: Vector3D) -> CollisionShape {
+        let normal: Vector3D = normal.normalize();
+        CollisionShape::Plane {
+            normal,
+            offset: normal.dot_product(&point),
+        }
+    }
+
+    fn map(
+        &mut self,
+        map_point: impl Fn(&Vector3D) -> Vector3D,
+        map_direction: impl Fn(&Vector3D) -> Vector3D,
+    ) {
+        match self {
+            CollisionShape::Sphere { center, .. } => *center = map_point(center),
+            CollisionShape::OrientedBox { center, axes, .. } => {
+                *center = map_point(center);
+                *axes = axes.map(|axis| map_direction(&axis));
+            }
+            CollisionShape::Capsule { start, end, .. } => {
+                *start = map_point(start);
+                *end = map_point(end);
+            }
+            CollisionShape::Plane { normal, offset } => {
+                let point: Vector3D = map_point(&normal.multiply(*offset));
+                *normal = map_direction(normal);
+                *offset = normal.dot_product(&point);
+            }
+            CollisionShape::ConvexHull { vertices } => {
+                for vertex in vertices.iter_mut() {
+                    *vertex = map_point(vertex);
+                }
+            }
+        }
+    }
+
+    pub fn translate(&mut self, translation: &Vector3D) {
+        self.map(
+            |point| point.add_vector(translation),
+            |direction| *direction,
+        );
+    }
+
+    pub fn rotate(&mut self, axis: &Vector3D, angle: f64) {
+        let rotate = |vector: &Vector3D| vector.rotate_around_axis(axis, angle);
+        self.map(rotate, rotate);
+    }
+
+    pub fn transform(&mut self, rotation: &Matrix3, translation: &Vector3D) {
+        self.map(
+            |point| rotation.multiply_vector(point).add_vector(translation),
+            |direction| rotation.multiply_vector(direction),
+        );
+    }
+
+    /// Bounds of the shape, planes are unbounded and have none.
+    pub fn get_aabb(&self) -> Option<([f64; 3], [f64; 3])> {
+        let (points, radius): (Vec<Vector3D>, f64) = self.get_core()?;
+        let first: Vector3D = *points.first()?;
+        let (min, max): (Vector3D, Vector3D) =
+            points.iter().fold((first, first), |(min, max), point| {
+                (min.component_min(point), max.component_max(point))
+            });
+        Some((min.subtract(radius).to_array(), max.add(radius).to_array()))
+    }
+
+    fn get_box_corners(
+        center: &Vector3D,
+        axes: &[Vector3D; 3],
+        half_extents: &Vector3D,
+    ) -> Vec<Vector3D> {
+        let half: [f64; 3] = half_extents.to_array();
+        let mut corners: Vec<Vector3D> = Vec::new();
+        for x in [-1.0, 1.0] {
+            for y in [-1.0, 1.0] {
+                for z in [-1.0, 1.0] {
+                    let corner: Vector3D = center
+                        .add_vector(&axes[0].multiply(x * half[0]))
+                        .add_vector(&axes[1].multiply(y * half[1]))
+                        .add_vector(&axes[2].multiply(z * half[2]));
+                    corners.push(corner);
+                }
+            }
+        }
+        corners
+    }
+
+    // Every shape but the plane is a convex point set grown by a radius,
+    // a point for spheres and a segment for capsules.
+    fn get_core(&self) -> Option<(Vec<Vector3D>, f64)> {
+        match self {
+            CollisionShape::Sphere { center, radius } => Some((vec![*center], *radius)),
+            CollisionShape::OrientedBox {
+                center,
+                axes,
+                half_extents,
+            } => Some((Self::get_box_corners(center, axes, half_extents), 0.0)),
+            CollisionShape::Capsule { start, end, radius } => Some((vec![*start, *end], *radius)),
+            CollisionShape::Plane { .. } => None,
+            CollisionShape::ConvexHull { vertices } => Some((vertices.clone(), 0.0)),
+        }
+    }
+
+    fn get_flipped(manifold: Option<ContactManifold>) -> Option<ContactManifold> {
+        manifold.map(|mut manifold| {
+            manifold.normal = manifold.normal.negate();
+            manifold
+        })
+    }
+
+    /// Contact between the two shapes with the normal pointing from this
+    /// shape to the other one, or None when they are apart. Pairs without a
+    /// dedicated test go through GJK and EPA on their cores.
+    pub fn get_contact(&self, other: &CollisionShape) -> Option<ContactManifold> {
+        match (self, other) {
+            (
+                CollisionShape::Sphere { center, radius },
+                CollisionShape::Sphere {
+                    center: other_center,
+                    radius: other_radius,
+                },
+            ) => Self::get_sphere_contact(*center, *radius, *other_center, *other_radius),
+            (
+                CollisionShape::Sphere { center, radius },
+                CollisionShape::Capsule {
+                    start,
+                    end,
+                    radius: other_radius,
+                },
+            ) => {
+                let closest: Vector3D = Self::get_closest_on_segment(center, start, end);
+                Self::get_sphere_contact(*center, *radius, closest, *other_radius)
+            }
+            (
+                CollisionShape::Capsule { start, end, radius },
+                CollisionShape::Capsule {
+                    start: other_start,
+                    end: other_end,
+                    radius: other_radius,
+                },
+            ) => {
+                let (closest, other_closest): (Vector3D, Vector3D) =
+                    Self::get_closest_between_segments(start, end, other_start, other_end);
+                Self::get_sphere_contact(closest, *radius, other_closest, *other_radius)
+            }
+            (
+                CollisionShape::Sphere { center, radius },
+                CollisionShape::OrientedBox {
+                    center: box_center,
+                    axes,
+                    half_extents,
+                },
+            ) => Self::get_sphere_box_contact(*center, *radius, box_center, axes, half_extents),
+            (CollisionShape::OrientedBox { .. }, CollisionShape::OrientedBox { .. }) => {
+                Self::get_box_contact(self, other)
+            }
+            (CollisionShape::Plane { .. }, CollisionShape::Plane { .. }) => None,
+            (CollisionShape::Plane { normal, offset }, _) => {
+                Self::get_plane_contact(normal, *offset, other)
+            }
+            (CollisionShape::Capsule { .. }, CollisionShape::Sphere { .. })
+            | (CollisionShape::OrientedBox { .. }, CollisionShape::Sphere { .. })
+            | (_, CollisionShape::Plane { .. }) => Self::get_flipped(other.get_contact(self)),
+            _ => Self::get_core_contact(self, other),
+        }
+    }
+
+    // Two spheres, or the closest points of two cores with their radii.
+    fn get_sphere_contact(
+        center: Vector3D,
+        radius: f64,
+        other_center: Vector3D,
+        other_radius: f64,
+    ) -> Option<ContactManifold> {
+        let offset: Vector3D = other_center.subtract_vector(&center);
+        let distance: f64 = offset.get_length();
+        let depth: f64 = radius + other_radius - distance;
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let normal: Vector3D = if distance > 0.0 {
+            offset.divide(distance)
+        } else {
+            Vector3D::new(0.0, 1.0, 0.0)
+        };
+        let surface: Vector3D = center.add_vector(&normal.multiply(radius));
+        let other_surface: Vector3D = other_center.subtract_vector(&normal.multiply(other_radius));
+        let point: ContactPoint = ContactPoint::new(surface.get_midpoint(&other_surface), depth);
+        Some(ContactManifold::from_points((0, 0), normal, vec![point]))
+    }
+
+    fn get_sphere_box_contact(
+        center: Vector3D,
+        radius: f64,
+        box_center: &Vector3D,
+        axes: &[Vector3D; 3],
+        half_extents: &Vector3D,
+    ) -> Option<ContactManifold> {
+        let offset: Vector3D = center.subtract_vector(box_center);
+        let local: [f64; 3] = axes.map(|axis| axis.dot_product(&offset));
+        let half: [f64; 3] = half_extents.to_array();
+
+        if (0..3).any(|i| local[i].abs() > half[i]) {
+            let mut closest: Vector3D = *box_center;
+            for i in 0..3 {
+                closest = closest.add_vector(&axes[i].multiply(local[i].clamp(-half[i], half[i])));
+            }
+            return Self::get_sphere_contact(center, radius, closest, 0.0);
+        }
+
+        // A center inside the box is pushed out through the nearest face.
+        let get_face_distance = |i: usize| half[i] - local[i].abs();
+        let axis: usize = (0..3)
+            .min_by(|&i, &j| get_face_distance(i).total_cmp(&get_face_distance(j)))
+            .unwrap();
+        let sign: f64 = if local[axis] < 0.0 { -1.0 } else { 1.0 };
+        let face_normal: Vector3D = axes[axis].multiply(sign);
+        let face_distance: f64 = get_face_distance(axis);
+
+        let surface: Vector3D = center.subtract_vector(&face_normal.multiply(radius));
+        let face_point: Vector3D = center.add_vector(&face_normal.multiply(face_distance));
+        let point: ContactPoint =
+            ContactPoint::new(surface.get_midpoint(&face_point), radius + face_distance);
+        Some(ContactManifold::from_points(
+            (0, 0),
+            face_normal.negate(),
+            vec![point],
+        ))
+    }
+
+    // Separating axis test over the face normals of both boxes and the cross
+    // products of their edges, the faces along the shallowest axis are then
+    // clipped against each other.
+    fn get_box_contact(a: &CollisionShape, b: &CollisionShape) -> Option<ContactManifold> {
+        let (
+            CollisionShape::OrientedBox {
+                center: center_a,
+                axes: axes_a,
+                half_extents: half_a,
+            },
+            CollisionShape::OrientedBox {
+                center: center_b,
+                axes: axes_b,
+                half_extents: half_b,
+            },
+        ) = (a, b)
+        else {
+            return None;
+        };
+
+        let get_radius = |axes: &[Vector3D; 3], half: &Vector3D, axis: &Vector3D| -> f64 {
+            let half: [f64; 3] = half.to_array();
+            (0..3)
+                .map(|i| half[i] * axes[i].dot_product(axis).abs())
+                .sum()
+        };
+        let mut candidates: Vec<(Vector3D, bool)> = Vec::new();
+        candidates.extend(axes_a.iter().chain(axes_b).map(|axis| (*axis, false)));
+        for axis_a in axes_a {
+            for axis_b in axes_b {
+                candidates.push((axis_a.cross_product(axis_b), true));
+            }
+        }
+
+        let offset: Vector3D = center_b.subtract_vector(center_a);
+        let mut best: Option<(f64, f64, Vector3D)> = None;
+        for (axis, is_edge) in candidates {
+            let length: f64 = axis.get_length();
+            if length < PARALLEL_TOLERANCE {
+                continue;
+            }
+            let axis: Vector3D = axis.divide(length);
+            let distance: f64 = offset.dot_product(&axis);
+            let overlap: f64 = get_radius(axes_a, half_a, &axis)
+                + get_radius(axes_b, half_b, &axis)
+                - distance.abs();
+            if overlap <= 0.0 {
+                return None;
+            }
+
+            let score: f64 = if is_edge {
+                overlap / EDGE_AXIS_BIAS
+            } else {
+                overlap
+            };
+            if best.is_none_or(|(best_score, _, _)| score < best_score) {
+                let sign: f64 = if distance < 0.0 { -1.0 } else { 1.0 };
+                best = Some((score, overlap, axis.multiply(sign)));
+            }
+        }
+        let (_, depth, normal): (f64, f64, Vector3D) = best?;
+
+        let corners_a: Vec<Vector3D> = Self::get_box_corners(center_a, axes_a, half_a);
+        let corners_b: Vec<Vector3D> = Self::get_box_corners(center_b, axes_b, half_b);
+        let (start_a, end_a): (Vector3D, Vector3D) = Self::get_support_edge(&corners_a, &normal);
+        let (start_b, end_b): (Vector3D, Vector3D) =
+            Self::get_support_edge(&corners_b, &normal.negate());
+        let (closest_a, closest_b): (Vector3D, Vector3D) =
+            Self::get_closest_between_segments(&start_a, &end_a, &start_b, &end_b);
+
+        Some(ContactManifold::from_vertices(
+            (0, 0),
+            &corners_a,
+            &corners_b,
+            normal.multiply(depth),
+            closest_a.get_midpoint(&closest_b),
+        ))
+    }
+
+    // The first and last corners furthest along the direction, which span
+    // the edge two crossing boxes touch with.
+    fn get_support_edge(corners: &[Vector3D], direction: &Vector3D) -> (Vector3D, Vector3D) {
+        let max: f64 = corners
+            .iter()
+            .map(|corner| corner.dot_product(direction))
+            .fold(f64::NEG_INFINITY, f64::max);
+        let tolerance: f64 = PARALLEL_TOLERANCE * max.abs().max(1.0);
+        let support: Vec<&Vector3D> = corners
+            .iter()
+            .filter(|corner| corner.dot_product(direction) >= max - tolerance)
+            .collect();
+        (*support[0], *support[support.len() - 1])
+    }
+
+    // Every point of the other shape's core reaching below the plane is a
+    // contact, reduced to the widest spread by the manifold.
+    fn get_plane_contact(
+        normal: &Vector3D,
+        offset: f64,
+        other: &CollisionShape,
+    ) -> Option<ContactManifold> {
+        let (points, radius): (Vec<Vector3D>, f64) = other.get_core()?;
+        let contact_points: Vec<ContactPoint> = points
+            .iter()
+            .filter_map(|point| {
+                let depth: f64 = offset - normal.dot_product(point) + radius;
+                if depth <= 0.0 {
+                    return None;
+                }
+                let deepest: Vector3D = point.subtract_vector(&normal.multiply(radius));
+                let position: Vector3D = deepest.add_vector(&normal.multiply(depth / 2.0));
+                Some(ContactPoint::new(position, depth))
+            })
+            .collect();
+
+        if contact_points.is_empty() {
+            return None;
+        }
+        Some(ContactManifold::from_points(
+            (0, 0),
+            *normal,
+            contact_points,
+        ))
+    }
+
+    // Separated cores only touch within their radii, overlapping cores are
+    // pushed apart by EPA with the radii added to the depth.
+    fn get_core_contact(a: &CollisionShape, b: &CollisionShape) -> Option<ContactManifold> {
+        let (core_a, radius_a): (Vec<Vector3D>, f64) = a.get_core()?;
+        let (core_b, radius_b): (Vec<Vector3D>, f64) = b.get_core()?;
+
+        let Some(simplex) = gjk::gjk_intersection(&core_a, &core_b) else {
+            let (closest_a, closest_b): (Vector3D, Vector3D) =
+                gjk::gjk_closest_points(&core_a, &core_b)?;
+            return Self::get_sphere_contact(closest_a, radius_a, closest_b, radius_b);
+        };
+        let (mtv, contact_point): (Vector3D, Vector3D) =
+            gjk::epa_penetration(&core_a, &core_b, simplex)?;
+        if mtv.get_length() == 0.0 {
+            return None;
+        }
+
+        if radius_a == 0.0 && radius_b == 0.0 {
+            return Some(ContactManifold::from_vertices(
+                (0, 0),
+                &core_a,
+                &core_b,
+                mtv,
+                contact_point,
+            ));
+        }
+        let depth: f64 = mtv.get_length() + radius_a + radius_b;
+        let point: ContactPoint = ContactPoint::new(contact_point, depth);
+        Some(ContactManifold::from_points(
+            (0, 0),
+            mtv.normalize(),
+            vec![point],
+        ))
+    }
+
+    fn get_closest_on_segment(point: &Vector3D, start: &Vector3D, end: &Vector3D) -> Vector3D {
+        let segment: Vector3D = end.subtract_vector(start);
+        let length_squared: f64 = segment.get_length_squared();
+        if length_squared == 0.0 {
+            return *start;
+        }
+        let t: f64 = point.subtract_vector(start).dot_product(&segment) / length_squared;
+        start.add_vector(&segment.multiply(t.clamp(0.0, 1.0)))
+    }
+
+    // Closest points of two segments, clamping the parameter of one segment
+    // and solving for the other.
+    fn get_closest_between_segments(
+        start: &Vector3D,
+        end: &Vector3D,
+        other_start: &Vector3D,
+        other_end: &Vector3D,
+    ) -> (Vector3D, Vector3D) {
+        let direction: Vector3D = end.subtract_vector(start);
+        let other_direction: Vector3D = other_end.subtract_vector(other_start);
+        let offset: Vector3D = start.subtract_vector(other_start);
+        let length_squared: f64 = direction.get_length_squared();
+        let other_length_squared: f64 = other_direction.get_length_squared();
+        let f: f64 = other_direction.dot_product(&offset);
+
+        if length_squared == 0.0 {
+            let t: f64 = if other_length_squared > 0.0 {
+                (f / other_length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            return (*start, other_start.add_vector(&other_direction.multiply(t)));
+        }
+
+        let c: f64 = direction.dot_product(&offset);
+        let (mut s, mut t): (f64, f64) = (0.0, 0.0);
+        if other_length_squared == 0.0 {
+            s = (-c / length_squared).clamp(0.0, 1.0);
+        } else {
+            let b: f64 = direction.dot_product(&other_direction);
+            let denominator: f64 = length_squared * other_length_squared - b * b;
+            if denominator > 0.0 {
+                s = ((b * f - c * other_length_squared) / denominator).clamp(0.0, 1.0);
+            }
+            t = (b * s + f) / other_length_squared;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / length_squared).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / length_squared).clamp(0.0, 1.0);
+            }
+        }
+        (
+            start.add_vector(&direction.multiply(s)),
+            other_start.add_vector(&other_direction.multiply(t)),
+        )
+    }
+}
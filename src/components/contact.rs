@@ -0,0 +1,337 @@
+use crate::components::bvh::BVHNode;
+use crate::components::vectors::Vector3D;
+
+const MAX_CONTACT_POINTS: usize = 4;
+const FACE_TOLERANCE: f64 = 0.01;
+const MATCH_TOLERANCE: f64 = 0.05;
+
+// Tangent plane coordinates plus the offset along the contact normal.
+type PlanePoint = (f64, f64, f64);
+
+#[derive(Clone, Copy, Debug)]
+pub struct ContactPoint {
+    pub position: Vector3D,
+    pub depth: f64,
+    pub normal_impulse: f64,
+    pub tangent_impulse: Vector3D,
+}
+
+impl ContactPoint {
+    pub fn new(position: Vector3D, depth: f64) -> ContactPoint {
+        ContactPoint {
+            position,
+            depth,
+            normal_impulse: 0.0,
+            tangent_impulse: Vector3D::default(0.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ContactManifold {
+    pub mesh_pair: (usize, usize),
+    pub normal: Vector3D,
+    pub points: Vec<ContactPoint>,
+}
+
+impl ContactManifold {
+    pub fn new(
+        mesh_pair: (usize, usize),
+        self_bvh: &BVHNode,
+        target_bvh: &BVHNode,
+        mtv: Vector3D,
+        contact_point: Vector3D,
+    ) -> ContactManifold {
+        Self::from_vertices(
+            mesh_pair,
+            &self_bvh.vertices,
+            &target_bvh.vertices,
+            mtv,
+            contact_point,
+        )
+    }
+
+    /// Clips the faces of two convex vertex sets against each other along
+    /// the separation vector pointing from the first set to the second.
+    pub fn from_vertices(
+        mesh_pair: (usize, usize),
+        self_vertices: &[Vector3D],
+        target_vertices: &[Vector3D],
+        mtv: Vector3D,
+        contact_point: Vector3D,
+    ) -> ContactManifold {
+        let depth: f64 = mtv.get_length();
+        let normal: Vector3D = mtv.normalize();
+
+        // A slightly tilted body may only show a vertex or an edge towards the
+        // other one, then the other body's face serves as the reference.
+        let mut points: Vec<ContactPoint> =
+            Self::clip_faces(self_vertices, target_vertices, &normal);
+        if points.is_empty() {
+            points = Self::clip_faces(target_vertices, self_vertices, &normal.negate());
+        }
+        if points.is_empty() {
+            points.push(ContactPoint::new(contact_point, depth));
+        }
+        Self::from_points(mesh_pair, normal, points)
+    }
+
+    /// Manifold of contact points that are already known, like those of the
+    /// analytic shapes.
+    pub fn from_points(
+        mesh_pair: (usize, usize),
+        normal: Vector3D,
+        points: Vec<ContactPoint>,
+    ) -> ContactManifold {
+        ContactManifold {
+            mesh_pair,
+            normal,
+            points: Self::reduce_points(points),
+        }
+    }
+
+    pub fn get_max_depth(&self) -> f64 {
+        self.points.iter().map(|p| p.depth).fold(0.0, f64::max)
+    }
+
+    pub fn get_center(&self) -> Vector3D {
+        let mut center: Vector3D = Vector3D::default(0.0);
+        for point in &self.points {
+            center = center.add_vector(&point.position);
+        }
+        center.divide(self.points.len().max(1) as f64)
+    }
+
+    /// Carries accumulated impulses over from the previous step's manifold
+    /// for points that stayed roughly in place.
+    pub fn warm_start(&mut self, previous: &ContactManifold) {
+        if previous.normal.dot_product(&self.normal) < 1.0 - MATCH_TOLERANCE {
+            return;
+        }
+
+        let extent: f64 = self.get_extent().max(previous.get_extent());
+        let max_distance: f64 = (extent * MATCH_TOLERANCE).max(self.get_max_depth());
+
+        for point in self.points.iter_mut() {
+            let closest: Option<&ContactPoint> = previous.points.iter().min_by(|a, b| {
+                let distance_a: f64 = a.position.get_distance(&point.position);
+                let distance_b: f64 = b.position.get_distance(&point.position);
+                distance_a.total_cmp(&distance_b)
+            });
+
+            if let Some(closest) = closest {
+                if closest.position.get_distance(&point.position) <= max_distance {
+                    point.normal_impulse = closest.normal_impulse;
+                    point.tangent_impulse = closest.tangent_impulse;
+                }
+            }
+        }
+    }
+
+    fn get_extent(&self) -> f64 {
+        let center: Vector3D = self.get_center();
+        self.points
+            .iter()
+            .map(|p| p.position.get_distance(&center))
+            .fold(0.0, f64::max)
+    }
+
+    pub fn get_tangents(normal: &Vector3D) -> (Vector3D, Vector3D) {
+        let axis: Vector3D = if normal.x.abs() < 0.57 {
+            Vector3D::new(1.0, 0.0, 0.0)
+        } else {
+            Vector3D::new(0.0, 1.0, 0.0)
+        };
+        let tangent1: Vector3D = normal.cross_product(&axis).normalize();
+        let tangent2: Vector3D = normal.cross_product(&tangent1);
+        (tangent1, tangent2)
+    }
+
+    fn get_support_face(vertices: &[Vector3D], direction: &Vector3D) -> (Vec<Vector3D>, f64) {
+        let mut min: f64 = f64::MAX;
+        let mut max: f64 = f64::MIN;
+        for vertex in vertices {
+            let projection: f64 = vertex.dot_product(direction);
+            min = min.min(projection);
+            max = max.max(projection);
+        }
+
+        let tolerance: f64 = (max - min) * FACE_TOLERANCE;
+        let face: Vec<Vector3D> = vertices
+            .iter()
+            .filter(|v| v.dot_product(direction) >= max - tolerance)
+            .copied()
+            .collect();
+        (face, max)
+    }
+
+    fn get_convex_polygon(points: &[PlanePoint]) -> Vec<PlanePoint> {
+        let mut points: Vec<PlanePoint> = points.to_vec();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        points.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+
+        if points.len() < 3 {
+            return points;
+        }
+
+        let cross = |o: &PlanePoint, a: &PlanePoint, b: &PlanePoint| -> f64 {
+            (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+        };
+
+        let mut hull: Vec<PlanePoint> = Vec::new();
+        for pass in 0..2 {
+            let start: usize = hull.len();
+            let ordered: Vec<&PlanePoint> = if pass == 0 {
+                points.iter().collect()
+            } else {
+                points.iter().rev().collect()
+            };
+
+            for point in ordered {
+                while hull.len() >= start + 2
+                    && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) <= 0.0
+                {
+                    hull.pop();
+                }
+                hull.push(*point);
+            }
+            hull.pop();
+        }
+        hull
+    }
+
+    fn lerp_plane_point(a: &PlanePoint, b: &PlanePoint, t: f64) -> PlanePoint {
+        (
+            a.0 + (b.0 - a.0) * t,
+            a.1 + (b.1 - a.1) * t,
+            a.2 + (b.2 - a.2) * t,
+        )
+    }
+
+    fn clip_polygon(subject: &[PlanePoint], clip: &[PlanePoint]) -> Vec<PlanePoint> {
+        let mut output: Vec<PlanePoint> = subject.to_vec();
+
+        for i in 0..clip.len() {
+            if output.is_empty() {
+                break;
+            }
+            let edge_start: PlanePoint = clip[i];
+            let edge_end: PlanePoint = clip[(i + 1) % clip.len()];
+            let side = |p: &PlanePoint| -> f64 {
+                (edge_end.0 - edge_start.0) * (p.1 - edge_start.1)
+                    - (edge_end.1 - edge_start.1) * (p.0 - edge_start.0)
+            };
+
+            let input: Vec<PlanePoint> = output;
+            output = Vec::new();
+            for j in 0..input.len() {
+                let current: PlanePoint = input[j];
+                let previous: PlanePoint = input[(j + input.len() - 1) % input.len()];
+                let current_side: f64 = side(&current);
+                let previous_side: f64 = side(&previous);
+
+                if current_side >= 0.0 {
+                    if previous_side < 0.0 {
+                        let t: f64 = previous_side / (previous_side - current_side);
+                        output.push(Self::lerp_plane_point(&previous, &current, t));
+                    }
+                    output.push(current);
+                } else if previous_side >= 0.0 {
+                    let t: f64 = previous_side / (previous_side - current_side);
+                    output.push(Self::lerp_plane_point(&previous, &current, t));
+                }
+            }
+        }
+        output
+    }
+
+    fn clip_faces(
+        self_vertices: &[Vector3D],
+        target_vertices: &[Vector3D],
+        normal: &Vector3D,
+    ) -> Vec<ContactPoint> {
+        if normal.get_length() == 0.0 {
+            return Vec::new();
+        }
+
+        let (reference_face, reference_offset): (Vec<Vector3D>, f64) =
+            Self::get_support_face(self_vertices, normal);
+        let (incident_face, _): (Vec<Vector3D>, f64) =
+            Self::get_support_face(target_vertices, &normal.negate());
+
+        let (tangent1, tangent2): (Vector3D, Vector3D) = Self::get_tangents(normal);
+        let to_plane = |v: &Vector3D| -> PlanePoint {
+            (
+                v.dot_product(&tangent1),
+                v.dot_product(&tangent2),
+                v.dot_product(normal),
+            )
+        };
+
+        let reference_polygon: Vec<PlanePoint> =
+            Self::get_convex_polygon(&reference_face.iter().map(to_plane).collect::<Vec<_>>());
+        let incident_polygon: Vec<PlanePoint> =
+            Self::get_convex_polygon(&incident_face.iter().map(to_plane).collect::<Vec<_>>());
+
+        // A reference vertex or edge has no area to clip against, those
+        // contacts fall back to the single narrow phase point.
+        if reference_polygon.len() < 3 {
+            return Vec::new();
+        }
+
+        let mut clipped: Vec<PlanePoint> =
+            Self::clip_polygon(&incident_polygon, &reference_polygon);
+        clipped.dedup();
+        let mut points: Vec<ContactPoint> = Vec::new();
+
+        for (u, v, offset) in clipped {
+            let point_depth: f64 = reference_offset - offset;
+            if point_depth < 0.0 {
+                continue;
+            }
+            let position: Vector3D = tangent1
+                .multiply(u)
+                .add_vector(&tangent2.multiply(v))
+                .add_vector(&normal.multiply((reference_offset + offset) / 2.0));
+            points.push(ContactPoint::new(position, point_depth));
+        }
+        points
+    }
+
+    fn reduce_points(points: Vec<ContactPoint>) -> Vec<ContactPoint> {
+        if points.len() <= MAX_CONTACT_POINTS {
+            return points;
+        }
+
+        let mut selected: Vec<ContactPoint> = Vec::new();
+        let deepest: ContactPoint = *points
+            .iter()
+            .max_by(|a, b| a.depth.total_cmp(&b.depth))
+            .unwrap();
+        selected.push(deepest);
+
+        // Each further point is the one that spreads the selection the most,
+        // which keeps the manifold's area as large as possible.
+        while selected.len() < MAX_CONTACT_POINTS {
+            let mut best: Option<ContactPoint> = None;
+            let mut best_score: f64 = 0.0;
+
+            for point in &points {
+                let score: f64 = selected
+                    .iter()
+                    .map(|s| s.position.get_distance(&point.position))
+                    .fold(f64::MAX, f64::min);
+                if score > best_score {
+                    best_score = score;
+                    best = Some(*point);
+                }
+            }
+
+            match best {
+                Some(point) => selected.push(point),
+                None => break,
+            }
+        }
+        selected
+    }
+}
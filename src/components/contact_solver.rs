@@ -0,0 +1,245 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::contact::ContactManifold;
+use crate::components::joints::get_pair_mut;
+use crate::components::joints::JointFrame;
+use crate::components::physics::Physics;
+use crate::components::vectors::Vector3D;
+
+use std::collections::HashMap;
+
+// One contact point as a normal row and two friction rows, the impulses are
+// accumulated over the iterations and handed back to the manifold after.
+#[derive(Clone, Debug)]
+struct ContactConstraint {
+    pair: (usize, usize),
+    manifold_idx: usize,
+    point_idx: usize,
+    frame: JointFrame,
+    normal: Vector3D,
+    tangents: [Vector3D; 2],
+    normal_mass: f64,
+    tangent_masses: [f64; 2],
+    bias: f64,
+    friction: (f64, f64),
+    normal_impulse: f64,
+    tangent_impulses: [f64; 2],
+}
+
+impl ContactConstraint {
+    fn get_tangent_impulse(&self) -> Vector3D {
+        self.tangents[0]
+            .multiply(self.tangent_impulses[0])
+            .add_vector(&self.tangents[1].multiply(self.tangent_impulses[1]))
+    }
+
+    fn solve_friction(&mut self, a: &mut Physics, b: &mut Physics) {
+        let velocity: Vector3D = self.frame.get_relative_velocity(a, b);
+        let previous: [f64; 2] = self.tangent_impulses;
+        for i in 0..2 {
+            if self.tangent_masses[i] == 0.0 {
+                continue;
+            }
+            let speed: f64 = velocity.dot_product(&self.tangents[i]);
+            self.tangent_impulses[i] -= speed / self.tangent_masses[i];
+        }
+
+        // The friction stays static while it fits in the cone, otherwise it
+        // slides with the dynamic coefficient.
+        let (static_friction, dynamic_friction): (f64, f64) = self.friction;
+        let length: f64 = self.tangent_impulses[0].hypot(self.tangent_impulses[1]);
+        if length > static_friction * self.normal_impulse {
+            let scale: f64 = dynamic_friction * self.normal_impulse / length;
+            self.tangent_impulses = self.tangent_impulses.map(|impulse| impulse * scale);
+        }
+
+        let change: Vector3D = self.tangents[0]
+            .multiply(self.tangent_impulses[0] - previous[0])
+            .add_vector(&self.tangents[1].multiply(self.tangent_impulses[1] - previous[1]));
+        self.frame.apply_impulse(a, b, &change);
+    }
+
+    fn solve_normal(&mut self, a: &mut Physics, b: &mut Physics) {
+        if self.normal_mass == 0.0 {
+            return;
+        }
+        let speed: f64 = self
+            .frame
+            .get_relative_velocity(a, b)
+            .dot_product(&self.normal);
+        let impulse: f64 = (self.bias - speed) / self.normal_mass;
+
+        let total: f64 = (self.normal_impulse + impulse).max(0.0);
+        let impulse: f64 = total - self.normal_impulse;
+        self.normal_impulse = total;
+        self.frame
+            .apply_impulse(a, b, &self.normal.multiply(impulse));
+    }
+}
+
+/// Resolves all contacts of a step together with sequential impulses.
+/// Penetration is removed with a Baumgarte bias velocity instead of moving
+/// the bodies apart.
+#[derive(Clone, Debug)]
+pub struct ContactSolver {
+    pub iterations: usize,
+    pub bias_factor: f64,
+    pub slop: f64,
+    pub restitution_threshold: f64,
+    pub warm_starting: bool,
+    constraints: Vec<ContactConstraint>,
+}
+
+impl ContactSolver {
+    pub fn new(iterations: usize, bias_factor: f64) -> ContactSolver {
+        ContactSolver {
+            iterations,
+            bias_factor,
+            slop: 0.01,
+            restitution_threshold: 1.0,
+            warm_starting: true,
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    pub fn set_bias_factor(&mut self, bias_factor: f64) {
+        self.bias_factor = bias_factor.clamp(0.0, 1.0);
+    }
+
+    pub fn toggle_warm_starting(&mut self) {
+        self.warm_starting = !self.warm_starting;
+    }
+
+    // The slop and the restitution threshold are in body radii and radii per
+    // second of the smaller body, like the sleep thresholds.
+    fn get_constraints(
+        &self,
+        objects: &[BodyType],
+        manifolds: &HashMap<(usize, usize), Vec<ContactManifold>>,
+        is_movable: &[bool],
+        timestep: f64,
+    ) -> Vec<ContactConstraint> {
+        let mut pairs: Vec<&(usize, usize)> = manifolds.keys().collect();
+        pairs.sort();
+
+        let mut constraints: Vec<ContactConstraint> = Vec::new();
+        for &(i, j) in pairs {
+            if !is_movable[i] && !is_movable[j] {
+                continue;
+            }
+            let (a, b): (&Physics, &Physics) = (objects[i].physics(), objects[j].physics());
+            let radius: f64 = a.get_bounding_radius().min(b.get_bounding_radius());
+            let restitution: f64 = a.material.get_restitution(&b.material);
+            let friction: (f64, f64) = a.material.get_friction(&b.material);
+
+            for (manifold_idx, manifold) in manifolds[&(i, j)].iter().enumerate() {
+                let normal: Vector3D = manifold.normal;
+                if normal.get_length() == 0.0 {
+                    continue;
+                }
+                let (tangent1, tangent2): (Vector3D, Vector3D) =
+                    ContactManifold::get_tangents(&normal);
+
+                for (point_idx, point) in manifold.points.iter().enumerate() {
+                    let anchors: (Vector3D, Vector3D) = (point.position, point.position);
+                    let frame: JointFrame = JointFrame::new(
+                        a,
+                        b,
+                        anchors,
+                        (is_movable[i], is_movable[j]),
+                        self.bias_factor,
+                        timestep,
+                    );
+
+                    let penetration: f64 = (point.depth - self.slop * radius).max(0.0);
+                    let speed: f64 = frame.get_relative_velocity(a, b).dot_product(&normal);
+                    let bounce: f64 = if -speed > self.restitution_threshold * radius {
+                        -restitution * speed
+                    } else {
+                        0.0
+                    };
+
+                    let tangent_impulse: Vector3D = point.tangent_impulse;
+                    constraints.push(ContactConstraint {
+                        pair: (i, j),
+                        manifold_idx,
+                        point_idx,
+                        frame,
+                        normal,
+                        tangents: [tangent1, tangent2],
+                        normal_mass: frame.get_direction_mass(&normal),
+                        tangent_masses: [
+                            frame.get_direction_mass(&tangent1),
+                            frame.get_direction_mass(&tangent2),
+                        ],
+                        bias: (self.bias_factor * penetration / timestep).max(bounce),
+                        friction,
+                        normal_impulse: point.normal_impulse.max(0.0),
+                        tangent_impulses: [
+                            tangent_impulse.dot_product(&tangent1),
+                            tangent_impulse.dot_product(&tangent2),
+                        ],
+                    });
+                }
+            }
+        }
+        constraints
+    }
+
+    /// Gathers every contact of the step and applies the impulses carried
+    /// over from the previous step.
+    pub fn prepare(
+        &mut self,
+        objects: &mut [BodyType],
+        manifolds: &HashMap<(usize, usize), Vec<ContactManifold>>,
+        is_movable: &[bool],
+        timestep: f64,
+    ) {
+        self.constraints = self.get_constraints(objects, manifolds, is_movable, timestep);
+
+        for constraint in self.constraints.iter_mut() {
+            if !self.warm_starting {
+                constraint.normal_impulse = 0.0;
+                constraint.tangent_impulses = [0.0; 2];
+                continue;
+            }
+            let (a, b) = get_pair_mut(objects, constraint.pair.0, constraint.pair.1);
+            let impulse: Vector3D = constraint
+                .normal
+                .multiply(constraint.normal_impulse)
+                .add_vector(&constraint.get_tangent_impulse());
+            constraint.frame.apply_impulse(a, b, &impulse);
+        }
+    }
+
+    pub fn iterate(&mut self, objects: &mut [BodyType]) {
+        for constraint in self.constraints.iter_mut() {
+            let (a, b) = get_pair_mut(objects, constraint.pair.0, constraint.pair.1);
+            constraint.solve_friction(a, b);
+            constraint.solve_normal(a, b);
+        }
+    }
+
+    /// Hands the accumulated impulses back to the contact points so the next
+    /// step can start from them.
+    pub fn store_impulses(
+        &mut self,
+        manifolds: &mut HashMap<(usize, usize), Vec<ContactManifold>>,
+    ) {
+        for constraint in self.constraints.drain(..) {
+            let manifold_list: Option<&mut Vec<ContactManifold>> =
+                manifolds.get_mut(&constraint.pair);
+            let point = manifold_list
+                .and_then(|list| list.get_mut(constraint.manifold_idx))
+                .and_then(|manifold| manifold.points.get_mut(constraint.point_idx));
+            if let Some(point) = point {
+                point.normal_impulse = constraint.normal_impulse;
+                point.tangent_impulse = constraint.get_tangent_impulse();
+            }
+        }
+    }
+}
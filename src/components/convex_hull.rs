@@ -1,124 +1,409 @@
+use crate::components::color::RGBA;
+use crate::components::polygons::Mesh;
+use crate::components::polygons::Polygon;
+use crate::components::polygons::Triangle;
 use crate::components::vectors::Vector3D;
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Points closer to a plane than this fraction of the input extent count as
+// lying on it.
+const HULL_TOLERANCE: f64 = 1e-11;
+
+#[derive(Clone, Debug)]
+struct HullFace {
+    vertices: [usize; 3],
+    normal: Vector3D,
+    offset: f64,
+    outside: Vec<usize>,
+    farthest: Option<(usize, f64)>,
+    is_removed: bool,
+}
+
+impl HullFace {
+    fn new(vertices: [usize; 3], points: &[Vector3D]) -> HullFace {
+        let [a, b, c]: [Vector3D; 3] = vertices.map(|idx| points[idx]);
+        let normal: Vector3D = b
+            .subtract_vector(&a)
+            .cross_product(&c.subtract_vector(&a))
+            .normalize();
+        HullFace {
+            vertices,
+            normal,
+            offset: normal.dot_product(&a),
+            outside: Vec::new(),
+            farthest: None,
+            is_removed: false,
+        }
+    }
 
-use std::f64::EPSILON;
+    fn add_outside(&mut self, idx: usize, distance: f64) {
+        self.outside.push(idx);
+        if self
+            .farthest
+            .is_none_or(|(_, farthest)| distance > farthest)
+        {
+            self.farthest = Some((idx, distance));
+        }
+    }
+
+    fn get_distance(&self, point: &Vector3D) -> f64 {
+        self.normal.dot_product(point) - self.offset
+    }
+
+    fn get_edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c]: [usize; 3] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
 
+/// Convex hull of a point cloud in 3D. The hull grows from a tetrahedron by
+/// repeatedly adding the farthest point outside a face and replacing every
+/// face that point can see. Faces are wound counter clockwise seen from
+/// outside, like the other meshes.
 pub struct QuickHull {
     vertices: Vec<Vector3D>,
+    pub max_vertices: usize,
 }
 
 impl QuickHull {
     pub fn new(vertices: Vec<Vector3D>) -> QuickHull {
-        QuickHull { vertices }
+        QuickHull {
+            vertices,
+            max_vertices: usize::MAX,
+        }
+    }
+
+    /// Stops adding points once the hull has this many vertices, which
+    /// leaves out the points that change the shape least.
+    pub fn set_max_vertices(&mut self, max_vertices: usize) {
+        self.max_vertices = max_vertices.max(4);
     }
 
-    fn get_left_set_a(
-        &self,
-        vertices: &Vec<Vector3D>,
-        farthest_vertex: &Vector3D,
-        p1: &Vector3D,
-    ) -> Vec<Vector3D> {
-        let left_set_a: Vec<Vector3D> = vertices
+    // Drops exact duplicates, which meshes have for every shared vertex,
+    // and scales the tolerance to the size of the input.
+    fn get_unique_points(&self) -> (Vec<Vector3D>, f64) {
+        let mut seen: HashSet<[u64; 3]> = HashSet::new();
+        let points: Vec<Vector3D> = self
+            .vertices
             .iter()
-            .filter(|v| {
-                ((farthest_vertex.subtract_vector(p1)).cross_product(&(v.subtract_vector(p1)))).z
-                    > EPSILON
-            })
+            .filter(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite())
+            .filter(|v| seen.insert([v.x.to_bits(), v.y.to_bits(), v.z.to_bits()]))
             .copied()
             .collect();
-        left_set_a
+
+        let extent: f64 = match points.first() {
+            Some(first) => {
+                let (min, max): (Vector3D, Vector3D) =
+                    points.iter().fold((*first, *first), |(min, max), v| {
+                        (min.component_min(v), max.component_max(v))
+                    });
+                max.subtract_vector(&min).get_length()
+            }
+            None => 0.0,
+        };
+        (points, extent * HULL_TOLERANCE)
+    }
+
+    fn get_farthest(points: &[Vector3D], get_distance: impl Fn(&Vector3D) -> f64) -> (usize, f64) {
+        points
+            .iter()
+            .map(get_distance)
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0))
+    }
+
+    // The two farthest apart of the axis extremes, the point farthest from
+    // their line and the point farthest from that plane. Inputs without
+    // volume stop early with the points found so far.
+    fn get_initial_points(points: &[Vector3D], epsilon: f64) -> Vec<usize> {
+        let mut extremes: Vec<usize> = Vec::new();
+        for axis in 0..3 {
+            let get_coordinate = |v: &Vector3D| v.to_array()[axis];
+            extremes.push(Self::get_farthest(points, get_coordinate).0);
+            extremes.push(Self::get_farthest(points, |v| -get_coordinate(v)).0);
+        }
+
+        let mut a: usize = 0;
+        let mut b: usize = 0;
+        let mut max_distance: f64 = 0.0;
+        for &i in &extremes {
+            for &j in &extremes {
+                let distance: f64 = points[i].get_distance(&points[j]);
+                if distance > max_distance {
+                    (a, b, max_distance) = (i, j, distance);
+                }
+            }
+        }
+        if max_distance <= epsilon {
+            return vec![a];
+        }
+
+        let line: Vector3D = points[b].subtract_vector(&points[a]).normalize();
+        let (c, distance): (usize, f64) = Self::get_farthest(points, |v| {
+            v.subtract_vector(&points[a])
+                .cross_product(&line)
+                .get_length()
+        });
+        if distance <= epsilon {
+            return vec![a, b];
+        }
+
+        let plane: HullFace = HullFace::new([a, b, c], points);
+        let (d, distance): (usize, f64) =
+            Self::get_farthest(points, |v| plane.get_distance(v).abs());
+        if distance <= epsilon {
+            return vec![a, b, c];
+        }
+        vec![a, b, c, d]
     }
 
-    fn get_left_set_b(
-        &self,
-        vertices: &Vec<Vector3D>,
-        farthest_vertex: &Vector3D,
-        p2: &Vector3D,
-    ) -> Vec<Vector3D> {
-        let left_set_b: Vec<Vector3D> = vertices
+    // Hull of points lying in one plane as a flat shape with a face on
+    // both sides, so the mesh stays closed.
+    fn get_planar_faces(points: &[Vector3D], initial: &[usize], epsilon: f64) -> Vec<[usize; 3]> {
+        let plane: HullFace = HullFace::new([initial[0], initial[1], initial[2]], points);
+        let origin: Vector3D = points[initial[0]];
+        let u: Vector3D = points[initial[1]].subtract_vector(&origin).normalize();
+        let v: Vector3D = plane.normal.cross_product(&u);
+        let projected: Vec<(f64, f64)> = points
             .iter()
-            .filter(|v| {
-                ((p2.subtract_vector(farthest_vertex))
-                    .cross_product(&(v.subtract_vector(farthest_vertex))))
-                .z > EPSILON
+            .map(|p| {
+                let offset: Vector3D = p.subtract_vector(&origin);
+                (offset.dot_product(&u), offset.dot_product(&v))
             })
-            .copied()
             .collect();
-        left_set_b
+
+        // Monotone chain, counter clockwise around the plane normal.
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by(|&i, &j| {
+            let (a, b): ((f64, f64), (f64, f64)) = (projected[i], projected[j]);
+            a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+        });
+        // Distance of the middle point left of the line between the others,
+        // points on the line are dropped.
+        let turn = |o: usize, a: usize, b: usize| -> f64 {
+            let (o, a, b) = (projected[o], projected[a], projected[b]);
+            let cross: f64 = (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+            -cross / (b.0 - o.0).hypot(b.1 - o.1)
+        };
+        let mut outline: Vec<usize> = Vec::new();
+        for pass in [order.clone(), order.into_iter().rev().collect()] {
+            let start: usize = outline.len();
+            for idx in pass {
+                while outline.len() >= start + 2
+                    && turn(outline[outline.len() - 2], outline[outline.len() - 1], idx) <= epsilon
+                {
+                    outline.pop();
+                }
+                outline.push(idx);
+            }
+            outline.pop();
+        }
+
+        // The back is fanned from the next corner, so it shares no diagonal
+        // with the front and every edge has exactly one twin.
+        let count: usize = outline.len();
+        let mut faces: Vec<[usize; 3]> = Vec::new();
+        for i in 1..count.saturating_sub(1) {
+            faces.push([outline[0], outline[i], outline[i + 1]]);
+        }
+        for i in 2..count {
+            faces.push([outline[1], outline[(i + 1) % count], outline[i]]);
+        }
+        faces
     }
 
-    fn recursive_hull(
-        &self,
-        vertices: &Vec<Vector3D>,
-        p1: &Vector3D,
-        p2: &Vector3D,
-        hull: &mut Vec<Vector3D>,
-    ) {
-        if vertices.is_empty() {
-            return;
+    fn get_faces(&self) -> (Vec<Vector3D>, Vec<[usize; 3]>) {
+        let (points, epsilon): (Vec<Vector3D>, f64) = self.get_unique_points();
+        let initial: Vec<usize> = Self::get_initial_points(&points, epsilon);
+        match initial.len() {
+            0..=2 => return (points, Vec::new()),
+            3 => {
+                let faces: Vec<[usize; 3]> = Self::get_planar_faces(&points, &initial, epsilon);
+                return (points, faces);
+            }
+            _ => {}
         }
 
-        let mut farthest_vertex: &Vector3D = &vertices[0];
-        let mut max_distance: f64 = 0.0;
+        // Each tetrahedron face is wound away from the opposite corner.
+        let mut faces: Vec<HullFace> = Vec::new();
+        for k in 0..4 {
+            let corners: Vec<usize> = (0..4).filter(|&i| i != k).map(|i| initial[i]).collect();
+            let mut face: HullFace = HullFace::new([corners[0], corners[1], corners[2]], &points);
+            if face.get_distance(&points[initial[k]]) > 0.0 {
+                face = HullFace::new([corners[0], corners[2], corners[1]], &points);
+            }
+            faces.push(face);
+        }
+        for idx in (0..points.len()).filter(|idx| !initial.contains(idx)) {
+            for face in faces.iter_mut() {
+                let distance: f64 = face.get_distance(&points[idx]);
+                if distance > epsilon {
+                    face.add_outside(idx, distance);
+                    break;
+                }
+            }
+        }
 
-        for vertex in vertices {
-            let base: Vector3D = p2.subtract_vector(p1);
-            let cross_product: Vector3D = base.cross_product(&(vertex.subtract_vector(p1)));
-            let distance: f64 = cross_product.get_length() / base.get_length();
-            if distance > max_distance {
-                farthest_vertex = vertex;
-                max_distance = distance;
+        // The farthest outside point of all faces goes in first, so a vertex
+        // limit keeps the points that add the most volume.
+        let mut vertex_count: usize = 4;
+        while vertex_count < self.max_vertices {
+            let Some((eye, _)): Option<(usize, f64)> = faces
+                .iter()
+                .filter(|face| !face.is_removed)
+                .filter_map(|face| face.farthest)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                break;
+            };
+            let eye_point: Vector3D = points[eye];
+            vertex_count += 1;
+
+            // The edges of the visible faces without a visible twin form the
+            // horizon, which is joined to the eye point with new faces.
+            let visible: Vec<usize> = (0..faces.len())
+                .filter(|&i| !faces[i].is_removed && faces[i].get_distance(&eye_point) > epsilon)
+                .collect();
+            let edges: Vec<(usize, usize)> =
+                visible.iter().flat_map(|&i| faces[i].get_edges()).collect();
+            let edge_set: HashSet<(usize, usize)> = edges.iter().copied().collect();
+
+            let mut orphans: Vec<usize> = Vec::new();
+            for &i in &visible {
+                faces[i].is_removed = true;
+                orphans.append(&mut faces[i].outside);
+            }
+
+            let first_new: usize = faces.len();
+            for &(a, b) in edges.iter().filter(|(a, b)| !edge_set.contains(&(*b, *a))) {
+                faces.push(HullFace::new([a, b, eye], &points));
+            }
+
+            // Orphans mostly belong to the new faces, but a point can also be
+            // outside an old face it was never assigned to.
+            for idx in orphans.into_iter().filter(|&idx| idx != eye) {
+                let is_outside = |face: &&mut HullFace| -> bool {
+                    !face.is_removed && face.get_distance(&points[idx]) > epsilon
+                };
+                let (old_faces, new_faces) = faces.split_at_mut(first_new);
+                let face: Option<&mut HullFace> = new_faces
+                    .iter_mut()
+                    .find(is_outside)
+                    .or_else(|| old_faces.iter_mut().find(is_outside));
+                if let Some(face) = face {
+                    let distance: f64 = face.get_distance(&points[idx]);
+                    face.add_outside(idx, distance);
+                }
             }
         }
-        hull.push(*farthest_vertex);
 
-        let left_set_a: Vec<Vector3D> = self.get_left_set_a(vertices, farthest_vertex, p1);
-        self.recursive_hull(&left_set_a, p1, farthest_vertex, hull);
-        let left_set_b: Vec<Vector3D> = self.get_left_set_b(vertices, farthest_vertex, p2);
-        self.recursive_hull(&left_set_b, farthest_vertex, p2, hull);
+        let faces: Vec<[usize; 3]> = faces
+            .iter()
+            .filter(|face| !face.is_removed)
+            .map(|face| face.vertices)
+            .collect();
+        (points, faces)
     }
 
+    /// The points on the hull, in the order the faces first use them.
     pub fn get_hull(&self) -> Vec<Vector3D> {
+        let (points, faces): (Vec<Vector3D>, Vec<[usize; 3]>) = self.get_faces();
+        let mut is_used: Vec<bool> = vec![false; points.len()];
         let mut hull: Vec<Vector3D> = Vec::new();
-        let vertices = &self.vertices;
-        let min_vertex = vertices
-            .iter()
-            .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
-            .unwrap();
+        for idx in faces.iter().flatten() {
+            if !is_used[*idx] {
+                is_used[*idx] = true;
+                hull.push(points[*idx]);
+            }
+        }
+        hull
+    }
 
-        let max_vertex = vertices
+    /// Outward normal and offset of every hull face, a point is inside the
+    /// hull when it is below all of them.
+    pub fn get_planes(&self) -> Vec<(Vector3D, f64)> {
+        let (points, faces): (Vec<Vector3D>, Vec<[usize; 3]>) = self.get_faces();
+        faces
             .iter()
-            .max_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
-            .unwrap();
+            .map(|&vertices| {
+                let face: HullFace = HullFace::new(vertices, &points);
+                (face.normal, face.offset)
+            })
+            .collect()
+    }
 
-        let left_set: Vec<Vector3D> = vertices
+    /// Closed triangle mesh of the hull, or None for points that do not span
+    /// a plane. Coplanar points give a flat hull with faces on both sides.
+    /// The mesh keeps the hull edges, so they can be drawn without building
+    /// the hull again.
+    pub fn get_mesh(&self) -> Option<Mesh> {
+        let (points, faces): (Vec<Vector3D>, Vec<[usize; 3]>) = self.get_faces();
+        if faces.is_empty() {
+            return None;
+        }
+        let color: RGBA = RGBA::from_rgb(1.0, 1.0, 1.0);
+        let shader: RGBA = RGBA::from_rgb(0.5, 0.5, 0.5);
+        let polygons: Vec<Polygon> = faces
             .iter()
-            .filter(|v| {
-                ((max_vertex.subtract_vector(min_vertex))
-                    .cross_product(&(v.subtract_vector(min_vertex))))
-                .z > EPSILON
+            .map(|&[a, b, c]| {
+                let vertices: [Vector3D; 3] = [points[a], points[b], points[c]];
+                Polygon::Triangle(Triangle::new(vertices, (a, b, c), shader, color))
             })
-            .copied()
             .collect();
+        let mut mesh: Mesh = Mesh::new(polygons);
+        mesh.hull_edges = Self::get_edge_corners(&points, &faces);
+        Some(mesh)
+    }
+
+    /// Edges between faces that are not coplanar, which leaves out the
+    /// diagonals splitting flat hull faces into triangles.
+    pub fn get_edges(&self) -> Vec<(Vector3D, Vector3D)> {
+        let (points, faces): (Vec<Vector3D>, Vec<[usize; 3]>) = self.get_faces();
+        let get_point = |corner: usize| -> Vector3D { points[faces[corner / 3][corner % 3]] };
+        Self::get_edge_corners(&points, &faces)
+            .into_iter()
+            .map(|(a, b)| (get_point(a), get_point(b)))
+            .collect()
+    }
 
-        let right_set: Vec<Vector3D> = vertices
+    // The edges as pairs of face corners, numbered three per face in face
+    // order like the vertices of the hull mesh.
+    fn get_edge_corners(points: &[Vector3D], faces: &[[usize; 3]]) -> Vec<(usize, usize)> {
+        let normals: Vec<Vector3D> = faces
             .iter()
-            .filter(|v| {
-                ((min_vertex.subtract_vector(max_vertex))
-                    .cross_product(&(v.subtract_vector(max_vertex))))
-                .z > EPSILON
-            })
-            .copied()
+            .map(|&vertices| HullFace::new(vertices, points).normal)
             .collect();
+        let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (face_idx, face) in faces.iter().enumerate() {
+            for i in 0..3 {
+                let edge: (usize, usize) = (face[i], face[(i + 1) % 3]);
+                edge_faces.entry(edge).or_default().push(face_idx);
+            }
+        }
 
-        hull.push(*min_vertex);
-        self.recursive_hull(&left_set, min_vertex, max_vertex, &mut hull);
-        hull.push(*max_vertex);
-        self.recursive_hull(&right_set, max_vertex, min_vertex, &mut hull);
-        hull
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut seen: HashSet<(usize, usize)> = HashSet::new();
+        for (face_idx, face) in faces.iter().enumerate() {
+            for i in 0..3 {
+                let (a, b): (usize, usize) = (face[i], face[(i + 1) % 3]);
+                let is_flat: bool = edge_faces.get(&(b, a)).is_some_and(|twins| {
+                    twins
+                        .iter()
+                        .any(|&twin| normals[twin].dot_product(&normals[face_idx]) > 1.0 - 1e-9)
+                });
+                if !is_flat && seen.insert((a.min(b), a.max(b))) {
+                    edges.push((3 * face_idx + i, 3 * face_idx + (i + 1) % 3));
+                }
+            }
+        }
+        edges
     }
 }
 
+/// Hull of points in the xy plane, merged from gift wrapped chunks.
 pub struct ChansHull {
     vertices: Vec<Vector3D>,
     m: usize,
@@ -261,213 +546,3 @@ impl ChansHull {
         hulls.into_iter().next().unwrap_or_else(Vec::new)
     }
 }
-
-// pub struct QuickHull {
-//     vertices: Vec<Vector3D>,
-// }
-
-// impl QuickHull {
-//     pub fn new(vertices: Vec<Vector3D>) -> QuickHull {
-//         QuickHull { vertices }
-//     }
-
-//     fn area_of_triangle(&self, a: &Vector3D, b: &Vector3D, c: &Vector3D) -> f64 {
-//         let ab: Vector3D = b.subtract_vector(a);
-//         let ac: Vector3D = c.subtract_vector(a);
-//         let cross_product: Vector3D = ab.cross_product(&ac);
-//         let area: f64 = cross_product.get_length() * 0.5;
-//         area
-//     }
-
-//     fn tetrahedron_volume(&self, a: &Vector3D, b: &Vector3D, c: &Vector3D, d: &Vector3D) -> f64 {
-//         let ab: Vector3D = b.subtract_vector(a);
-//         let ac: Vector3D = c.subtract_vector(a);
-//         let ad: Vector3D = d.subtract_vector(a);
-//         let cross_product: Vector3D = ab.cross_product(&ac);
-//         let volume: f64 = cross_product.dot_product(&ad).abs() / 6.0;
-//         volume
-//     }
-
-//     fn get_extreme_vertices(&self) -> (Vector3D, Vector3D) {
-//         let min_x: Vector3D = self
-//             .vertices
-//             .iter()
-//             .cloned()
-//             .min_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
-//             .unwrap();
-//         let max_x: Vector3D = self
-//             .vertices
-//             .iter()
-//             .cloned()
-//             .max_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal))
-//             .unwrap();
-//         (min_x, max_x)
-//     }
-
-//     fn initial_tetrahedron(&self) -> (Vector3D, Vector3D, Vector3D, Vector3D) {
-//         let (min_x, max_x): (Vector3D, Vector3D) = self.get_extreme_vertices();
-//         let mut max_distance: f64 = 0.0;
-//         let mut initial_vertex_a: Vector3D = min_x;
-//         let initial_vertex_b: Vector3D = max_x;
-
-//         for vertex in self.vertices.iter() {
-//             let distance =
-//                 vertex.get_distance(&initial_vertex_a) + vertex.get_distance(&initial_vertex_b);
-//             if distance > max_distance {
-//                 max_distance = distance;
-//                 initial_vertex_a = *vertex;
-//             }
-//         }
-
-//         let mut max_area: f64 = 0.0;
-//         let mut initial_vertex_c: Vector3D = min_x;
-//         for vertex in self.vertices.iter() {
-//             let area: f64 = self.area_of_triangle(&initial_vertex_a, &initial_vertex_b, vertex);
-//             if area > max_area {
-//                 max_area = area;
-//                 initial_vertex_c = *vertex;
-//             }
-//         }
-
-//         let mut max_volume: f64 = 0.0;
-//         let mut initial_vertex_d: Vector3D = min_x;
-//         for vertex in self.vertices.iter() {
-//             let volume: f64 = self.tetrahedron_volume(
-//                 &initial_vertex_a,
-//                 &initial_vertex_b,
-//                 &initial_vertex_c,
-//                 vertex,
-//             );
-//             if volume > max_volume {
-//                 max_volume = volume;
-//                 initial_vertex_d = *vertex;
-//             }
-//         }
-
-//         (
-//             initial_vertex_a,
-//             initial_vertex_b,
-//             initial_vertex_c,
-//             initial_vertex_d,
-//         )
-//     }
-
-//     fn find_furthest_point_on_mesh(
-//         &self,
-//         plane_normal: &Vector3D,
-//         plane_point: &Vector3D,
-//     ) -> Vector3D {
-//         let mut max_distance: f64 = 0.0;
-//         let mut furthest_point: Vector3D = plane_point.clone();
-//         for vertex in self.vertices.iter() {
-//             let distance: f64 = plane_normal.dot_product(&(vertex.subtract_vector(plane_point)));
-//             if distance > max_distance {
-//                 max_distance = distance;
-//                 furthest_point = *vertex;
-//             }
-//         }
-//         furthest_point
-//     }
-//     fn quick_hull_recursive(
-//         &self,
-//         vertices: &HashSet<Vector3D>,
-//         plane_normal: &Vector3D,
-//         plane_point: &Vector3D,
-//     ) -> Vec<Vector3D> {
-//         if vertices.is_empty() {
-//             return vec![];
-//         }
-
-//         let furthest_point: Vector3D = self.find_furthest_point_on_mesh(plane_normal, plane_point);
-//         let mut new_vertices: HashSet<Vector3D> = vertices.clone();
-//         new_vertices.remove(&furthest_point);
-
-//         let mut new_hull = vec![furthest_point];
-//         for i in 0..new_hull.len() {
-//             let a: Vector3D = new_hull[i];
-//             for j in (i + 1)..new_hull.len() {
-//                 let b: Vector3D = new_hull[j];
-//                 let ab: Vector3D = b.subtract_vector(&a);
-//                 let mut max_distance: f64 = 0.0;
-//                 let mut furthest_vertex: Vector3D = a;
-//                 for vertex in &new_vertices {
-//                     let distance: f64 =
-//                         ab.cross_product(&(vertex.subtract_vector(&a))).get_length();
-//                     if distance > max_distance {
-//                         max_distance = distance;
-//                         furthest_vertex = *vertex;
-//                     }
-//                 }
-//                 if furthest_vertex != a {
-//                     let face_normal: Vector3D = ab
-//                         .cross_product(&(furthest_vertex.subtract_vector(&a)))
-//                         .normalize();
-//                     let face_vertices: HashSet<Vector3D> = new_vertices
-//                         .iter()
-//                         .cloned()
-//                         .filter(|vertex| {
-//                             face_normal.dot_product(&(vertex.subtract_vector(&a))) >= 0.0
-//                         })
-//                         .collect::<HashSet<_>>();
-//                     let sub_hull: Vec<Vector3D> =
-//                         self.quick_hull_recursive(&face_vertices, &face_normal, &a);
-//                     new_hull.extend(sub_hull);
-//                     new_vertices = new_vertices.difference(&face_vertices).cloned().collect();
-//                 }
-//             }
-//         }
-
-//         new_hull
-//     }
-
-//     pub fn get_hull(&self) -> Vec<Vector3D> {
-//         let unique_vertices: HashSet<Vector3D> = self.vertices.iter().cloned().collect();
-//         let n: usize = unique_vertices.len();
-//         if n <= 4 {
-//             return unique_vertices.into_iter().collect();
-//         }
-
-//         let (a, b, c, d) = self.initial_tetrahedron();
-//         let mut vertices: HashSet<Vector3D> = unique_vertices.clone();
-//         vertices.remove(&a);
-//         vertices.remove(&b);
-//         vertices.remove(&c);
-//         vertices.remove(&d);
-
-//         let mut hull = vec![a, b, c, d];
-//         for i in 0..hull.len() {
-//             let a: Vector3D = hull[i];
-//             for j in (i + 1)..hull.len() {
-//                 let b: Vector3D = hull[j];
-//                 let ab: Vector3D = b.subtract_vector(&a);
-//                 let mut max_distance: f64 = 0.0;
-//                 let mut furthest_vertex = a;
-//                 for vertex in &vertices {
-//                     let distance: f64 =
-//                         ab.cross_product(&(vertex.subtract_vector(&a))).get_length();
-//                     if distance > max_distance {
-//                         max_distance = distance;
-//                         furthest_vertex = *vertex;
-//                     }
-//                 }
-//                 if furthest_vertex != a {
-//                     let face_normal = ab
-//                         .cross_product(&(furthest_vertex.subtract_vector(&a)))
-//                         .normalize();
-//                     let face_vertices = vertices
-//                         .iter()
-//                         .cloned()
-//                         .filter(|vertex| {
-//                             face_normal.dot_product(&(vertex.subtract_vector(&a))) >= 0.0
-//                         })
-//                         .collect::<HashSet<_>>();
-//                     let sub_hull = self.quick_hull_recursive(&face_vertices, &face_normal, &a);
-//                     hull.extend(sub_hull);
-//                     vertices = vertices.difference(&face_vertices).cloned().collect();
-//                 }
-//             }
-//         }
-
-//         hull
-//     }
-// }
@@ -1,101 +1,325 @@
+use crate::components::collision_shape::CollisionShape;
 use crate::components::color::RGBA;
+use crate::components::convex_hull::QuickHull;
 use crate::components::polygons::Mesh;
 use crate::components::polygons::Polygon;
 use crate::components::vectors::Vector3D;
 
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fs::read_to_string;
+use std::fs::write;
+
+// 64 bit FNV-1a, written out so cache keys stay the same across Rust
+// versions and platforms.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Convex collision hulls of a mesh together with the deepest distance of
+/// the mesh surface below its hull, relative to the bounding box diagonal.
+#[derive(Clone, Debug)]
+pub struct ConvexDecomposition {
+    pub hulls: Vec<Mesh>,
+    pub concavity: f64,
+}
+
+// A part of the mesh with the deepest point of its surface inside its hull.
+#[derive(Clone, Debug)]
+struct Piece {
+    polygons: Vec<Polygon>,
+    concavity: f64,
+    deepest: Vector3D,
+    is_final: bool,
+}
+
+impl Piece {
+    fn new(polygons: Vec<Polygon>) -> Piece {
+        let vertices: Vec<Vector3D> = MeshDecompose::get_unique_vertices(&polygons);
+        let planes: Vec<(Vector3D, f64)> = QuickHull::new(vertices.clone()).get_planes();
+
+        // Surface points that lie on the hull have no depth, concave parts
+        // of the surface sink below its faces.
+        let mut concavity: f64 = 0.0;
+        let mut deepest: Vector3D = vertices.first().copied().unwrap_or(Vector3D::default(0.0));
+        if !planes.is_empty() {
+            let centroids = polygons.iter().map(|polygon| polygon.get_centroid());
+            for point in vertices.iter().copied().chain(centroids) {
+                let depth: f64 = planes
+                    .iter()
+                    .map(|(normal, offset)| offset - normal.dot_product(&point))
+                    .fold(f64::INFINITY, f64::min);
+                if depth > concavity {
+                    concavity = depth;
+                    deepest = point;
+                }
+            }
+        }
+
+        Piece {
+            polygons,
+            concavity,
+            deepest,
+            is_final: false,
+        }
+    }
+}
+
+/// Approximate convex decomposition in the style of V-HACD. The most
+/// concave piece is cut by the plane that leaves the least concavity on
+/// both sides until every piece is nearly convex or the hull budget is used.
 #[derive(Clone, Debug)]
 pub struct MeshDecompose {
-    tolerance: f64,
+    pub concavity: f64,
+    pub max_hulls: usize,
+    pub max_vertices_per_hull: usize,
+    pub plane_resolution: usize,
 }
 
 impl MeshDecompose {
-    pub fn new(tolerance: f64) -> Self {
-        Self { tolerance }
+    /// The concavity is the allowed depth of the surface below a hull as a
+    /// fraction of the bounding box diagonal of the mesh.
+    pub fn new(concavity: f64) -> Self {
+        Self {
+            concavity,
+            max_hulls: 32,
+            max_vertices_per_hull: 64,
+            plane_resolution: 8,
+        }
+    }
+
+    pub fn set_max_hulls(&mut self, max_hulls: usize) {
+        self.max_hulls = max_hulls.max(1);
+    }
+
+    pub fn set_max_vertices_per_hull(&mut self, max_vertices_per_hull: usize) {
+        self.max_vertices_per_hull = max_vertices_per_hull.max(4);
     }
 
+    pub fn set_plane_resolution(&mut self, plane_resolution: usize) {
+        self.plane_resolution = plane_resolution.max(1);
+    }
+
+    /// The convex collision hulls of the mesh.
     pub fn decompose(&self, mesh: &Mesh) -> Vec<Mesh> {
-        let mut result: Vec<Mesh> = Vec::new();
+        self.get_decomposition(mesh).hulls
+    }
+
+    pub fn get_decomposition(&self, mesh: &Mesh) -> ConvexDecomposition {
+        let diagonal: f64 = Self::get_diagonal(mesh);
+        let threshold: f64 = self.concavity * diagonal;
+        let mut pieces: Vec<Piece> = vec![Piece::new(mesh.polygons.clone())];
 
-        if self.is_convex(&mesh) {
-            result.push(mesh.clone());
-            return result;
+        while pieces.len() < self.max_hulls {
+            let worst: Option<usize> = (0..pieces.len())
+                .filter(|&idx| !pieces[idx].is_final && pieces[idx].concavity > threshold)
+                .max_by(|&a, &b| pieces[a].concavity.total_cmp(&pieces[b].concavity));
+            let Some(worst) = worst else {
+                break;
+            };
+            match self.get_best_split(&pieces[worst]) {
+                Some((left, right)) => {
+                    pieces[worst] = left;
+                    pieces.push(right);
+                }
+                None => pieces[worst].is_final = true,
+            }
         }
 
-        let (left, right): (Option<Mesh>, Option<Mesh>) = self.split_mesh(mesh);
+        let concavity: f64 = pieces
+            .iter()
+            .map(|piece| piece.concavity)
+            .fold(0.0, f64::max);
+        let hulls: Vec<Mesh> = pieces
+            .iter()
+            .filter_map(|piece| {
+                let vertices: Vec<Vector3D> = Self::get_unique_vertices(&piece.polygons);
+                self.get_hull(vertices)
+            })
+            .collect();
+        ConvexDecomposition {
+            hulls,
+            concavity: if diagonal > 0.0 {
+                concavity / diagonal
+            } else {
+                0.0
+            },
+        }
+    }
 
-        if let Some(left_mesh) = left {
-            result.append(&mut self.decompose(&left_mesh));
+    /// Reads the hulls from the cache file when it was written for the same
+    /// mesh and settings, otherwise decomposes the mesh and rewrites it.
+    pub fn get_cached_decomposition(&self, mesh: &Mesh, cache_path: &str) -> ConvexDecomposition {
+        let key: String = self.get_cache_key(mesh);
+        if let Some(decomposition) = self.read_cache(cache_path, &key) {
+            return decomposition;
         }
 
-        if let Some(right_mesh) = right {
-            result.append(&mut self.decompose(&right_mesh));
+        let decomposition: ConvexDecomposition = self.get_decomposition(mesh);
+        if let Err(error) = self.write_cache(cache_path, &key, &decomposition) {
+            println!("Could not write hull cache {:?}: {}", cache_path, error);
+        }
+        decomposition
+    }
+
+    // Candidate planes are spread evenly across each axis of the piece, with
+    // three more through its deepest point where the surface folds inwards.
+    fn get_best_split(&self, piece: &Piece) -> Option<(Piece, Piece)> {
+        let centroids: Vec<[f64; 3]> = piece
+            .polygons
+            .iter()
+            .map(|polygon| polygon.get_centroid().to_array())
+            .collect();
+        let deepest: [f64; 3] = piece.deepest.to_array();
+
+        let mut planes: Vec<(usize, f64)> = Vec::new();
+        for axis in 0..3 {
+            let min: f64 = centroids
+                .iter()
+                .map(|c| c[axis])
+                .fold(f64::INFINITY, f64::min);
+            let max: f64 = centroids
+                .iter()
+                .map(|c| c[axis])
+                .fold(f64::NEG_INFINITY, f64::max);
+            for k in 0..self.plane_resolution {
+                let t: f64 = (k as f64 + 0.5) / self.plane_resolution as f64;
+                planes.push((axis, min + t * (max - min)));
+            }
+            planes.push((axis, deepest[axis]));
         }
 
-        result
+        planes
+            .par_iter()
+            .filter_map(|&(axis, position)| {
+                let mut left: Vec<Polygon> = Vec::new();
+                let mut right: Vec<Polygon> = Vec::new();
+                for (polygon, centroid) in piece.polygons.iter().zip(&centroids) {
+                    if centroid[axis] < position {
+                        left.push(*polygon);
+                    } else {
+                        right.push(*polygon);
+                    }
+                }
+                if left.is_empty() || right.is_empty() {
+                    return None;
+                }
+                Some((Piece::new(left), Piece::new(right)))
+            })
+            .min_by(|a, b| {
+                let cost_a: f64 = a.0.concavity + a.1.concavity;
+                let cost_b: f64 = b.0.concavity + b.1.concavity;
+                cost_a.total_cmp(&cost_b)
+            })
+    }
+
+    // Pieces without volume or area have no hull and are left out. The
+    // hull points become the collision shape of the piece.
+    fn get_hull(&self, vertices: Vec<Vector3D>) -> Option<Mesh> {
+        let mut hull: QuickHull = QuickHull::new(vertices);
+        hull.set_max_vertices(self.max_vertices_per_hull);
+        let mut mesh: Mesh = hull.get_mesh()?;
+        mesh.set_uniform_color(RGBA::from_random());
+        let vertices: Vec<Vector3D> = Self::get_unique_vertices(&mesh.polygons);
+        mesh.set_collision_shape(CollisionShape::ConvexHull { vertices });
+        Some(mesh)
+    }
+
+    fn get_unique_vertices(polygons: &[Polygon]) -> Vec<Vector3D> {
+        let mut seen: HashSet<[u64; 3]> = HashSet::new();
+        polygons
+            .iter()
+            .flat_map(|polygon| polygon.get_vertices().iter().copied())
+            .filter(|v| seen.insert([v.x.to_bits(), v.y.to_bits(), v.z.to_bits()]))
+            .collect()
+    }
+
+    fn get_diagonal(mesh: &Mesh) -> f64 {
+        let vertices: Vec<Vector3D> = Self::get_unique_vertices(&mesh.polygons);
+        let Some(first) = vertices.first() else {
+            return 0.0;
+        };
+        let (min, max): (Vector3D, Vector3D) =
+            vertices.iter().fold((*first, *first), |(min, max), v| {
+                (min.component_min(v), max.component_max(v))
+            });
+        max.subtract_vector(&min).get_length()
     }
 
-    fn is_convex(&self, mesh: &Mesh) -> bool {
-        for poly in &mesh.polygons {
-            let normal: Vector3D = poly.get_normal();
-            for neighbor in &mesh.polygons {
-                let neighbor_normal: Vector3D = neighbor.get_normal();
-                if normal.get_angle_between(&neighbor_normal) > self.tolerance {
-                    return false;
+    // Hashes the exact vertices and the settings, so any change to the model
+    // or its placement invalidates the cache.
+    fn get_cache_key(&self, mesh: &Mesh) -> String {
+        let mut hash: u64 = FNV_OFFSET_BASIS;
+        for polygon in &mesh.polygons {
+            for v in polygon.get_vertices() {
+                for value in [v.x, v.y, v.z] {
+                    hash = Self::hash_bytes(hash, &value.to_bits().to_le_bytes());
                 }
             }
         }
-        true
+        hash = Self::hash_bytes(hash, &self.concavity.to_bits().to_le_bytes());
+        for value in [
+            self.max_hulls,
+            self.max_vertices_per_hull,
+            self.plane_resolution,
+        ] {
+            hash = Self::hash_bytes(hash, &(value as u64).to_le_bytes());
+        }
+        format!("{:016x}", hash)
     }
 
-    fn randomize_mesh_color(&self, mut mesh: Mesh) -> Mesh {
-        let random_color: RGBA = RGBA::from_random();
-        for polygon in mesh.polygons.iter_mut() {
-            polygon.set_color(&random_color);
+    fn hash_bytes(hash: u64, bytes: &[u8]) -> u64 {
+        bytes.iter().fold(hash, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    // The cache stores the key, the concavity and the hull points, the hull
+    // faces are rebuilt from the points on load.
+    fn read_cache(&self, cache_path: &str, key: &str) -> Option<ConvexDecomposition> {
+        let contents: String = read_to_string(cache_path).ok()?;
+        let mut lines = contents.lines();
+        if lines.next()? != key {
+            return None;
         }
-        mesh
-    }
-
-    fn split_mesh(&self, mesh: &Mesh) -> (Option<Mesh>, Option<Mesh>) {
-        let (min, max): ([f64; 3], [f64; 3]) = mesh.get_bounding_box();
-        let mut axis: usize = 0;
-        let mut max_extent: f64 = max[0] - min[0];
-        for i in 1..3 {
-            let extent: f64 = max[i] - min[i];
-            if extent > max_extent {
-                max_extent = extent;
-                axis = i;
+        let concavity: f64 = lines.next()?.strip_prefix("concavity ")?.parse().ok()?;
+
+        let mut point_sets: Vec<Vec<Vector3D>> = Vec::new();
+        for line in lines {
+            if line.starts_with("hull") {
+                point_sets.push(Vec::new());
+                continue;
+            }
+            let coordinates: Vec<f64> = line
+                .split_whitespace()
+                .map(|value| value.parse().ok())
+                .collect::<Option<Vec<f64>>>()?;
+            if coordinates.len() != 3 {
+                return None;
             }
+            let point: Vector3D = Vector3D::new(coordinates[0], coordinates[1], coordinates[2]);
+            point_sets.last_mut()?.push(point);
         }
 
-        let mid: f64 = (min[axis] + max[axis]) / 2.0;
-        let mut left_polygons: Vec<Polygon> = Vec::new();
-        let mut right_polygons: Vec<Polygon> = Vec::new();
+        let hulls: Vec<Mesh> = point_sets
+            .into_iter()
+            .map(|points| self.get_hull(points))
+            .collect::<Option<Vec<Mesh>>>()?;
+        Some(ConvexDecomposition { hulls, concavity })
+    }
 
-        for poly in &mesh.polygons {
-            let centroid: [f64; 3] = poly.get_centroid().to_array();
-            if centroid[axis] < mid {
-                left_polygons.push(poly.clone());
-            } else {
-                right_polygons.push(poly.clone());
+    fn write_cache(
+        &self,
+        cache_path: &str,
+        key: &str,
+        decomposition: &ConvexDecomposition,
+    ) -> std::io::Result<()> {
+        let mut contents: String = format!("{}\nconcavity {}\n", key, decomposition.concavity);
+        for (idx, hull) in decomposition.hulls.iter().enumerate() {
+            contents.push_str(&format!("hull {}\n", idx));
+            for v in QuickHull::new(hull.bvh_node.vertices.clone()).get_hull() {
+                contents.push_str(&format!("{} {} {}\n", v.x, v.y, v.z));
             }
         }
-
-        let left_mesh: Option<Mesh> = if !left_polygons.is_empty() {
-            let mesh: Mesh = Mesh::new(left_polygons);
-            let mesh: Mesh = self.randomize_mesh_color(mesh);
-            Some(mesh)
-        } else {
-            None
-        };
-
-        let right_mesh: Option<Mesh> = if !right_polygons.is_empty() {
-            let mesh: Mesh = Mesh::new(right_polygons);
-            let mesh: Mesh = self.randomize_mesh_color(mesh);
-            Some(mesh)
-        } else {
-            None
-        };
-
-        (left_mesh, right_mesh)
+        write(cache_path, contents)
     }
 }
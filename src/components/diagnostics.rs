@@ -0,0 +1,243 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::forces::ForceRegistry;
+use crate::components::gravity::Gravity;
+use crate::components::gravity::GravitySolver;
+use crate::components::physics::Physics;
+use crate::components::vectors::Vector3D;
+
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+/// The conserved quantities of the whole scene at one point in time. The
+/// angular momentum is taken about the origin. The potential energy covers
+/// the gravity solver and the force generators that have a potential, the
+/// work done by drag, wind and buoyancy is not counted.
+#[derive(Clone, Copy, Debug)]
+pub struct ConservationSnapshot {
+    pub time: f64,
+    pub total_mass: f64,
+    pub kinetic_energy: f64,
+    pub rotational_energy: f64,
+    pub potential_energy: f64,
+    pub linear_momentum: Vector3D,
+    pub angular_momentum: Vector3D,
+    pub center_of_mass: Vector3D,
+}
+
+impl ConservationSnapshot {
+    pub fn from_objects(
+        objects: &[BodyType],
+        gravity: &Gravity,
+        forces: &ForceRegistry,
+        time: f64,
+    ) -> ConservationSnapshot {
+        let mut total_mass: f64 = 0.0;
+        let mut kinetic_energy: f64 = 0.0;
+        let mut rotational_energy: f64 = 0.0;
+        let mut linear_momentum: Vector3D = Vector3D::default(0.0);
+        let mut angular_momentum: Vector3D = Vector3D::default(0.0);
+        let mut weighted_position: Vector3D = Vector3D::default(0.0);
+
+        for object in objects {
+            let physics: &Physics = object.physics();
+            let momentum: Vector3D = physics.velocity.multiply(physics.mass);
+            let spin: Vector3D = physics.get_angular_momentum();
+
+            total_mass += physics.mass;
+            kinetic_energy += 0.5 * physics.mass * physics.velocity.get_length_squared();
+            rotational_energy += 0.5 * physics.angular_velocity.dot_product(&spin);
+            linear_momentum = linear_momentum.add_vector(&momentum);
+            angular_momentum = angular_momentum
+                .add_vector(&physics.get_position().cross_product(&momentum))
+                .add_vector(&spin);
+            weighted_position =
+                weighted_position.add_vector(&physics.get_position().multiply(physics.mass));
+        }
+
+        let center_of_mass: Vector3D = if total_mass > 0.0 {
+            weighted_position.divide(total_mass)
+        } else {
+            Vector3D::default(0.0)
+        };
+
+        ConservationSnapshot {
+            time,
+            total_mass,
+            kinetic_energy,
+            rotational_energy,
+            potential_energy: Self::get_potential_energy(objects, gravity)
+                + forces.get_potential_energy(objects),
+            linear_momentum,
+            angular_momentum,
+            center_of_mass,
+        }
+    }
+
+    // Uses the same softened distance as the gravity solvers, so the energy
+    // stays conserved for softened forces too.
+    fn get_potential_energy(objects: &[BodyType], gravity: &Gravity) -> f64 {
+        if gravity.solver == GravitySolver::Disabled {
+            return 0.0;
+        }
+        let physics: Vec<&Physics> = objects.iter().map(|o| o.physics()).collect();
+        let softening_squared: f64 = gravity.softening.powi(2);
+
+        (0..physics.len())
+            .into_par_iter()
+            .map(|i| {
+                let a: &Physics = physics[i];
+                let mut energy: f64 = 0.0;
+                for b in &physics[i + 1..] {
+                    let distance_squared: f64 = a
+                        .get_position()
+                        .subtract_vector(&b.get_position())
+                        .get_length_squared();
+                    let distance: f64 = (distance_squared + softening_squared).sqrt();
+                    if distance > 0.0 {
+                        energy -= b.g_const * a.mass * b.mass / distance;
+                    }
+                }
+                energy
+            })
+            .sum()
+    }
+
+    pub fn get_total_energy(&self) -> f64 {
+        self.kinetic_energy + self.rotational_energy + self.potential_energy
+    }
+
+    /// Where the center of mass should be at the given time if it kept
+    /// moving with the total momentum of this snapshot.
+    pub fn get_expected_center_of_mass(&self, time: f64) -> Vector3D {
+        if self.total_mass == 0.0 {
+            return self.center_of_mass;
+        }
+        let velocity: Vector3D = self.linear_momentum.divide(self.total_mass);
+        self.center_of_mass
+            .add_vector(&velocity.multiply(time - self.time))
+    }
+}
+
+/// Change of the conserved quantities since the initial snapshot. The
+/// energy drift is relative to the initial total energy, the others are the
+/// lengths of the absolute changes.
+#[derive(Clone, Copy, Debug)]
+pub struct ConservationDrift {
+    pub energy: f64,
+    pub linear_momentum: f64,
+    pub angular_momentum: f64,
+    pub center_of_mass: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConservationDiagnostics {
+    pub enabled: bool,
+    pub history_length: usize,
+    pub initial: Option<ConservationSnapshot>,
+    pub current: Option<ConservationSnapshot>,
+    energy_history: VecDeque<f64>,
+}
+
+impl ConservationDiagnostics {
+    pub fn new(enabled: bool) -> ConservationDiagnostics {
+        ConservationDiagnostics {
+            enabled,
+            history_length: 120,
+            initial: None,
+            current: None,
+            energy_history: VecDeque::new(),
+        }
+    }
+
+    /// The drift is measured again from the step the diagnostics are
+    /// switched on.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.reset();
+    }
+
+    /// Makes the next snapshot the new reference for the drift.
+    pub fn reset(&mut self) {
+        self.initial = None;
+        self.current = None;
+        self.energy_history.clear();
+    }
+
+    pub fn update(
+        &mut self,
+        objects: &[BodyType],
+        gravity: &Gravity,
+        forces: &ForceRegistry,
+        time: f64,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let snapshot: ConservationSnapshot =
+            ConservationSnapshot::from_objects(objects, gravity, forces, time);
+        if self.initial.is_none() {
+            self.initial = Some(snapshot);
+        }
+        self.current = Some(snapshot);
+
+        if let Some(drift) = self.get_drift() {
+            self.energy_history.push_back(drift.energy);
+            while self.energy_history.len() > self.history_length {
+                self.energy_history.pop_front();
+            }
+        }
+    }
+
+    pub fn get_drift(&self) -> Option<ConservationDrift> {
+        let initial: &ConservationSnapshot = self.initial.as_ref()?;
+        let current: &ConservationSnapshot = self.current.as_ref()?;
+
+        let initial_energy: f64 = initial.get_total_energy();
+        let energy_change: f64 = current.get_total_energy() - initial_energy;
+        let energy: f64 = if initial_energy != 0.0 {
+            energy_change / initial_energy.abs()
+        } else {
+            energy_change
+        };
+        let expected_center: Vector3D = initial.get_expected_center_of_mass(current.time);
+
+        Some(ConservationDrift {
+            energy,
+            linear_momentum: current
+                .linear_momentum
+                .subtract_vector(&initial.linear_momentum)
+                .get_length(),
+            angular_momentum: current
+                .angular_momentum
+                .subtract_vector(&initial.angular_momentum)
+                .get_length(),
+            center_of_mass: current.center_of_mass.get_distance(&expected_center),
+        })
+    }
+
+    pub fn get_energy_history(&self) -> Vec<f64> {
+        self.energy_history.iter().copied().collect()
+    }
+
+    /// Screen points of the energy drift history scaled into the given box
+    /// with the oldest value on the left, together with the height of zero
+    /// drift.
+    pub fn get_sparkline(&self, origin: (f64, f64), size: (f64, f64)) -> (Vec<(f64, f64)>, f64) {
+        let (x, y): (f64, f64) = origin;
+        let (width, height): (f64, f64) = size;
+        let min: f64 = self.energy_history.iter().copied().fold(0.0, f64::min);
+        let max: f64 = self.energy_history.iter().copied().fold(0.0, f64::max);
+        let range: f64 = if max > min { max - min } else { 1.0 };
+        let spacing: f64 = width / self.history_length.saturating_sub(1).max(1) as f64;
+
+        let get_y = |drift: f64| -> f64 { y + height * (max - drift) / range };
+        let points: Vec<(f64, f64)> = self
+            .energy_history
+            .iter()
+            .enumerate()
+            .map(|(idx, drift)| (x + idx as f64 * spacing, get_y(*drift)))
+            .collect();
+        (points, get_y(0.0))
+    }
+}
@@ -4,14 +4,16 @@ use crate::abstracts::body::Body;
 use crate::abstracts::body::BodyType;
 use crate::components::backface_culling::BackfaceCulling;
 use crate::components::buffer_sort::ZBufferSort;
+use crate::components::camera::Camera;
 use crate::components::color::RGBA;
 use crate::components::frametime::FrameTimeHandler;
 use crate::components::graphics::Graphics;
+use crate::components::joints::JointSolver;
 use crate::components::polygons::Polygon;
 use crate::components::shaders::Light;
 use crate::components::shaders::Shaders;
 use crate::components::simulation::Simulation;
-use crate::Camera;
+use crate::components::sleep::SleepManager;
 
 pub struct DrawCall {
     pub graphics: Graphics,
@@ -82,11 +84,7 @@ impl DrawCall {
         let thickness = 1.0;
 
         for mesh in meshes {
-            let vertices = &mesh.bvh_node.vertices;
-            for i in 0..vertices.len() {
-                let v1: Vector3D = vertices[i];
-                let v2: Vector3D = vertices[(i + 1) % vertices.len()];
-
+            for (v1, v2) in mesh.get_hull_edges() {
                 let line: Option<(Vector3D, Vector3D)> = camera.transform_line(v1, v2);
                 if line.is_some() {
                     let (v1, v2): (Vector3D, Vector3D) = line.unwrap();
@@ -192,6 +190,54 @@ impl DrawCall {
         }
     }
 
+    fn draw_joint_anchor(graphics: &mut Graphics, camera: &mut Camera, anchor: Vector3D) {
+        let color: RGBA = RGBA::from_rgb(1.0, 0.8, 0.3);
+        let radius: f64 = 4.0;
+
+        let anchor: Vector3D = camera.apply_view_transform(anchor);
+        if !camera.frustum.is_point_in_frustum(anchor) {
+            return;
+        }
+        let anchor: Vector3D = camera.calculate_perspective_projection(anchor);
+        let anchor: Vector3D = camera.ndc_to_screen_coordinates(anchor);
+        graphics.draw_circle(anchor, color, radius);
+    }
+
+    // Anchors are joined by a line that stretches when the joint drifts and
+    // hinges show their axis through the anchor of the first body.
+    fn draw_joints(
+        graphics: &mut Graphics,
+        camera: &mut Camera,
+        objects: &[BodyType],
+        joint_solver: &JointSolver,
+    ) {
+        let link_color: RGBA = RGBA::from_rgb(1.0, 0.8, 0.3);
+        let axis_color: RGBA = RGBA::from_rgb(0.4, 0.8, 1.0);
+        let thickness = 1.0;
+
+        for joint in joint_solver.joints.iter().filter(|j| !j.is_broken) {
+            let (anchor_a, anchor_b): (Vector3D, Vector3D) = joint.get_world_anchors(objects);
+
+            let line: Option<(Vector3D, Vector3D)> = camera.transform_line(anchor_a, anchor_b);
+            if let Some((v1, v2)) = line {
+                graphics.draw_line(v1, v2, link_color, thickness);
+            }
+
+            if let Some(axis) = joint.get_world_axis(objects) {
+                let length: f64 = objects[joint.body_a].physics().get_bounding_radius();
+                let v1: Vector3D = anchor_a.subtract_vector(&axis.multiply(length));
+                let v2: Vector3D = anchor_a.add_vector(&axis.multiply(length));
+                let line: Option<(Vector3D, Vector3D)> = camera.transform_line(v1, v2);
+                if let Some((v1, v2)) = line {
+                    graphics.draw_line(v1, v2, axis_color, thickness);
+                }
+            }
+
+            Self::draw_joint_anchor(graphics, camera, anchor_a);
+            Self::draw_joint_anchor(graphics, camera, anchor_b);
+        }
+    }
+
     // fn draw_center_of_masses(&mut self) {
     //     let camera: &mut Camera = &mut self.simulation.camera;
     //     let color: RGBA = RGBA::from_rgb(0.6, 1.0, 0.6);
@@ -220,7 +266,7 @@ impl DrawCall {
         let radius: f64 = 5.0;
 
         for object in self.simulation.objects.iter_mut() {
-            let inertia = object.physics_mut().moment_of_inertia;
+            let inertia = object.physics_mut().inertia_tensor.get_diagonal();
 
             let inertia: Vector3D = camera.apply_view_transform(inertia);
 
@@ -334,9 +380,18 @@ impl DrawCall {
         let graphics: &mut Graphics = &mut self.graphics;
 
         let meshes: Vec<&Mesh> = objects.iter().map(|body| body.mesh()).collect();
-        let mut polygons: Vec<Polygon> = meshes
+        let sleep: &SleepManager = &self.simulation.sleep;
+        let mut polygons: Vec<Polygon> = objects
             .iter()
-            .flat_map(|mesh| mesh.polygons.clone())
+            .flat_map(|body| {
+                let mut polygons: Vec<Polygon> = body.mesh().polygons.clone();
+                if sleep.draw_tint && body.physics().is_sleeping {
+                    for polygon in polygons.iter_mut() {
+                        polygon.set_color(&sleep.tint);
+                    }
+                }
+                polygons
+            })
             .collect();
 
         // Self::draw_contact_points(graphics, camera, objects);
@@ -344,7 +399,14 @@ impl DrawCall {
         // self.draw_inertias();
 
         if self.simulation.draw_mesh {
-            Self::draw_convex_hulls(graphics, camera, &meshes);
+            // Only meshes built as hulls, like the cluster pieces, have edges.
+            let hulls: Vec<&Mesh> = objects
+                .iter()
+                .flat_map(|body| {
+                    std::iter::once(body.mesh()).chain(body.mesh_cluster().iter().flatten())
+                })
+                .collect();
+            Self::draw_convex_hulls(graphics, camera, &hulls);
             Self::draw_bounding_box(graphics, camera, &meshes);
         }
 
@@ -359,6 +421,16 @@ impl DrawCall {
 
             self.graphics.draw_polygons(polygons);
         }
+
+        if self.simulation.joint_solver.draw_joints {
+            let simulation: &mut Simulation = &mut self.simulation;
+            Self::draw_joints(
+                &mut self.graphics,
+                &mut simulation.camera,
+                &simulation.objects,
+                &simulation.joint_solver,
+            );
+        }
     }
 
     pub fn draw_clusters(&mut self) {
@@ -0,0 +1,632 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::integrator::LinearState;
+use crate::components::physics::Physics;
+use crate::components::vectors::Vector3D;
+
+use std::f64::consts::PI;
+
+pub trait ForceGenerator {
+    fn get_name(&self) -> &'static str;
+    /// Adds the accelerations of the generator for a trial state, only the
+    /// bodies marked as affected are pushed.
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    );
+    /// The potential energy of the affected bodies, generators that do work
+    /// without a potential return None.
+    fn get_potential_energy(&self, objects: &[BodyType], is_affected: &[bool]) -> Option<f64>;
+}
+
+/// Pulls every body along the up axis with its own `gravity`, which is
+/// negative so bodies fall.
+#[derive(Clone, Debug)]
+pub struct UniformGravity {
+    pub up: Vector3D,
+}
+
+impl UniformGravity {
+    pub fn new() -> UniformGravity {
+        UniformGravity {
+            up: Vector3D::new(0.0, 1.0, 0.0),
+        }
+    }
+
+    pub fn set_up(&mut self, up: Vector3D) {
+        self.up = up.normalize();
+    }
+}
+
+impl Default for UniformGravity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ForceGenerator for UniformGravity {
+    fn get_name(&self) -> &'static str {
+        "Uniform Gravity"
+    }
+
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        _state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    ) {
+        for (idx, object) in objects.iter().enumerate() {
+            if !is_affected[idx] {
+                continue;
+            }
+            let gravity: f64 = object.physics().gravity;
+            accelerations[idx] = accelerations[idx].add_vector(&self.up.multiply(gravity));
+        }
+    }
+
+    fn get_potential_energy(&self, objects: &[BodyType], is_affected: &[bool]) -> Option<f64> {
+        let energy: f64 = objects
+            .iter()
+            .zip(is_affected)
+            .filter(|(_, is_affected)| **is_affected)
+            .map(|(object, _)| {
+                let physics: &Physics = object.physics();
+                -physics.mass * physics.gravity * self.up.dot_product(&physics.get_position())
+            })
+            .sum();
+        Some(energy)
+    }
+}
+
+/// Slows bodies down with a force of (linear + quadratic * speed) * velocity.
+#[derive(Clone, Debug)]
+pub struct Drag {
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+impl Drag {
+    pub fn new(linear: f64, quadratic: f64) -> Drag {
+        Drag { linear, quadratic }
+    }
+}
+
+impl ForceGenerator for Drag {
+    fn get_name(&self) -> &'static str {
+        "Drag"
+    }
+
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    ) {
+        for (idx, object) in objects.iter().enumerate() {
+            let mass: f64 = object.physics().mass;
+            if !is_affected[idx] || mass <= 0.0 {
+                continue;
+            }
+            let velocity: Vector3D = state.velocities[idx];
+            let coefficient: f64 = self.linear + self.quadratic * velocity.get_length();
+            let acceleration: Vector3D = velocity.multiply(-coefficient / mass);
+            accelerations[idx] = accelerations[idx].add_vector(&acceleration);
+        }
+    }
+
+    fn get_potential_energy(&self, _objects: &[BodyType], _is_affected: &[bool]) -> Option<f64> {
+        None
+    }
+}
+
+/// A Hooke spring between the centers of mass of two bodies, damped along
+/// the spring axis.
+#[derive(Clone, Debug)]
+pub struct HookeSpring {
+    pub body_a: usize,
+    pub body_b: usize,
+    pub rest_length: f64,
+    pub stiffness: f64,
+    pub damping: f64,
+}
+
+impl HookeSpring {
+    pub fn new(
+        body_a: usize,
+        body_b: usize,
+        rest_length: f64,
+        stiffness: f64,
+        damping: f64,
+    ) -> HookeSpring {
+        HookeSpring {
+            body_a,
+            body_b,
+            rest_length,
+            stiffness,
+            damping,
+        }
+    }
+}
+
+impl ForceGenerator for HookeSpring {
+    fn get_name(&self) -> &'static str {
+        "Hooke Spring"
+    }
+
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    ) {
+        let (a, b): (usize, usize) = (self.body_a, self.body_b);
+        if a.max(b) >= objects.len() || a == b {
+            return;
+        }
+        let offset: Vector3D = state.positions[b].subtract_vector(&state.positions[a]);
+        let length: f64 = offset.get_length();
+        if length == 0.0 {
+            return;
+        }
+        let direction: Vector3D = offset.divide(length);
+        let speed: f64 = state.velocities[b]
+            .subtract_vector(&state.velocities[a])
+            .dot_product(&direction);
+        let strength: f64 = self.stiffness * (length - self.rest_length) + self.damping * speed;
+        let force: Vector3D = direction.multiply(strength);
+
+        for (idx, force) in [(a, force), (b, force.negate())] {
+            let mass: f64 = objects[idx].physics().mass;
+            if is_affected[idx] && mass > 0.0 {
+                accelerations[idx] = accelerations[idx].add_vector(&force.divide(mass));
+            }
+        }
+    }
+
+    // The spring only stores energy while it pulls on both of its bodies.
+    fn get_potential_energy(&self, objects: &[BodyType], is_affected: &[bool]) -> Option<f64> {
+        let (a, b): (usize, usize) = (self.body_a, self.body_b);
+        if a.max(b) >= objects.len() || a == b || !is_affected[a] || !is_affected[b] {
+            return Some(0.0);
+        }
+        let length: f64 = objects[a]
+            .physics()
+            .get_position()
+            .get_distance(&objects[b].physics().get_position());
+        Some(0.5 * self.stiffness * (length - self.rest_length).powi(2))
+    }
+}
+
+/// Pulls bodies towards a center, or pushes them away for a negative
+/// strength. The acceleration falls off linearly to zero at the radius.
+#[derive(Clone, Debug)]
+pub struct RadialField {
+    pub center: Vector3D,
+    pub strength: f64,
+    pub radius: f64,
+}
+
+impl RadialField {
+    pub fn attractor(center: Vector3D, strength: f64, radius: f64) -> RadialField {
+        RadialField {
+            center,
+            strength: strength.abs(),
+            radius,
+        }
+    }
+
+    pub fn repulsor(center: Vector3D, strength: f64, radius: f64) -> RadialField {
+        RadialField {
+            center,
+            strength: -strength.abs(),
+            radius,
+        }
+    }
+}
+
+impl ForceGenerator for RadialField {
+    fn get_name(&self) -> &'static str {
+        if self.strength >= 0.0 {
+            "Attractor"
+        } else {
+            "Repulsor"
+        }
+    }
+
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    ) {
+        for idx in 0..objects.len() {
+            if !is_affected[idx] {
+                continue;
+            }
+            let offset: Vector3D = self.center.subtract_vector(&state.positions[idx]);
+            let distance: f64 = offset.get_length();
+            if distance == 0.0 || distance > self.radius {
+                continue;
+            }
+            let falloff: f64 = 1.0 - distance / self.radius;
+            let acceleration: Vector3D = offset.multiply(self.strength * falloff / distance);
+            accelerations[idx] = accelerations[idx].add_vector(&acceleration);
+        }
+    }
+
+    // The linear falloff integrates to a quadratic well that is zero at the
+    // radius and outside of it.
+    fn get_potential_energy(&self, objects: &[BodyType], is_affected: &[bool]) -> Option<f64> {
+        let energy: f64 = objects
+            .iter()
+            .zip(is_affected)
+            .filter(|(_, is_affected)| **is_affected)
+            .map(|(object, _)| {
+                let physics: &Physics = object.physics();
+                let distance: f64 = self.center.get_distance(&physics.get_position());
+                if distance > self.radius {
+                    return 0.0;
+                }
+                -physics.mass * self.strength * (self.radius - distance).powi(2)
+                    / (2.0 * self.radius)
+            })
+            .sum();
+        Some(energy)
+    }
+}
+
+/// Drags bodies towards the wind velocity with a force proportional to
+/// their velocity relative to the air.
+#[derive(Clone, Debug)]
+pub struct Wind {
+    pub velocity: Vector3D,
+    pub coefficient: f64,
+}
+
+impl Wind {
+    pub fn new(velocity: Vector3D, coefficient: f64) -> Wind {
+        Wind {
+            velocity,
+            coefficient,
+        }
+    }
+}
+
+impl ForceGenerator for Wind {
+    fn get_name(&self) -> &'static str {
+        "Wind"
+    }
+
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    ) {
+        for (idx, object) in objects.iter().enumerate() {
+            let mass: f64 = object.physics().mass;
+            if !is_affected[idx] || mass <= 0.0 {
+                continue;
+            }
+            let relative_velocity: Vector3D = self.velocity.subtract_vector(&state.velocities[idx]);
+            let acceleration: Vector3D = relative_velocity.multiply(self.coefficient / mass);
+            accelerations[idx] = accelerations[idx].add_vector(&acceleration);
+        }
+    }
+
+    fn get_potential_energy(&self, _objects: &[BodyType], _is_affected: &[bool]) -> Option<f64> {
+        None
+    }
+}
+
+/// A box of fluid with its surface at the top of the box. Bodies are
+/// treated as their bounding spheres, they are lifted by the weight of the
+/// displaced fluid and damped by the submerged fraction of their volume.
+#[derive(Clone, Debug)]
+pub struct BuoyancyVolume {
+    pub min: Vector3D,
+    pub max: Vector3D,
+    pub density: f64,
+    pub gravity: f64,
+    pub drag: f64,
+}
+
+impl BuoyancyVolume {
+    pub fn new(min: Vector3D, max: Vector3D, density: f64) -> BuoyancyVolume {
+        BuoyancyVolume {
+            min,
+            max,
+            density,
+            gravity: 9.8,
+            drag: 0.5,
+        }
+    }
+
+    // The submerged part of a sphere is a cap of the height below the
+    // surface.
+    fn get_submerged_volume(&self, position: &Vector3D, radius: f64) -> f64 {
+        let inside: bool = position.x >= self.min.x
+            && position.x <= self.max.x
+            && position.z >= self.min.z
+            && position.z <= self.max.z
+            && position.y + radius >= self.min.y;
+        if !inside {
+            return 0.0;
+        }
+        let height: f64 = (self.max.y - (position.y - radius)).clamp(0.0, 2.0 * radius);
+        PI * height * height * (3.0 * radius - height) / 3.0
+    }
+}
+
+impl ForceGenerator for BuoyancyVolume {
+    fn get_name(&self) -> &'static str {
+        "Buoyancy"
+    }
+
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    ) {
+        for (idx, object) in objects.iter().enumerate() {
+            let physics: &Physics = object.physics();
+            let radius: f64 = physics.get_bounding_radius();
+            if !is_affected[idx] || physics.mass <= 0.0 || radius <= 0.0 {
+                continue;
+            }
+            let volume: f64 = self.get_submerged_volume(&state.positions[idx], radius);
+            if volume == 0.0 {
+                continue;
+            }
+            let fraction: f64 = volume / (4.0 / 3.0 * PI * radius.powi(3));
+            let lift: f64 = self.density * volume * self.gravity / physics.mass;
+            let acceleration: Vector3D = Vector3D::new(0.0, lift, 0.0)
+                .subtract_vector(&state.velocities[idx].multiply(self.drag * fraction));
+            accelerations[idx] = accelerations[idx].add_vector(&acceleration);
+        }
+    }
+
+    // The lift has a potential but the damping of the fluid does not.
+    fn get_potential_energy(&self, _objects: &[BodyType], _is_affected: &[bool]) -> Option<f64> {
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ForceGeneratorType {
+    UniformGravity(UniformGravity),
+    Drag(Drag),
+    HookeSpring(HookeSpring),
+    RadialField(RadialField),
+    Wind(Wind),
+    BuoyancyVolume(BuoyancyVolume),
+}
+
+impl ForceGenerator for ForceGeneratorType {
+    fn get_name(&self) -> &'static str {
+        match self {
+            ForceGeneratorType::UniformGravity(f) => f.get_name(),
+            ForceGeneratorType::Drag(f) => f.get_name(),
+            ForceGeneratorType::HookeSpring(f) => f.get_name(),
+            ForceGeneratorType::RadialField(f) => f.get_name(),
+            ForceGeneratorType::Wind(f) => f.get_name(),
+            ForceGeneratorType::BuoyancyVolume(f) => f.get_name(),
+        }
+    }
+
+    fn add_accelerations(
+        &self,
+        objects: &[BodyType],
+        state: &LinearState,
+        is_affected: &[bool],
+        accelerations: &mut [Vector3D],
+    ) {
+        match self {
+            ForceGeneratorType::UniformGravity(f) => {
+                f.add_accelerations(objects, state, is_affected, accelerations)
+            }
+            ForceGeneratorType::Drag(f) => {
+                f.add_accelerations(objects, state, is_affected, accelerations)
+            }
+            ForceGeneratorType::HookeSpring(f) => {
+                f.add_accelerations(objects, state, is_affected, accelerations)
+            }
+            ForceGeneratorType::RadialField(f) => {
+                f.add_accelerations(objects, state, is_affected, accelerations)
+            }
+            ForceGeneratorType::Wind(f) => {
+                f.add_accelerations(objects, state, is_affected, accelerations)
+            }
+            ForceGeneratorType::BuoyancyVolume(f) => {
+                f.add_accelerations(objects, state, is_affected, accelerations)
+            }
+        }
+    }
+
+    fn get_potential_energy(&self, objects: &[BodyType], is_affected: &[bool]) -> Option<f64> {
+        match self {
+            ForceGeneratorType::UniformGravity(f) => f.get_potential_energy(objects, is_affected),
+            ForceGeneratorType::Drag(f) => f.get_potential_energy(objects, is_affected),
+            ForceGeneratorType::HookeSpring(f) => f.get_potential_energy(objects, is_affected),
+            ForceGeneratorType::RadialField(f) => f.get_potential_energy(objects, is_affected),
+            ForceGeneratorType::Wind(f) => f.get_potential_energy(objects, is_affected),
+            ForceGeneratorType::BuoyancyVolume(f) => f.get_potential_energy(objects, is_affected),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BodyFilter {
+    All,
+    Only(Vec<usize>),
+    Except(Vec<usize>),
+}
+
+impl BodyFilter {
+    fn get_mask(&self, body_count: usize) -> Vec<bool> {
+        match self {
+            BodyFilter::All => vec![true; body_count],
+            BodyFilter::Only(idxs) => {
+                let mut mask: Vec<bool> = vec![false; body_count];
+                for idx in idxs.iter().filter(|idx| **idx < body_count) {
+                    mask[*idx] = true;
+                }
+                mask
+            }
+            BodyFilter::Except(idxs) => {
+                let mut mask: Vec<bool> = vec![true; body_count];
+                for idx in idxs.iter().filter(|idx| **idx < body_count) {
+                    mask[*idx] = false;
+                }
+                mask
+            }
+        }
+    }
+
+    fn remap_bodies(&mut self, body_map: &[Option<usize>]) {
+        if let BodyFilter::Only(idxs) | BodyFilter::Except(idxs) = self {
+            *idxs = idxs
+                .iter()
+                .filter_map(|idx| body_map.get(*idx).copied().flatten())
+                .collect();
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ForceEntry {
+    pub generator: ForceGeneratorType,
+    pub filter: BodyFilter,
+    pub enabled: bool,
+}
+
+/// The force generators registered on a simulation, each one applies to
+/// the bodies its filter lets through while it is enabled.
+#[derive(Clone, Debug)]
+pub struct ForceRegistry {
+    pub entries: Vec<ForceEntry>,
+    pub enabled: bool,
+}
+
+impl ForceRegistry {
+    pub fn new() -> ForceRegistry {
+        ForceRegistry {
+            entries: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    pub fn add(&mut self, generator: ForceGeneratorType, filter: BodyFilter) -> usize {
+        self.entries.push(ForceEntry {
+            generator,
+            filter,
+            enabled: true,
+        });
+        self.entries.len() - 1
+    }
+
+    pub fn set_enabled(&mut self, idx: usize, enabled: bool) {
+        if let Some(entry) = self.entries.get_mut(idx) {
+            entry.enabled = enabled;
+        }
+    }
+
+    pub fn toggle(&mut self, idx: usize) {
+        if let Some(entry) = self.entries.get_mut(idx) {
+            entry.enabled = !entry.enabled;
+        }
+    }
+
+    pub fn toggle_all(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn get_active_count(&self) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        self.entries.iter().filter(|entry| entry.enabled).count()
+    }
+
+    /// Sums the accelerations of all enabled generators for a trial state.
+    pub fn get_accelerations(&self, objects: &[BodyType], state: &LinearState) -> Vec<Vector3D> {
+        let mut accelerations: Vec<Vector3D> = vec![Vector3D::default(0.0); objects.len()];
+        if !self.enabled {
+            return accelerations;
+        }
+        for entry in self.entries.iter().filter(|entry| entry.enabled) {
+            let is_affected: Vec<bool> = entry.filter.get_mask(objects.len());
+            entry
+                .generator
+                .add_accelerations(objects, state, &is_affected, &mut accelerations);
+        }
+        accelerations
+    }
+
+    /// Sums the potential energy of the enabled generators that have one.
+    pub fn get_potential_energy(&self, objects: &[BodyType]) -> f64 {
+        if !self.enabled {
+            return 0.0;
+        }
+        self.entries
+            .iter()
+            .filter(|entry| entry.enabled)
+            .filter_map(|entry| {
+                let is_affected: Vec<bool> = entry.filter.get_mask(objects.len());
+                entry.generator.get_potential_energy(objects, &is_affected)
+            })
+            .sum()
+    }
+
+    /// Names of the enabled generators whose work is left out of the energy.
+    pub fn get_unconserved_names(&self) -> Vec<&'static str> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut names: Vec<&'static str> = Vec::new();
+        // Generators without a potential return None even without bodies.
+        for entry in self.entries.iter().filter(|entry| entry.enabled) {
+            let name: &'static str = entry.generator.get_name();
+            if entry.generator.get_potential_energy(&[], &[]).is_none() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Moves the filters and springs to the new body indices, springs on a
+    /// body without a new index are dropped.
+    pub fn remap_bodies(&mut self, body_map: &[Option<usize>]) {
+        self.entries.retain_mut(|entry| {
+            entry.filter.remap_bodies(body_map);
+            let ForceGeneratorType::HookeSpring(spring) = &mut entry.generator else {
+                return true;
+            };
+            let get_idx = |idx: usize| body_map.get(idx).copied().flatten();
+            match (get_idx(spring.body_a), get_idx(spring.body_b)) {
+                (Some(body_a), Some(body_b)) => {
+                    spring.body_a = body_a;
+                    spring.body_b = body_b;
+                    true
+                }
+                _ => false,
+            }
+        });
+    }
+}
+
+impl Default for ForceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -37,6 +37,13 @@ impl FrameTimeHandler {
         }
     }
 
+    pub fn get_last_frame_time(&self) -> Duration {
+        match self.frame_times.back() {
+            Some(frame_time) => *frame_time,
+            None => Duration::new(0, 0),
+        }
+    }
+
     pub fn get_frames_per_second(&self) -> f64 {
         let average_frame_time: f64 = self.get_average_frame_time().as_secs_f64();
         if average_frame_time == 0.0 {
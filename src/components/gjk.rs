@@ -0,0 +1,578 @@
+use crate::components::vectors::Vector3D;
+
+const MAX_ITERATIONS: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SupportPoint {
+    pub point: Vector3D,
+    pub a: Vector3D,
+    pub b: Vector3D,
+}
+
+fn get_furthest_vertex(vertices: &[Vector3D], direction: &Vector3D) -> Vector3D {
+    let mut furthest_vertex: Vector3D = vertices[0];
+    let mut max_dot: f64 = f64::MIN;
+
+    for vertex in vertices {
+        let dot: f64 = vertex.dot_product(direction);
+        if dot > max_dot {
+            max_dot = dot;
+            furthest_vertex = *vertex;
+        }
+    }
+    furthest_vertex
+}
+
+fn get_support(a: &[Vector3D], b: &[Vector3D], direction: &Vector3D) -> SupportPoint {
+    let support_a: Vector3D = get_furthest_vertex(a, direction);
+    let support_b: Vector3D = get_furthest_vertex(b, &direction.negate());
+
+    SupportPoint {
+        point: support_a.subtract_vector(&support_b),
+        a: support_a,
+        b: support_b,
+    }
+}
+
+fn get_centroid(vertices: &[Vector3D]) -> Vector3D {
+    let mut vertices_sum: Vector3D = Vector3D::default(0.0);
+    for vertex in vertices {
+        vertices_sum = vertices_sum.add_vector(vertex);
+    }
+    vertices_sum.divide(vertices.len() as f64)
+}
+
+fn triple_cross(a: &Vector3D, b: &Vector3D, c: &Vector3D) -> Vector3D {
+    a.cross_product(b).cross_product(c)
+}
+
+fn get_perpendicular(vector: &Vector3D) -> Vector3D {
+    let axis: Vector3D = if vector.x.abs() < vector.y.abs() && vector.x.abs() < vector.z.abs() {
+        Vector3D::new(1.0, 0.0, 0.0)
+    } else if vector.y.abs() < vector.z.abs() {
+        Vector3D::new(0.0, 1.0, 0.0)
+    } else {
+        Vector3D::new(0.0, 0.0, 1.0)
+    };
+    vector.cross_product(&axis)
+}
+
+fn update_line(simplex: &mut Vec<SupportPoint>, direction: &mut Vector3D) -> bool {
+    let a: Vector3D = simplex[1].point;
+    let b: Vector3D = simplex[0].point;
+    let ab: Vector3D = b.subtract_vector(&a);
+    let ao: Vector3D = a.negate();
+
+    if ab.dot_product(&ao) > 0.0 {
+        *direction = triple_cross(&ab, &ao, &ab);
+        if direction.get_length_squared() == 0.0 {
+            // The origin lies on the segment.
+            return true;
+        }
+    } else {
+        *simplex = vec![simplex[1]];
+        *direction = ao;
+    }
+    false
+}
+
+fn update_triangle(simplex: &mut Vec<SupportPoint>, direction: &mut Vector3D) -> bool {
+    let a: Vector3D = simplex[2].point;
+    let b: Vector3D = simplex[1].point;
+    let c: Vector3D = simplex[0].point;
+    let ab: Vector3D = b.subtract_vector(&a);
+    let ac: Vector3D = c.subtract_vector(&a);
+    let ao: Vector3D = a.negate();
+    let abc: Vector3D = ab.cross_product(&ac);
+
+    if abc.cross_product(&ac).dot_product(&ao) > 0.0 {
+        if ac.dot_product(&ao) > 0.0 {
+            *simplex = vec![simplex[0], simplex[2]];
+            *direction = triple_cross(&ac, &ao, &ac);
+        } else {
+            *simplex = vec![simplex[1], simplex[2]];
+            return update_line(simplex, direction);
+        }
+    } else if ab.cross_product(&abc).dot_product(&ao) > 0.0 {
+        *simplex = vec![simplex[1], simplex[2]];
+        return update_line(simplex, direction);
+    } else {
+        let side: f64 = abc.dot_product(&ao);
+        if side > 0.0 {
+            *direction = abc;
+        } else if side < 0.0 {
+            *simplex = vec![simplex[1], simplex[0], simplex[2]];
+            *direction = abc.negate();
+        } else {
+            // The origin lies on the triangle.
+            return true;
+        }
+    }
+    false
+}
+
+fn update_tetrahedron(simplex: &mut Vec<SupportPoint>, direction: &mut Vector3D) -> bool {
+    let a: Vector3D = simplex[3].point;
+    let b: Vector3D = simplex[2].point;
+    let c: Vector3D = simplex[1].point;
+    let d: Vector3D = simplex[0].point;
+    let ab: Vector3D = b.subtract_vector(&a);
+    let ac: Vector3D = c.subtract_vector(&a);
+    let ad: Vector3D = d.subtract_vector(&a);
+    let ao: Vector3D = a.negate();
+
+    let abc: Vector3D = ab.cross_product(&ac);
+    let acd: Vector3D = ac.cross_product(&ad);
+    let adb: Vector3D = ad.cross_product(&ab);
+
+    if abc.dot_product(&ao) > 0.0 {
+        *simplex = vec![simplex[1], simplex[2], simplex[3]];
+        return update_triangle(simplex, direction);
+    }
+    if acd.dot_product(&ao) > 0.0 {
+        *simplex = vec![simplex[0], simplex[1], simplex[3]];
+        return update_triangle(simplex, direction);
+    }
+    if adb.dot_product(&ao) > 0.0 {
+        *simplex = vec![simplex[2], simplex[0], simplex[3]];
+        return update_triangle(simplex, direction);
+    }
+    true
+}
+
+fn update_simplex(simplex: &mut Vec<SupportPoint>, direction: &mut Vector3D) -> bool {
+    match simplex.len() {
+        2 => update_line(simplex, direction),
+        3 => update_triangle(simplex, direction),
+        4 => update_tetrahedron(simplex, direction),
+        _ => false,
+    }
+}
+
+/// Runs GJK over the Minkowski difference `a - b` and returns the final
+/// simplex when the origin is enclosed.
+pub fn gjk_intersection(a: &[Vector3D], b: &[Vector3D]) -> Option<Vec<SupportPoint>> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let mut direction: Vector3D = get_centroid(b).subtract_vector(&get_centroid(a));
+    if direction.get_length_squared() == 0.0 {
+        direction = Vector3D::new(1.0, 0.0, 0.0);
+    }
+
+    let first: SupportPoint = get_support(a, b, &direction);
+    let mut simplex: Vec<SupportPoint> = vec![first];
+    direction = first.point.negate();
+
+    for _ in 0..MAX_ITERATIONS {
+        if direction.get_length_squared() == 0.0 {
+            return Some(simplex);
+        }
+
+        let support: SupportPoint = get_support(a, b, &direction);
+        if support.point.dot_product(&direction) < 0.0 {
+            return None;
+        }
+
+        simplex.push(support);
+        if update_simplex(&mut simplex, &mut direction) {
+            return Some(simplex);
+        }
+    }
+    None
+}
+
+fn get_closest_point_on_segment(a: &Vector3D, b: &Vector3D) -> Vector3D {
+    let ab: Vector3D = b.subtract_vector(a);
+    let length_squared: f64 = ab.get_length_squared();
+    if length_squared == 0.0 {
+        return *a;
+    }
+    let t: f64 = (a.negate().dot_product(&ab) / length_squared).clamp(0.0, 1.0);
+    a.add_vector(&ab.multiply(t))
+}
+
+fn get_closest_point_on_triangle(a: &Vector3D, b: &Vector3D, c: &Vector3D) -> Vector3D {
+    let normal: Vector3D = b.subtract_vector(a).cross_product(&c.subtract_vector(a));
+    let length_squared: f64 = normal.get_length_squared();
+
+    if length_squared > 0.0 {
+        let projected: Vector3D = normal.multiply(a.dot_product(&normal) / length_squared);
+        let (u, v, w): (f64, f64, f64) = get_barycentric(&projected, a, b, c);
+        if u >= 0.0 && v >= 0.0 && w >= 0.0 {
+            return projected;
+        }
+    }
+
+    let candidates: [Vector3D; 3] = [
+        get_closest_point_on_segment(a, b),
+        get_closest_point_on_segment(b, c),
+        get_closest_point_on_segment(c, a),
+    ];
+    let mut closest: Vector3D = candidates[0];
+    for candidate in candidates.iter() {
+        if candidate.get_length_squared() < closest.get_length_squared() {
+            closest = *candidate;
+        }
+    }
+    closest
+}
+
+/// Returns the distance between two convex vertex sets, or 0.0 when they
+/// overlap.
+pub fn gjk_distance(a: &[Vector3D], b: &[Vector3D]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::MAX;
+    }
+    if gjk_intersection(a, b).is_some() {
+        return 0.0;
+    }
+
+    let mut simplex: Vec<Vector3D> = vec![get_support(a, b, &Vector3D::new(1.0, 0.0, 0.0)).point];
+    let mut closest: Vector3D = simplex[0];
+
+    for _ in 0..MAX_ITERATIONS {
+        let distance: f64 = closest.get_length();
+        if distance == 0.0 {
+            return 0.0;
+        }
+
+        let support: Vector3D = get_support(a, b, &closest.negate()).point;
+        let progress: f64 = closest.dot_product(&closest) - closest.dot_product(&support);
+        if progress <= distance * 1e-9 {
+            return distance;
+        }
+
+        simplex.push(support);
+        if simplex.len() > 3 {
+            simplex.remove(0);
+        }
+
+        closest = match simplex.len() {
+            1 => simplex[0],
+            2 => get_closest_point_on_segment(&simplex[0], &simplex[1]),
+            _ => get_closest_point_on_triangle(&simplex[0], &simplex[1], &simplex[2]),
+        };
+    }
+    closest.get_length()
+}
+
+/// Closest points on two separated convex vertex sets, one on each, or None
+/// when they overlap.
+pub fn gjk_closest_points(a: &[Vector3D], b: &[Vector3D]) -> Option<(Vector3D, Vector3D)> {
+    if a.is_empty() || b.is_empty() || gjk_intersection(a, b).is_some() {
+        return None;
+    }
+
+    let mut simplex: Vec<SupportPoint> = vec![get_support(a, b, &Vector3D::new(1.0, 0.0, 0.0))];
+    let mut closest: Vector3D = simplex[0].point;
+
+    for _ in 0..MAX_ITERATIONS {
+        let distance: f64 = closest.get_length();
+        let support: SupportPoint = get_support(a, b, &closest.negate());
+        let progress: f64 = closest.dot_product(&closest) - closest.dot_product(&support.point);
+        if distance == 0.0 || progress <= distance * 1e-9 {
+            break;
+        }
+
+        simplex.push(support);
+        if simplex.len() > 3 {
+            simplex.remove(0);
+        }
+
+        let points: Vec<Vector3D> = simplex.iter().map(|s| s.point).collect();
+        closest = match points.len() {
+            1 => points[0],
+            2 => get_closest_point_on_segment(&points[0], &points[1]),
+            _ => get_closest_point_on_triangle(&points[0], &points[1], &points[2]),
+        };
+    }
+
+    // The closest point of the difference is blended from the simplex, the
+    // same weights blend the support points of both sets.
+    let weights: Vec<f64> = match simplex.len() {
+        1 => vec![1.0],
+        2 => {
+            let start: Vector3D = simplex[0].point;
+            let edge: Vector3D = simplex[1].point.subtract_vector(&start);
+            let length_squared: f64 = edge.get_length_squared();
+            let t: f64 = if length_squared > 0.0 {
+                (closest.subtract_vector(&start).dot_product(&edge) / length_squared)
+                    .clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            vec![1.0 - t, t]
+        }
+        _ => {
+            let (u, v, w): (f64, f64, f64) = get_barycentric(
+                &closest,
+                &simplex[0].point,
+                &simplex[1].point,
+                &simplex[2].point,
+            );
+            vec![u, v, w]
+        }
+    };
+
+    let mut closest_a: Vector3D = Vector3D::default(0.0);
+    let mut closest_b: Vector3D = Vector3D::default(0.0);
+    for (support, weight) in simplex.iter().zip(weights) {
+        closest_a = closest_a.add_vector(&support.a.multiply(weight));
+        closest_b = closest_b.add_vector(&support.b.multiply(weight));
+    }
+    Some((closest_a, closest_b))
+}
+
+fn get_barycentric(p: &Vector3D, a: &Vector3D, b: &Vector3D, c: &Vector3D) -> (f64, f64, f64) {
+    let v0: Vector3D = b.subtract_vector(a);
+    let v1: Vector3D = c.subtract_vector(a);
+    let v2: Vector3D = p.subtract_vector(a);
+
+    let d00: f64 = v0.dot_product(&v0);
+    let d01: f64 = v0.dot_product(&v1);
+    let d11: f64 = v1.dot_product(&v1);
+    let d20: f64 = v2.dot_product(&v0);
+    let d21: f64 = v2.dot_product(&v1);
+    let denominator: f64 = d00 * d11 - d01 * d01;
+
+    if denominator == 0.0 {
+        return (1.0, 0.0, 0.0);
+    }
+
+    let v: f64 = (d11 * d20 - d01 * d21) / denominator;
+    let w: f64 = (d00 * d21 - d01 * d20) / denominator;
+    (1.0 - v - w, v, w)
+}
+
+fn get_tetrahedron_volume(simplex: &[SupportPoint]) -> f64 {
+    let a: Vector3D = simplex[0].point;
+    let ab: Vector3D = simplex[1].point.subtract_vector(&a);
+    let ac: Vector3D = simplex[2].point.subtract_vector(&a);
+    let ad: Vector3D = simplex[3].point.subtract_vector(&a);
+    ab.cross_product(&ac).dot_product(&ad).abs()
+}
+
+fn complete_simplex(
+    a: &[Vector3D],
+    b: &[Vector3D],
+    mut simplex: Vec<SupportPoint>,
+) -> Option<Vec<SupportPoint>> {
+    let axes: [Vector3D; 3] = [
+        Vector3D::new(1.0, 0.0, 0.0),
+        Vector3D::new(0.0, 1.0, 0.0),
+        Vector3D::new(0.0, 0.0, 1.0),
+    ];
+
+    while simplex.len() < 4 {
+        let directions: Vec<Vector3D> = match simplex.len() {
+            1 => axes
+                .iter()
+                .flat_map(|axis| [*axis, axis.negate()])
+                .collect(),
+            2 => {
+                let line: Vector3D = simplex[1].point.subtract_vector(&simplex[0].point);
+                let perpendicular: Vector3D = get_perpendicular(&line);
+                let other: Vector3D = line.cross_product(&perpendicular);
+                vec![perpendicular, perpendicular.negate(), other, other.negate()]
+            }
+            _ => {
+                let ab: Vector3D = simplex[1].point.subtract_vector(&simplex[0].point);
+                let ac: Vector3D = simplex[2].point.subtract_vector(&simplex[0].point);
+                let normal: Vector3D = ab.cross_product(&ac);
+                vec![normal, normal.negate()]
+            }
+        };
+
+        let mut added: bool = false;
+        for direction in directions {
+            let support: SupportPoint = get_support(a, b, &direction);
+            let is_new: bool = simplex
+                .iter()
+                .all(|s| s.point.get_distance(&support.point) > 0.0);
+            if !is_new {
+                continue;
+            }
+
+            simplex.push(support);
+            let is_degenerate: bool = match simplex.len() {
+                2 => false,
+                3 => {
+                    let ab: Vector3D = simplex[1].point.subtract_vector(&simplex[0].point);
+                    let ac: Vector3D = simplex[2].point.subtract_vector(&simplex[0].point);
+                    ab.cross_product(&ac).get_length_squared() == 0.0
+                }
+                _ => get_tetrahedron_volume(&simplex) == 0.0,
+            };
+
+            if is_degenerate {
+                simplex.pop();
+                continue;
+            }
+            added = true;
+            break;
+        }
+
+        if !added {
+            return None;
+        }
+    }
+    Some(simplex)
+}
+
+struct PolytopeFace {
+    indices: [usize; 3],
+    normal: Vector3D,
+    distance: f64,
+}
+
+fn get_face(
+    vertices: &[SupportPoint],
+    indices: [usize; 3],
+    interior: &Vector3D,
+) -> Option<PolytopeFace> {
+    let a: Vector3D = vertices[indices[0]].point;
+    let b: Vector3D = vertices[indices[1]].point;
+    let c: Vector3D = vertices[indices[2]].point;
+
+    let normal: Vector3D = b.subtract_vector(&a).cross_product(&c.subtract_vector(&a));
+    if normal.get_length_squared() == 0.0 {
+        return None;
+    }
+
+    let mut normal: Vector3D = normal.normalize();
+    let mut indices: [usize; 3] = indices;
+
+    // The origin can lie on a face plane, so faces are oriented away from a
+    // point inside the polytope instead.
+    if normal.dot_product(&a.subtract_vector(interior)) < 0.0 {
+        normal = normal.negate();
+        indices.swap(1, 2);
+    }
+    let distance: f64 = normal.dot_product(&a);
+
+    Some(PolytopeFace {
+        indices,
+        normal,
+        distance,
+    })
+}
+
+fn get_closest_face_idx(faces: &[PolytopeFace]) -> usize {
+    let mut closest_idx: usize = 0;
+    for (idx, face) in faces.iter().enumerate() {
+        if face.distance < faces[closest_idx].distance {
+            closest_idx = idx;
+        }
+    }
+    closest_idx
+}
+
+fn add_horizon_edge(edges: &mut Vec<(usize, usize)>, edge: (usize, usize)) {
+    let reverse_idx: Option<usize> = edges.iter().position(|e| *e == (edge.1, edge.0));
+    match reverse_idx {
+        Some(idx) => {
+            edges.remove(idx);
+        }
+        None => edges.push(edge),
+    }
+}
+
+/// Expands the GJK simplex into the Minkowski difference boundary and
+/// returns the penetration vector (pointing from `a` towards `b`) and the
+/// contact point.
+pub fn epa_penetration(
+    a: &[Vector3D],
+    b: &[Vector3D],
+    simplex: Vec<SupportPoint>,
+) -> Option<(Vector3D, Vector3D)> {
+    let mut simplex: Vec<SupportPoint> = simplex;
+    if simplex.len() == 4 && get_tetrahedron_volume(&simplex) == 0.0 {
+        simplex.pop();
+    }
+    let mut vertices: Vec<SupportPoint> = complete_simplex(a, b, simplex)?;
+    let interior: Vector3D = vertices
+        .iter()
+        .fold(Vector3D::default(0.0), |sum, v| sum.add_vector(&v.point))
+        .divide(4.0);
+
+    let mut faces: Vec<PolytopeFace> = [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]]
+        .iter()
+        .filter_map(|indices| get_face(&vertices, *indices, &interior))
+        .collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        if faces.is_empty() {
+            return None;
+        }
+
+        let closest_idx: usize = get_closest_face_idx(&faces);
+        let normal: Vector3D = faces[closest_idx].normal;
+        let distance: f64 = faces[closest_idx].distance;
+        let support: SupportPoint = get_support(a, b, &normal);
+        let support_distance: f64 = support.point.dot_product(&normal);
+
+        let tolerance: f64 = (distance.abs() * 1e-6).max(1e-9);
+        if support_distance - distance <= tolerance {
+            break;
+        }
+
+        let support_idx: usize = vertices.len();
+        vertices.push(support);
+
+        let mut horizon: Vec<(usize, usize)> = Vec::new();
+        faces.retain(|face| {
+            let v0: Vector3D = vertices[face.indices[0]].point;
+            let is_visible: bool =
+                face.normal.dot_product(&support.point.subtract_vector(&v0)) > 0.0;
+            if is_visible {
+                let [i0, i1, i2] = face.indices;
+                add_horizon_edge(&mut horizon, (i0, i1));
+                add_horizon_edge(&mut horizon, (i1, i2));
+                add_horizon_edge(&mut horizon, (i2, i0));
+            }
+            !is_visible
+        });
+
+        for (i0, i1) in horizon {
+            if let Some(face) = get_face(&vertices, [i0, i1, support_idx], &interior) {
+                faces.push(face);
+            }
+        }
+    }
+
+    if faces.is_empty() {
+        return None;
+    }
+
+    let face: &PolytopeFace = &faces[get_closest_face_idx(&faces)];
+    let [i0, i1, i2] = face.indices;
+    let projected: Vector3D = face.normal.multiply(face.distance);
+    let (u, v, w): (f64, f64, f64) = get_barycentric(
+        &projected,
+        &vertices[i0].point,
+        &vertices[i1].point,
+        &vertices[i2].point,
+    );
+
+    let contact_a: Vector3D = vertices[i0]
+        .a
+        .multiply(u)
+        .add_vector(&vertices[i1].a.multiply(v))
+        .add_vector(&vertices[i2].a.multiply(w));
+    let contact_b: Vector3D = vertices[i0]
+        .b
+        .multiply(u)
+        .add_vector(&vertices[i1].b.multiply(v))
+        .add_vector(&vertices[i2].b.multiply(w));
+
+    let mtv: Vector3D = face.normal.multiply(face.distance);
+    let contact_point: Vector3D = contact_a.get_midpoint(&contact_b);
+    Some((mtv, contact_point))
+}
+
+pub fn gjk_epa_intersection(a: &[Vector3D], b: &[Vector3D]) -> Option<(Vector3D, Vector3D)> {
+    let simplex: Vec<SupportPoint> = gjk_intersection(a, b)?;
+    epa_penetration(a, b, simplex)
+}
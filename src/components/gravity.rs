@@ -0,0 +1,388 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::physics::Physics;
+use crate::components::vectors::Vector3D;
+
+use rayon::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GravitySolver {
+    Disabled,
+    Pairwise,
+    BarnesHut,
+}
+
+impl GravitySolver {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            GravitySolver::Disabled => "Disabled",
+            GravitySolver::Pairwise => "Pairwise",
+            GravitySolver::BarnesHut => "Barnes-Hut",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GravitySolver> {
+        match name.to_lowercase().as_str() {
+            "none" | "disabled" => Some(GravitySolver::Disabled),
+            "pairwise" | "exact" => Some(GravitySolver::Pairwise),
+            "barnes-hut" | "barnes_hut" | "bh" => Some(GravitySolver::BarnesHut),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> GravitySolver {
+        match self {
+            GravitySolver::Disabled => GravitySolver::Pairwise,
+            GravitySolver::Pairwise => GravitySolver::BarnesHut,
+            GravitySolver::BarnesHut => GravitySolver::Disabled,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct GravityBody {
+    position: Vector3D,
+    mass: f64,
+    g_const: f64,
+}
+
+impl GravityBody {
+    fn from_physics(physics: &Physics) -> GravityBody {
+        GravityBody {
+            position: physics.get_position(),
+            mass: physics.mass,
+            g_const: physics.g_const,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct OctreeNode {
+    center: Vector3D,
+    half_size: f64,
+    mass: f64,
+    center_of_mass: Vector3D,
+    children: Vec<usize>,
+    bodies: Vec<usize>,
+}
+
+impl OctreeNode {
+    fn contains(&self, position: &Vector3D) -> bool {
+        let offset: Vector3D = position.subtract_vector(&self.center).abs();
+        let half_size: f64 = self.half_size;
+        offset.x <= half_size && offset.y <= half_size && offset.z <= half_size
+    }
+}
+
+struct Octree {
+    nodes: Vec<OctreeNode>,
+    max_depth: usize,
+}
+
+impl Octree {
+    fn new(bodies: &[GravityBody]) -> Octree {
+        let mut octree: Octree = Octree {
+            nodes: Vec::new(),
+            max_depth: 32,
+        };
+
+        if bodies.is_empty() {
+            return octree;
+        }
+
+        let mut min: Vector3D = bodies[0].position;
+        let mut max: Vector3D = bodies[0].position;
+        for body in bodies {
+            min = min.component_min(&body.position);
+            max = max.component_max(&body.position);
+        }
+
+        let center: Vector3D = min.get_midpoint(&max);
+        let extent: Vector3D = max.subtract_vector(&min);
+        let half_size: f64 = extent.x.max(extent.y).max(extent.z) / 2.0 + 1.0;
+
+        let indices: Vec<usize> = (0..bodies.len()).collect();
+        octree.build_node(bodies, indices, center, half_size, 0);
+        octree
+    }
+
+    fn get_octant(center: &Vector3D, position: &Vector3D) -> usize {
+        let mut octant: usize = 0;
+        if position.x >= center.x {
+            octant |= 1;
+        }
+        if position.y >= center.y {
+            octant |= 2;
+        }
+        if position.z >= center.z {
+            octant |= 4;
+        }
+        octant
+    }
+
+    fn get_octant_center(center: &Vector3D, half_size: f64, octant: usize) -> Vector3D {
+        let quarter: f64 = half_size / 2.0;
+        let x: f64 = if octant & 1 != 0 { quarter } else { -quarter };
+        let y: f64 = if octant & 2 != 0 { quarter } else { -quarter };
+        let z: f64 = if octant & 4 != 0 { quarter } else { -quarter };
+        center.add_elements(x, y, z)
+    }
+
+    fn build_node(
+        &mut self,
+        bodies: &[GravityBody],
+        indices: Vec<usize>,
+        center: Vector3D,
+        half_size: f64,
+        depth: usize,
+    ) -> usize {
+        let mut mass: f64 = 0.0;
+        let mut weighted_position: Vector3D = Vector3D::default(0.0);
+        for &idx in &indices {
+            let body: &GravityBody = &bodies[idx];
+            mass += body.mass;
+            weighted_position = weighted_position.add_vector(&body.position.multiply(body.mass));
+        }
+
+        let center_of_mass: Vector3D = if mass > 0.0 {
+            weighted_position.divide(mass)
+        } else {
+            center
+        };
+
+        let node_idx: usize = self.nodes.len();
+        self.nodes.push(OctreeNode {
+            center,
+            half_size,
+            mass,
+            center_of_mass,
+            children: Vec::new(),
+            bodies: Vec::new(),
+        });
+
+        if indices.len() <= 1 || depth >= self.max_depth {
+            self.nodes[node_idx].bodies = indices;
+            return node_idx;
+        }
+
+        let mut octants: Vec<Vec<usize>> = vec![Vec::new(); 8];
+        for idx in indices {
+            let octant: usize = Self::get_octant(&center, &bodies[idx].position);
+            octants[octant].push(idx);
+        }
+
+        let mut children: Vec<usize> = Vec::new();
+        for (octant, octant_indices) in octants.into_iter().enumerate() {
+            if octant_indices.is_empty() {
+                continue;
+            }
+            let child_center: Vector3D = Self::get_octant_center(&center, half_size, octant);
+            let child_half_size: f64 = half_size / 2.0;
+            let child_idx: usize = self.build_node(
+                bodies,
+                octant_indices,
+                child_center,
+                child_half_size,
+                depth + 1,
+            );
+            children.push(child_idx);
+        }
+
+        self.nodes[node_idx].children = children;
+        node_idx
+    }
+
+    fn get_acceleration(
+        &self,
+        bodies: &[GravityBody],
+        body_idx: usize,
+        theta: f64,
+        softening: f64,
+    ) -> Vector3D {
+        let mut acceleration: Vector3D = Vector3D::default(0.0);
+        if self.nodes.is_empty() {
+            return acceleration;
+        }
+
+        let body: &GravityBody = &bodies[body_idx];
+        let mut stack: Vec<usize> = vec![0];
+
+        while let Some(node_idx) = stack.pop() {
+            let node: &OctreeNode = &self.nodes[node_idx];
+
+            if node.children.is_empty() {
+                for &other_idx in &node.bodies {
+                    if other_idx == body_idx {
+                        continue;
+                    }
+                    let other: &GravityBody = &bodies[other_idx];
+                    let other_acceleration: Vector3D = Gravity::get_point_acceleration(
+                        body,
+                        &other.position,
+                        other.mass,
+                        softening,
+                    );
+                    acceleration = acceleration.add_vector(&other_acceleration);
+                }
+                continue;
+            }
+
+            let distance: f64 = node.center_of_mass.get_distance(&body.position);
+            let size: f64 = node.half_size * 2.0;
+            let is_far: bool = distance > 0.0 && size / distance < theta;
+
+            if is_far && !node.contains(&body.position) {
+                let node_acceleration: Vector3D = Gravity::get_point_acceleration(
+                    body,
+                    &node.center_of_mass,
+                    node.mass,
+                    softening,
+                );
+                acceleration = acceleration.add_vector(&node_acceleration);
+            } else {
+                stack.extend(node.children.iter());
+            }
+        }
+
+        acceleration
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Gravity {
+    pub solver: GravitySolver,
+    pub theta: f64,
+    pub softening: f64,
+}
+
+impl Gravity {
+    pub fn new(solver: GravitySolver, theta: f64, softening: f64) -> Gravity {
+        Gravity {
+            solver,
+            theta,
+            softening,
+        }
+    }
+
+    pub fn set_solver(&mut self, solver: GravitySolver) {
+        self.solver = solver;
+    }
+
+    pub fn set_theta(&mut self, theta: f64) {
+        self.theta = theta;
+    }
+
+    pub fn set_softening(&mut self, softening: f64) {
+        self.softening = softening;
+    }
+
+    fn get_point_acceleration(
+        body: &GravityBody,
+        position: &Vector3D,
+        mass: f64,
+        softening: f64,
+    ) -> Vector3D {
+        let direction: Vector3D = position.subtract_vector(&body.position);
+        let distance_squared: f64 = direction.get_length_squared() + softening.powi(2);
+        if distance_squared == 0.0 {
+            return Vector3D::default(0.0);
+        }
+
+        let inverse_cube: f64 = 1.0 / (distance_squared * distance_squared.sqrt());
+        direction.multiply(body.g_const * mass * inverse_cube)
+    }
+
+    fn get_bodies(objects: &[BodyType]) -> Vec<GravityBody> {
+        objects
+            .iter()
+            .map(|object| GravityBody::from_physics(object.physics()))
+            .collect()
+    }
+
+    fn get_pairwise_accelerations(&self, bodies: &[GravityBody]) -> Vec<Vector3D> {
+        let softening: f64 = self.softening;
+        (0..bodies.len())
+            .into_par_iter()
+            .map(|i| {
+                let body: &GravityBody = &bodies[i];
+                let mut acceleration: Vector3D = Vector3D::default(0.0);
+                for (j, other) in bodies.iter().enumerate() {
+                    if i == j {
+                        continue;
+                    }
+                    let other_acceleration: Vector3D =
+                        Self::get_point_acceleration(body, &other.position, other.mass, softening);
+                    acceleration = acceleration.add_vector(&other_acceleration);
+                }
+                acceleration
+            })
+            .collect()
+    }
+
+    fn get_barnes_hut_accelerations(&self, bodies: &[GravityBody]) -> Vec<Vector3D> {
+        let octree: Octree = Octree::new(bodies);
+        (0..bodies.len())
+            .into_par_iter()
+            .map(|i| octree.get_acceleration(bodies, i, self.theta, self.softening))
+            .collect()
+    }
+
+    fn get_solver_accelerations(
+        &self,
+        solver: GravitySolver,
+        bodies: &[GravityBody],
+    ) -> Vec<Vector3D> {
+        match solver {
+            GravitySolver::Disabled => vec![Vector3D::default(0.0); bodies.len()],
+            GravitySolver::Pairwise => self.get_pairwise_accelerations(bodies),
+            GravitySolver::BarnesHut => self.get_barnes_hut_accelerations(bodies),
+        }
+    }
+
+    pub fn get_accelerations(&self, objects: &[BodyType]) -> Vec<Vector3D> {
+        let bodies: Vec<GravityBody> = Self::get_bodies(objects);
+        self.get_solver_accelerations(self.solver, &bodies)
+    }
+
+    // Accelerations with the bodies moved to trial positions, which lets
+    // multi-stage integrators re-evaluate gravity within a step.
+    pub fn get_accelerations_at(
+        &self,
+        objects: &[BodyType],
+        positions: &[Vector3D],
+    ) -> Vec<Vector3D> {
+        let mut bodies: Vec<GravityBody> = Self::get_bodies(objects);
+        for (body, position) in bodies.iter_mut().zip(positions) {
+            body.position = *position;
+        }
+        self.get_solver_accelerations(self.solver, &bodies)
+    }
+
+    /// Returns the (max, mean) relative error of Barnes-Hut against the
+    /// exact pairwise accelerations for the current scene.
+    pub fn compare_solvers(&self, objects: &[BodyType]) -> (f64, f64) {
+        let bodies: Vec<GravityBody> = Self::get_bodies(objects);
+        let exact: Vec<Vector3D> = self.get_solver_accelerations(GravitySolver::Pairwise, &bodies);
+        let approx: Vec<Vector3D> =
+            self.get_solver_accelerations(GravitySolver::BarnesHut, &bodies);
+
+        let mut max_error: f64 = 0.0;
+        let mut total_error: f64 = 0.0;
+        for (exact, approx) in exact.iter().zip(approx.iter()) {
+            let exact_length: f64 = exact.get_length();
+            if exact_length == 0.0 {
+                continue;
+            }
+            let error: f64 = approx.subtract_vector(exact).get_length() / exact_length;
+            max_error = max_error.max(error);
+            total_error += error;
+        }
+
+        let mean_error: f64 = if bodies.is_empty() {
+            0.0
+        } else {
+            total_error / bodies.len() as f64
+        };
+        (max_error, mean_error)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::abstracts::body::Body;
+use crate::components::physics::Physics;
+use crate::components::simulation::Simulation;
+use crate::components::vectors::Vector3D;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BodyState {
+    pub index: usize,
+    pub position: Vector3D,
+    pub velocity: Vector3D,
+    pub angular_velocity: Vector3D,
+    pub mass: f64,
+}
+
+impl BodyState {
+    pub fn from_physics(index: usize, physics: &Physics) -> BodyState {
+        BodyState {
+            index,
+            position: physics.get_position(),
+            velocity: physics.velocity,
+            angular_velocity: physics.angular_velocity,
+            mass: physics.mass,
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        format!(
+            "Body {}:  Mass: {:.2}  Position: {}  Velocity: {}  Angular Velocity: {}",
+            self.index,
+            self.mass,
+            self.position.to_string(),
+            self.velocity.to_string(),
+            self.angular_velocity.to_string()
+        )
+    }
+}
+
+pub struct HeadlessRunner {
+    pub simulation: Simulation,
+}
+
+impl HeadlessRunner {
+    pub fn new(simulation: Simulation) -> HeadlessRunner {
+        HeadlessRunner { simulation }
+    }
+
+    pub fn run_steps(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.simulation.compute_objects();
+        }
+    }
+
+    pub fn run_for(&mut self, sim_time: f64) {
+        let end_time: f64 = self.simulation.sim_time + sim_time;
+        while self.simulation.sim_time < end_time {
+            self.simulation.compute_objects();
+        }
+    }
+
+    pub fn get_body_states(&self) -> Vec<BodyState> {
+        let objects = &self.simulation.objects;
+        let body_states: Vec<BodyState> = objects
+            .iter()
+            .enumerate()
+            .map(|(idx, body)| BodyState::from_physics(idx, body.physics()))
+            .collect();
+        body_states
+    }
+
+    pub fn report(&self) {
+        let simulation: &Simulation = &self.simulation;
+        println!("Simulation report");
+        println!("Steps:  {}", simulation.step_count);
+        println!("Sim Time:  {:.4} s", simulation.sim_time);
+        println!("Objects:  {}", simulation.objects.len());
+
+        for body_state in self.get_body_states() {
+            println!("{}", body_state.get_text());
+        }
+
+        if let Some(drift) = simulation.diagnostics.get_drift() {
+            println!("Energy Drift:  {:+.3e}", drift.energy);
+            println!("Linear Momentum Drift:  {:.3e}", drift.linear_momentum);
+            println!("Angular Momentum Drift:  {:.3e}", drift.angular_momentum);
+            println!("Center of Mass Drift:  {:.3e}", drift.center_of_mass);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::components::vectors::Vector3D;
+
+#[derive(Clone, Debug, Default)]
+pub struct LinearState {
+    pub positions: Vec<Vector3D>,
+    pub velocities: Vec<Vector3D>,
+}
+
+impl LinearState {
+    pub fn new(positions: Vec<Vector3D>, velocities: Vec<Vector3D>) -> LinearState {
+        LinearState {
+            positions,
+            velocities,
+        }
+    }
+
+    // Returns the state advanced by the given rates, used for the trial
+    // states of multi-stage integrators.
+    fn get_offset(
+        &self,
+        velocities: &[Vector3D],
+        accelerations: &[Vector3D],
+        dt: f64,
+    ) -> LinearState {
+        let positions: Vec<Vector3D> = self
+            .positions
+            .iter()
+            .zip(velocities)
+            .map(|(p, v)| p.add_vector(&v.multiply(dt)))
+            .collect();
+        let velocities: Vec<Vector3D> = self
+            .velocities
+            .iter()
+            .zip(accelerations)
+            .map(|(v, a)| v.add_vector(&a.multiply(dt)))
+            .collect();
+        LinearState::new(positions, velocities)
+    }
+}
+
+/// Evaluates the acceleration of every body for a trial state.
+pub type AccelerationFn<'a> = dyn Fn(&LinearState) -> Vec<Vector3D> + 'a;
+
+pub trait Integrator {
+    fn get_name(&self) -> &'static str;
+    fn integrate(&mut self, state: &mut LinearState, accelerations: &AccelerationFn, timestep: f64);
+}
+
+fn add_scaled(target: &mut [Vector3D], values: &[Vector3D], scale: f64) {
+    for (t, v) in target.iter_mut().zip(values) {
+        *t = t.add_vector(&v.multiply(scale));
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SemiImplicitEuler;
+
+impl SemiImplicitEuler {
+    pub fn new() -> SemiImplicitEuler {
+        SemiImplicitEuler
+    }
+}
+
+impl Integrator for SemiImplicitEuler {
+    fn get_name(&self) -> &'static str {
+        "Semi-Implicit Euler"
+    }
+
+    fn integrate(
+        &mut self,
+        state: &mut LinearState,
+        accelerations: &AccelerationFn,
+        timestep: f64,
+    ) {
+        let acceleration: Vec<Vector3D> = accelerations(state);
+        add_scaled(&mut state.velocities, &acceleration, timestep);
+        let velocities: Vec<Vector3D> = state.velocities.clone();
+        add_scaled(&mut state.positions, &velocities, timestep);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct VelocityVerlet;
+
+impl VelocityVerlet {
+    pub fn new() -> VelocityVerlet {
+        VelocityVerlet
+    }
+}
+
+impl Integrator for VelocityVerlet {
+    fn get_name(&self) -> &'static str {
+        "Velocity Verlet"
+    }
+
+    // Kick-drift-kick leapfrog. Contacts move bodies between steps, so the
+    // end of step accelerations are not reused for the next step.
+    fn integrate(
+        &mut self,
+        state: &mut LinearState,
+        accelerations: &AccelerationFn,
+        timestep: f64,
+    ) {
+        let half_step: f64 = timestep / 2.0;
+
+        let acceleration: Vec<Vector3D> = accelerations(state);
+        add_scaled(&mut state.velocities, &acceleration, half_step);
+        let velocities: Vec<Vector3D> = state.velocities.clone();
+        add_scaled(&mut state.positions, &velocities, timestep);
+
+        let acceleration: Vec<Vector3D> = accelerations(state);
+        add_scaled(&mut state.velocities, &acceleration, half_step);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RungeKutta4;
+
+impl RungeKutta4 {
+    pub fn new() -> RungeKutta4 {
+        RungeKutta4
+    }
+}
+
+impl Integrator for RungeKutta4 {
+    fn get_name(&self) -> &'static str {
+        "RK4"
+    }
+
+    fn integrate(
+        &mut self,
+        state: &mut LinearState,
+        accelerations: &AccelerationFn,
+        timestep: f64,
+    ) {
+        let half_step: f64 = timestep / 2.0;
+
+        let k1_v: Vec<Vector3D> = state.velocities.clone();
+        let k1_a: Vec<Vector3D> = accelerations(state);
+
+        let stage2: LinearState = state.get_offset(&k1_v, &k1_a, half_step);
+        let k2_a: Vec<Vector3D> = accelerations(&stage2);
+        let k2_v: Vec<Vector3D> = stage2.velocities;
+
+        let stage3: LinearState = state.get_offset(&k2_v, &k2_a, half_step);
+        let k3_a: Vec<Vector3D> = accelerations(&stage3);
+        let k3_v: Vec<Vector3D> = stage3.velocities;
+
+        let stage4: LinearState = state.get_offset(&k3_v, &k3_a, timestep);
+        let k4_a: Vec<Vector3D> = accelerations(&stage4);
+        let k4_v: Vec<Vector3D> = stage4.velocities;
+
+        for (k, weight) in [(&k1_v, 1.0), (&k2_v, 2.0), (&k3_v, 2.0), (&k4_v, 1.0)] {
+            add_scaled(&mut state.positions, k, timestep * weight / 6.0);
+        }
+        for (k, weight) in [(&k1_a, 1.0), (&k2_a, 2.0), (&k3_a, 2.0), (&k4_a, 1.0)] {
+            add_scaled(&mut state.velocities, k, timestep * weight / 6.0);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum IntegratorType {
+    SemiImplicitEuler(SemiImplicitEuler),
+    VelocityVerlet(VelocityVerlet),
+    RungeKutta4(RungeKutta4),
+}
+
+impl IntegratorType {
+    pub fn from_name(name: &str) -> Option<IntegratorType> {
+        match name.to_lowercase().as_str() {
+            "euler" | "semi-implicit-euler" => {
+                Some(IntegratorType::SemiImplicitEuler(SemiImplicitEuler::new()))
+            }
+            "verlet" | "velocity-verlet" | "leapfrog" => {
+                Some(IntegratorType::VelocityVerlet(VelocityVerlet::new()))
+            }
+            "rk4" | "runge-kutta" => Some(IntegratorType::RungeKutta4(RungeKutta4::new())),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> IntegratorType {
+        match self {
+            IntegratorType::SemiImplicitEuler(_) => {
+                IntegratorType::VelocityVerlet(VelocityVerlet::new())
+            }
+            IntegratorType::VelocityVerlet(_) => IntegratorType::RungeKutta4(RungeKutta4::new()),
+            IntegratorType::RungeKutta4(_) => {
+                IntegratorType::SemiImplicitEuler(SemiImplicitEuler::new())
+            }
+        }
+    }
+}
+
+impl Integrator for IntegratorType {
+    fn get_name(&self) -> &'static str {
+        match self {
+            IntegratorType::SemiImplicitEuler(i) => i.get_name(),
+            IntegratorType::VelocityVerlet(i) => i.get_name(),
+            IntegratorType::RungeKutta4(i) => i.get_name(),
+        }
+    }
+
+    fn integrate(
+        &mut self,
+        state: &mut LinearState,
+        accelerations: &AccelerationFn,
+        timestep: f64,
+    ) {
+        match self {
+            IntegratorType::SemiImplicitEuler(i) => i.integrate(state, accelerations, timestep),
+            IntegratorType::VelocityVerlet(i) => i.integrate(state, accelerations, timestep),
+            IntegratorType::RungeKutta4(i) => i.integrate(state, accelerations, timestep),
+        }
+    }
+}
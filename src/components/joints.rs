@@ -0,0 +1,899 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::physics::Physics;
+use crate::components::vectors::Matrix3;
+use crate::components::vectors::Quaternion;
+use crate::components::vectors::Vector3D;
+
+use std::collections::HashSet;
+
+fn get_skew(v: &Vector3D) -> Matrix3 {
+    Matrix3::new([[0.0, -v.z, v.y], [v.z, 0.0, -v.x], [-v.y, v.x, 0.0]])
+}
+
+// Any unit vector perpendicular to the given one, used as hinge reference.
+fn get_perpendicular(v: &Vector3D) -> Vector3D {
+    let helper: Vector3D = if v.x.abs() < 0.9 {
+        Vector3D::new(1.0, 0.0, 0.0)
+    } else {
+        Vector3D::new(0.0, 1.0, 0.0)
+    };
+    v.cross_product(&helper).normalize()
+}
+
+/// Borrows the physics of two different bodies at once.
+pub fn get_pair_mut(objects: &mut [BodyType], i: usize, j: usize) -> (&mut Physics, &mut Physics) {
+    if i < j {
+        let (left, right) = objects.split_at_mut(j);
+        (left[i].physics_mut(), right[0].physics_mut())
+    } else {
+        let (left, right) = objects.split_at_mut(i);
+        (right[0].physics_mut(), left[j].physics_mut())
+    }
+}
+
+/// The state of both bodies of a joint for one solve. Positions stay fixed
+/// during the velocity iterations, so the anchors are only placed once.
+#[derive(Clone, Copy, Debug)]
+pub struct JointFrame {
+    pub anchor_a: Vector3D,
+    pub anchor_b: Vector3D,
+    pub r_a: Vector3D,
+    pub r_b: Vector3D,
+    pub orientation_a: Quaternion,
+    pub orientation_b: Quaternion,
+    pub inverse_mass_a: f64,
+    pub inverse_mass_b: f64,
+    pub inverse_inertia_a: Matrix3,
+    pub inverse_inertia_b: Matrix3,
+    pub bias_factor: f64,
+    pub timestep: f64,
+}
+
+impl JointFrame {
+    pub fn new(
+        a: &Physics,
+        b: &Physics,
+        anchors: (Vector3D, Vector3D),
+        is_movable: (bool, bool),
+        bias_factor: f64,
+        timestep: f64,
+    ) -> JointFrame {
+        let (anchor_a, anchor_b): (Vector3D, Vector3D) = anchors;
+        let (inverse_mass_a, inverse_inertia_a): (f64, Matrix3) =
+            Self::get_inverse_mass(a, is_movable.0);
+        let (inverse_mass_b, inverse_inertia_b): (f64, Matrix3) =
+            Self::get_inverse_mass(b, is_movable.1);
+
+        JointFrame {
+            anchor_a,
+            anchor_b,
+            r_a: anchor_a.subtract_vector(&a.transform.position),
+            r_b: anchor_b.subtract_vector(&b.transform.position),
+            orientation_a: a.transform.orientation,
+            orientation_b: b.transform.orientation,
+            inverse_mass_a,
+            inverse_mass_b,
+            inverse_inertia_a,
+            inverse_inertia_b,
+            bias_factor,
+            timestep,
+        }
+    }
+
+    fn get_inverse_mass(physics: &Physics, is_movable: bool) -> (f64, Matrix3) {
+        if is_movable {
+            (1.0 / physics.mass, physics.inverse_inertia_world)
+        } else {
+            (0.0, Matrix3::zero())
+        }
+    }
+
+    pub fn get_relative_velocity(&self, a: &Physics, b: &Physics) -> Vector3D {
+        let velocity_a: Vector3D = a
+            .velocity
+            .add_vector(&a.angular_velocity.cross_product(&self.r_a));
+        let velocity_b: Vector3D = b
+            .velocity
+            .add_vector(&b.angular_velocity.cross_product(&self.r_b));
+        velocity_b.subtract_vector(&velocity_a)
+    }
+
+    pub fn get_relative_angular_velocity(&self, a: &Physics, b: &Physics) -> Vector3D {
+        b.angular_velocity.subtract_vector(&a.angular_velocity)
+    }
+
+    /// Applies the impulse to body b at its anchor and the opposite impulse
+    /// to body a.
+    pub fn apply_impulse(&self, a: &mut Physics, b: &mut Physics, impulse: &Vector3D) {
+        let impulse_a: Vector3D = impulse.negate();
+        a.velocity = a
+            .velocity
+            .add_vector(&impulse_a.multiply(self.inverse_mass_a));
+        b.velocity = b
+            .velocity
+            .add_vector(&impulse.multiply(self.inverse_mass_b));
+
+        let angular_a: Vector3D = self.r_a.cross_product(&impulse_a);
+        let angular_b: Vector3D = self.r_b.cross_product(impulse);
+        self.apply_angular_impulse(a, b, &angular_a.negate(), &angular_b);
+    }
+
+    fn apply_angular_impulse(
+        &self,
+        a: &mut Physics,
+        b: &mut Physics,
+        impulse_a: &Vector3D,
+        impulse_b: &Vector3D,
+    ) {
+        let change_a: Vector3D = self.inverse_inertia_a.multiply_vector(impulse_a);
+        let change_b: Vector3D = self.inverse_inertia_b.multiply_vector(impulse_b);
+        a.angular_velocity = a.angular_velocity.subtract_vector(&change_a);
+        b.angular_velocity = b.angular_velocity.add_vector(&change_b);
+    }
+
+    pub fn apply_torque_impulse(&self, a: &mut Physics, b: &mut Physics, impulse: &Vector3D) {
+        self.apply_angular_impulse(a, b, impulse, impulse);
+    }
+
+    // Relates an impulse at the anchors to the change in their relative
+    // velocity.
+    fn get_point_mass_matrix(&self) -> Matrix3 {
+        let skew_a: Matrix3 = get_skew(&self.r_a);
+        let skew_b: Matrix3 = get_skew(&self.r_b);
+        let rotation_a: Matrix3 = skew_a.multiply(&self.inverse_inertia_a).multiply(&skew_a);
+        let rotation_b: Matrix3 = skew_b.multiply(&self.inverse_inertia_b).multiply(&skew_b);
+        Matrix3::identity()
+            .scale(self.inverse_mass_a + self.inverse_mass_b)
+            .subtract(&rotation_a)
+            .subtract(&rotation_b)
+    }
+
+    pub fn get_direction_mass(&self, direction: &Vector3D) -> f64 {
+        let cross_a: Vector3D = self.r_a.cross_product(direction);
+        let cross_b: Vector3D = self.r_b.cross_product(direction);
+        let rotation_a: f64 = self
+            .inverse_inertia_a
+            .multiply_vector(&cross_a)
+            .dot_product(&cross_a);
+        let rotation_b: f64 = self
+            .inverse_inertia_b
+            .multiply_vector(&cross_b)
+            .dot_product(&cross_b);
+        self.inverse_mass_a + self.inverse_mass_b + rotation_a + rotation_b
+    }
+
+    fn get_angular_mass(&self, axis: &Vector3D) -> f64 {
+        let inverse_inertia: Matrix3 = self.inverse_inertia_a.add(&self.inverse_inertia_b);
+        inverse_inertia.multiply_vector(axis).dot_product(axis)
+    }
+
+    fn get_bias(&self, error: f64) -> f64 {
+        self.bias_factor * error / self.timestep
+    }
+}
+
+// The three linear rows shared by the ball-socket, hinge and fixed joints.
+#[derive(Clone, Copy, Debug)]
+struct PointConstraint {
+    impulse: Vector3D,
+    error: Vector3D,
+}
+
+impl PointConstraint {
+    fn new() -> PointConstraint {
+        PointConstraint {
+            impulse: Vector3D::default(0.0),
+            error: Vector3D::default(0.0),
+        }
+    }
+
+    fn prepare(&mut self, frame: &JointFrame) {
+        self.impulse = Vector3D::default(0.0);
+        self.error = frame.anchor_b.subtract_vector(&frame.anchor_a);
+    }
+
+    fn solve(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        let mass: Matrix3 = frame.get_point_mass_matrix();
+        if mass.get_determinant() == 0.0 {
+            return;
+        }
+        let bias: Vector3D = self.error.multiply(frame.bias_factor / frame.timestep);
+        let velocity: Vector3D = frame.get_relative_velocity(a, b).add_vector(&bias);
+        let impulse: Vector3D = mass.inverse().multiply_vector(&velocity).negate();
+
+        frame.apply_impulse(a, b, &impulse);
+        self.impulse = self.impulse.add_vector(&impulse);
+    }
+}
+
+pub trait Constraint {
+    fn get_name(&self) -> &'static str;
+    fn attach(&mut self, a: &Physics, b: &Physics);
+    fn prepare(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics);
+    fn solve(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics);
+    fn get_linear_impulse(&self) -> f64;
+    fn get_angular_impulse(&self) -> f64;
+}
+
+/// Keeps the anchors at a fixed distance, a rope only stops them from moving
+/// further apart.
+#[derive(Clone, Debug)]
+pub struct DistanceJoint {
+    pub length: f64,
+    pub is_rope: bool,
+    direction: Vector3D,
+    error: f64,
+    impulse: f64,
+}
+
+impl DistanceJoint {
+    pub fn new(length: f64) -> DistanceJoint {
+        DistanceJoint {
+            length,
+            is_rope: false,
+            direction: Vector3D::default(0.0),
+            error: 0.0,
+            impulse: 0.0,
+        }
+    }
+
+    pub fn rope(length: f64) -> DistanceJoint {
+        let mut joint: DistanceJoint = DistanceJoint::new(length);
+        joint.is_rope = true;
+        joint
+    }
+}
+
+impl Constraint for DistanceJoint {
+    fn get_name(&self) -> &'static str {
+        if self.is_rope {
+            "Rope"
+        } else {
+            "Distance"
+        }
+    }
+
+    fn attach(&mut self, _a: &Physics, _b: &Physics) {}
+
+    fn prepare(&mut self, frame: &JointFrame, _a: &mut Physics, _b: &mut Physics) {
+        let offset: Vector3D = frame.anchor_b.subtract_vector(&frame.anchor_a);
+        let distance: f64 = offset.get_length();
+        self.direction = if distance > 0.0 {
+            offset.divide(distance)
+        } else {
+            Vector3D::default(0.0)
+        };
+        self.error = distance - self.length;
+        self.impulse = 0.0;
+    }
+
+    fn solve(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        let mass: f64 = frame.get_direction_mass(&self.direction);
+        if mass == 0.0 {
+            return;
+        }
+        // A slack rope may close the gap within the step but not overshoot it.
+        let bias: f64 = if self.is_rope && self.error < 0.0 {
+            self.error / frame.timestep
+        } else {
+            frame.get_bias(self.error)
+        };
+        let velocity: f64 = frame
+            .get_relative_velocity(a, b)
+            .dot_product(&self.direction);
+        let mut impulse: f64 = -(velocity + bias) / mass;
+
+        if self.is_rope {
+            let total: f64 = (self.impulse + impulse).min(0.0);
+            impulse = total - self.impulse;
+        }
+        self.impulse += impulse;
+        frame.apply_impulse(a, b, &self.direction.multiply(impulse));
+    }
+
+    fn get_linear_impulse(&self) -> f64 {
+        self.impulse.abs()
+    }
+
+    fn get_angular_impulse(&self) -> f64 {
+        0.0
+    }
+}
+
+/// A damped spring between the anchors, applied once per step as a force.
+#[derive(Clone, Debug)]
+pub struct SpringJoint {
+    pub rest_length: f64,
+    pub stiffness: f64,
+    pub damping: f64,
+    impulse: f64,
+}
+
+impl SpringJoint {
+    pub fn new(rest_length: f64, stiffness: f64, damping: f64) -> SpringJoint {
+        SpringJoint {
+            rest_length,
+            stiffness,
+            damping,
+            impulse: 0.0,
+        }
+    }
+}
+
+impl Constraint for SpringJoint {
+    fn get_name(&self) -> &'static str {
+        "Spring"
+    }
+
+    fn attach(&mut self, _a: &Physics, _b: &Physics) {}
+
+    fn prepare(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        let offset: Vector3D = frame.anchor_b.subtract_vector(&frame.anchor_a);
+        let distance: f64 = offset.get_length();
+        if distance == 0.0 {
+            self.impulse = 0.0;
+            return;
+        }
+        let direction: Vector3D = offset.divide(distance);
+        let velocity: f64 = frame.get_relative_velocity(a, b).dot_product(&direction);
+
+        let force: f64 = -self.stiffness * (distance - self.rest_length) - self.damping * velocity;
+        self.impulse = force * frame.timestep;
+        frame.apply_impulse(a, b, &direction.multiply(self.impulse));
+    }
+
+    fn solve(&mut self, _frame: &JointFrame, _a: &mut Physics, _b: &mut Physics) {}
+
+    fn get_linear_impulse(&self) -> f64 {
+        self.impulse.abs()
+    }
+
+    fn get_angular_impulse(&self) -> f64 {
+        0.0
+    }
+}
+
+/// Pins the anchors together and leaves the rotation free.
+#[derive(Clone, Debug)]
+pub struct BallSocketJoint {
+    point: PointConstraint,
+}
+
+impl BallSocketJoint {
+    pub fn new() -> BallSocketJoint {
+        BallSocketJoint {
+            point: PointConstraint::new(),
+        }
+    }
+}
+
+impl Default for BallSocketJoint {
+    fn default() -> BallSocketJoint {
+        BallSocketJoint::new()
+    }
+}
+
+impl Constraint for BallSocketJoint {
+    fn get_name(&self) -> &'static str {
+        "Ball Socket"
+    }
+
+    fn attach(&mut self, _a: &Physics, _b: &Physics) {}
+
+    fn prepare(&mut self, frame: &JointFrame, _a: &mut Physics, _b: &mut Physics) {
+        self.point.prepare(frame);
+    }
+
+    fn solve(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        self.point.solve(frame, a, b);
+    }
+
+    fn get_linear_impulse(&self) -> f64 {
+        self.point.impulse.get_length()
+    }
+
+    fn get_angular_impulse(&self) -> f64 {
+        0.0
+    }
+}
+
+/// Pins the anchors together and only allows rotation about one axis,
+/// optionally between a lower and upper angle in radians.
+#[derive(Clone, Debug)]
+pub struct HingeJoint {
+    pub axis: Vector3D,
+    pub limits: Option<(f64, f64)>,
+    pub angle: f64,
+    point: PointConstraint,
+    local_axis_a: Vector3D,
+    local_axis_b: Vector3D,
+    local_reference_a: Vector3D,
+    local_reference_b: Vector3D,
+    alignment_errors: [f64; 2],
+    limit_impulse: f64,
+    angular_impulse: Vector3D,
+}
+
+impl HingeJoint {
+    pub fn new(axis: Vector3D, limits: Option<(f64, f64)>) -> HingeJoint {
+        let axis: Vector3D = axis.normalize();
+        HingeJoint {
+            axis,
+            limits,
+            angle: 0.0,
+            point: PointConstraint::new(),
+            local_axis_a: axis,
+            local_axis_b: axis,
+            local_reference_a: get_perpendicular(&axis),
+            local_reference_b: get_perpendicular(&axis),
+            alignment_errors: [0.0; 2],
+            limit_impulse: 0.0,
+            angular_impulse: Vector3D::default(0.0),
+        }
+    }
+
+    fn get_world_axis(&self, frame: &JointFrame) -> Vector3D {
+        frame.orientation_a.rotate_vector(&self.local_axis_a)
+    }
+
+    fn get_tangents(axis: &Vector3D) -> [Vector3D; 2] {
+        let tangent: Vector3D = get_perpendicular(axis);
+        [tangent, axis.cross_product(&tangent)]
+    }
+
+    fn solve_limit(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        let (lower, upper): (f64, f64) = match self.limits {
+            Some(limits) => limits,
+            None => return,
+        };
+        let error: f64 = if self.angle < lower {
+            self.angle - lower
+        } else if self.angle > upper {
+            self.angle - upper
+        } else {
+            return;
+        };
+
+        let axis: Vector3D = self.get_world_axis(frame);
+        let mass: f64 = frame.get_angular_mass(&axis);
+        if mass == 0.0 {
+            return;
+        }
+        let velocity: f64 = frame.get_relative_angular_velocity(a, b).dot_product(&axis);
+        let impulse: f64 = -(velocity + frame.get_bias(error)) / mass;
+
+        // Below the lower limit the impulse may only push the angle up, above
+        // the upper limit only down.
+        let total: f64 = if error < 0.0 {
+            (self.limit_impulse + impulse).max(0.0)
+        } else {
+            (self.limit_impulse + impulse).min(0.0)
+        };
+        let impulse: f64 = total - self.limit_impulse;
+        self.limit_impulse = total;
+        let impulse: Vector3D = axis.multiply(impulse);
+        frame.apply_torque_impulse(a, b, &impulse);
+        self.angular_impulse = self.angular_impulse.add_vector(&impulse);
+    }
+}
+
+impl Constraint for HingeJoint {
+    fn get_name(&self) -> &'static str {
+        "Hinge"
+    }
+
+    fn attach(&mut self, a: &Physics, b: &Physics) {
+        let reference: Vector3D = get_perpendicular(&self.axis);
+        let inverse_a: Quaternion = a.transform.orientation.conjugate();
+        let inverse_b: Quaternion = b.transform.orientation.conjugate();
+        self.local_axis_a = inverse_a.rotate_vector(&self.axis);
+        self.local_axis_b = inverse_b.rotate_vector(&self.axis);
+        self.local_reference_a = inverse_a.rotate_vector(&reference);
+        self.local_reference_b = inverse_b.rotate_vector(&reference);
+    }
+
+    fn prepare(&mut self, frame: &JointFrame, _a: &mut Physics, _b: &mut Physics) {
+        self.point.prepare(frame);
+        self.limit_impulse = 0.0;
+        self.angular_impulse = Vector3D::default(0.0);
+
+        let axis_a: Vector3D = self.get_world_axis(frame);
+        let axis_b: Vector3D = frame.orientation_b.rotate_vector(&self.local_axis_b);
+
+        // Tilting b about a tangent by a small angle shows up as that angle
+        // in the cross product of the two axes.
+        let misalignment: Vector3D = axis_a.cross_product(&axis_b);
+        self.alignment_errors = Self::get_tangents(&axis_a).map(|t| misalignment.dot_product(&t));
+
+        let reference_a: Vector3D = frame.orientation_a.rotate_vector(&self.local_reference_a);
+        let reference_b: Vector3D = frame.orientation_b.rotate_vector(&self.local_reference_b);
+        let sine: f64 = reference_a.cross_product(&reference_b).dot_product(&axis_a);
+        self.angle = sine.atan2(reference_a.dot_product(&reference_b));
+    }
+
+    fn solve(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        self.solve_limit(frame, a, b);
+
+        let tangents: [Vector3D; 2] = Self::get_tangents(&self.get_world_axis(frame));
+        for (tangent, error) in tangents.iter().zip(self.alignment_errors) {
+            let mass: f64 = frame.get_angular_mass(tangent);
+            if mass == 0.0 {
+                continue;
+            }
+            let velocity: f64 = frame
+                .get_relative_angular_velocity(a, b)
+                .dot_product(tangent);
+            let impulse: f64 = -(velocity + frame.get_bias(error)) / mass;
+            let impulse: Vector3D = tangent.multiply(impulse);
+            frame.apply_torque_impulse(a, b, &impulse);
+            self.angular_impulse = self.angular_impulse.add_vector(&impulse);
+        }
+
+        self.point.solve(frame, a, b);
+    }
+
+    fn get_linear_impulse(&self) -> f64 {
+        self.point.impulse.get_length()
+    }
+
+    // The alignment and limit rows together.
+    fn get_angular_impulse(&self) -> f64 {
+        self.angular_impulse.get_length()
+    }
+}
+
+/// Locks both the anchors and the relative orientation of the bodies.
+#[derive(Clone, Debug)]
+pub struct FixedJoint {
+    point: PointConstraint,
+    relative_orientation: Quaternion,
+    orientation_error: Vector3D,
+    angular_impulse: Vector3D,
+}
+
+impl FixedJoint {
+    pub fn new() -> FixedJoint {
+        FixedJoint {
+            point: PointConstraint::new(),
+            relative_orientation: Quaternion::identity(),
+            orientation_error: Vector3D::default(0.0),
+            angular_impulse: Vector3D::default(0.0),
+        }
+    }
+}
+
+impl Default for FixedJoint {
+    fn default() -> FixedJoint {
+        FixedJoint::new()
+    }
+}
+
+impl Constraint for FixedJoint {
+    fn get_name(&self) -> &'static str {
+        "Fixed"
+    }
+
+    fn attach(&mut self, a: &Physics, b: &Physics) {
+        let inverse_a: Quaternion = a.transform.orientation.conjugate();
+        self.relative_orientation = inverse_a.multiply(&b.transform.orientation);
+    }
+
+    fn prepare(&mut self, frame: &JointFrame, _a: &mut Physics, _b: &mut Physics) {
+        self.point.prepare(frame);
+        self.angular_impulse = Vector3D::default(0.0);
+
+        // The rotation that takes b from where the joint wants it to where it
+        // is, as a rotation vector.
+        let target: Quaternion = frame.orientation_a.multiply(&self.relative_orientation);
+        let error: Quaternion = frame.orientation_b.multiply(&target.conjugate());
+        let sign: f64 = if error.w < 0.0 { -1.0 } else { 1.0 };
+        self.orientation_error = Vector3D::new(error.x, error.y, error.z).multiply(2.0 * sign);
+    }
+
+    fn solve(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        let mass: Matrix3 = frame.inverse_inertia_a.add(&frame.inverse_inertia_b);
+        if mass.get_determinant() != 0.0 {
+            let bias: Vector3D = self
+                .orientation_error
+                .multiply(frame.bias_factor / frame.timestep);
+            let velocity: Vector3D = frame.get_relative_angular_velocity(a, b).add_vector(&bias);
+            let impulse: Vector3D = mass.inverse().multiply_vector(&velocity).negate();
+            frame.apply_torque_impulse(a, b, &impulse);
+            self.angular_impulse = self.angular_impulse.add_vector(&impulse);
+        }
+
+        self.point.solve(frame, a, b);
+    }
+
+    fn get_linear_impulse(&self) -> f64 {
+        self.point.impulse.get_length()
+    }
+
+    fn get_angular_impulse(&self) -> f64 {
+        self.angular_impulse.get_length()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum JointType {
+    Distance(DistanceJoint),
+    Spring(SpringJoint),
+    BallSocket(BallSocketJoint),
+    Hinge(HingeJoint),
+    Fixed(FixedJoint),
+}
+
+impl Constraint for JointType {
+    fn get_name(&self) -> &'static str {
+        match self {
+            JointType::Distance(j) => j.get_name(),
+            JointType::Spring(j) => j.get_name(),
+            JointType::BallSocket(j) => j.get_name(),
+            JointType::Hinge(j) => j.get_name(),
+            JointType::Fixed(j) => j.get_name(),
+        }
+    }
+
+    fn attach(&mut self, a: &Physics, b: &Physics) {
+        match self {
+            JointType::Distance(j) => j.attach(a, b),
+            JointType::Spring(j) => j.attach(a, b),
+            JointType::BallSocket(j) => j.attach(a, b),
+            JointType::Hinge(j) => j.attach(a, b),
+            JointType::Fixed(j) => j.attach(a, b),
+        }
+    }
+
+    fn prepare(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        match self {
+            JointType::Distance(j) => j.prepare(frame, a, b),
+            JointType::Spring(j) => j.prepare(frame, a, b),
+            JointType::BallSocket(j) => j.prepare(frame, a, b),
+            JointType::Hinge(j) => j.prepare(frame, a, b),
+            JointType::Fixed(j) => j.prepare(frame, a, b),
+        }
+    }
+
+    fn solve(&mut self, frame: &JointFrame, a: &mut Physics, b: &mut Physics) {
+        match self {
+            JointType::Distance(j) => j.solve(frame, a, b),
+            JointType::Spring(j) => j.solve(frame, a, b),
+            JointType::BallSocket(j) => j.solve(frame, a, b),
+            JointType::Hinge(j) => j.solve(frame, a, b),
+            JointType::Fixed(j) => j.solve(frame, a, b),
+        }
+    }
+
+    fn get_linear_impulse(&self) -> f64 {
+        match self {
+            JointType::Distance(j) => j.get_linear_impulse(),
+            JointType::Spring(j) => j.get_linear_impulse(),
+            JointType::BallSocket(j) => j.get_linear_impulse(),
+            JointType::Hinge(j) => j.get_linear_impulse(),
+            JointType::Fixed(j) => j.get_linear_impulse(),
+        }
+    }
+
+    fn get_angular_impulse(&self) -> f64 {
+        match self {
+            JointType::Distance(j) => j.get_angular_impulse(),
+            JointType::Spring(j) => j.get_angular_impulse(),
+            JointType::BallSocket(j) => j.get_angular_impulse(),
+            JointType::Hinge(j) => j.get_angular_impulse(),
+            JointType::Fixed(j) => j.get_angular_impulse(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Joint {
+    pub body_a: usize,
+    pub body_b: usize,
+    pub local_anchor_a: Vector3D,
+    pub local_anchor_b: Vector3D,
+    pub joint_type: JointType,
+    pub break_force: Option<f64>,
+    pub break_torque: Option<f64>,
+    pub applied_force: f64,
+    pub applied_torque: f64,
+    pub is_broken: bool,
+    frame: Option<JointFrame>,
+}
+
+impl Joint {
+    /// Connects two bodies at the given world space anchors, the anchors and
+    /// any axes are stored relative to the bodies as they are now.
+    pub fn new(
+        objects: &[BodyType],
+        body_a: usize,
+        body_b: usize,
+        anchor_a: Vector3D,
+        anchor_b: Vector3D,
+        joint_type: JointType,
+    ) -> Joint {
+        assert!(body_a != body_b, "a joint needs two different bodies");
+        let (a, b): (&Physics, &Physics) = (objects[body_a].physics(), objects[body_b].physics());
+        let mut joint_type: JointType = joint_type;
+        joint_type.attach(a, b);
+
+        Joint {
+            body_a,
+            body_b,
+            local_anchor_a: a.transform.inverse_apply_point(&anchor_a),
+            local_anchor_b: b.transform.inverse_apply_point(&anchor_b),
+            joint_type,
+            break_force: None,
+            break_torque: None,
+            applied_force: 0.0,
+            applied_torque: 0.0,
+            is_broken: false,
+            frame: None,
+        }
+    }
+
+    pub fn set_break_force(&mut self, break_force: f64) {
+        self.break_force = Some(break_force);
+    }
+
+    pub fn set_break_torque(&mut self, break_torque: f64) {
+        self.break_torque = Some(break_torque);
+    }
+
+    pub fn get_world_anchors(&self, objects: &[BodyType]) -> (Vector3D, Vector3D) {
+        let a: &Physics = objects[self.body_a].physics();
+        let b: &Physics = objects[self.body_b].physics();
+        (
+            a.transform.apply_point(&self.local_anchor_a),
+            b.transform.apply_point(&self.local_anchor_b),
+        )
+    }
+
+    /// The hinge axis in world space, if the joint has one.
+    pub fn get_world_axis(&self, objects: &[BodyType]) -> Option<Vector3D> {
+        match &self.joint_type {
+            JointType::Hinge(hinge) => {
+                let a: &Physics = objects[self.body_a].physics();
+                Some(a.transform.apply_direction(&hinge.local_axis_a))
+            }
+            _ => None,
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        objects: &mut [BodyType],
+        is_movable: &[bool],
+        bias_factor: f64,
+        timestep: f64,
+    ) {
+        let (is_movable_a, is_movable_b): (bool, bool) =
+            (is_movable[self.body_a], is_movable[self.body_b]);
+        if !is_movable_a && !is_movable_b {
+            self.frame = None;
+            return;
+        }
+
+        let anchors: (Vector3D, Vector3D) = self.get_world_anchors(objects);
+        let (a, b) = get_pair_mut(objects, self.body_a, self.body_b);
+        let is_movable: (bool, bool) = (is_movable_a, is_movable_b);
+        let frame: JointFrame = JointFrame::new(a, b, anchors, is_movable, bias_factor, timestep);
+        self.joint_type.prepare(&frame, a, b);
+        self.frame = Some(frame);
+    }
+
+    fn solve(&mut self, objects: &mut [BodyType]) {
+        if let Some(frame) = &self.frame {
+            let (a, b) = get_pair_mut(objects, self.body_a, self.body_b);
+            self.joint_type.solve(frame, a, b);
+        }
+    }
+
+    fn update_break(&mut self, timestep: f64) {
+        if self.frame.is_none() {
+            return;
+        }
+        self.applied_force = self.joint_type.get_linear_impulse() / timestep;
+        self.applied_torque = self.joint_type.get_angular_impulse() / timestep;
+        let is_force_broken: bool = self
+            .break_force
+            .is_some_and(|limit| self.applied_force > limit);
+        let is_torque_broken: bool = self
+            .break_torque
+            .is_some_and(|limit| self.applied_torque > limit);
+        self.is_broken = is_force_broken || is_torque_broken;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct JointSolver {
+    pub joints: Vec<Joint>,
+    pub iterations: usize,
+    pub bias_factor: f64,
+    pub collide_connected: bool,
+    pub draw_joints: bool,
+}
+
+impl JointSolver {
+    pub fn new(iterations: usize, bias_factor: f64) -> JointSolver {
+        JointSolver {
+            joints: Vec::new(),
+            iterations,
+            bias_factor,
+            collide_connected: false,
+            draw_joints: false,
+        }
+    }
+
+    pub fn add_joint(&mut self, joint: Joint) -> usize {
+        self.joints.push(joint);
+        self.joints.len() - 1
+    }
+
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    pub fn toggle_draw_joints(&mut self) {
+        self.draw_joints = !self.draw_joints;
+    }
+
+    pub fn get_broken_count(&self) -> usize {
+        self.joints.iter().filter(|j| j.is_broken).count()
+    }
+
+    /// Body pairs held together by an intact joint.
+    pub fn get_connected_pairs(&self) -> Vec<(usize, usize)> {
+        self.joints
+            .iter()
+            .filter(|j| !j.is_broken)
+            .map(|j| (j.body_a.min(j.body_b), j.body_a.max(j.body_b)))
+            .collect()
+    }
+
+    /// Pairs that the contact solver should skip.
+    pub fn get_ignored_pairs(&self) -> HashSet<(usize, usize)> {
+        if self.collide_connected {
+            return HashSet::new();
+        }
+        self.get_connected_pairs().into_iter().collect()
+    }
+
+    /// Moves the joints to the new body indices after bodies were replaced
+    /// or removed, joints on a body without a new index are dropped.
+    pub fn remap_bodies(&mut self, body_map: &[Option<usize>]) {
+        self.joints.retain_mut(
+            |joint| match (body_map[joint.body_a], body_map[joint.body_b]) {
+                (Some(body_a), Some(body_b)) => {
+                    joint.body_a = body_a;
+                    joint.body_b = body_b;
+                    true
+                }
+                _ => false,
+            },
+        );
+    }
+
+    /// Places the intact joints for the coming step, the bodies that are not
+    /// movable hold their joints in place.
+    pub fn prepare(&mut self, objects: &mut [BodyType], is_movable: &[bool], timestep: f64) {
+        let bias_factor: f64 = self.bias_factor;
+        for joint in self.joints.iter_mut().filter(|j| !j.is_broken) {
+            joint.prepare(objects, is_movable, bias_factor, timestep);
+        }
+    }
+
+    pub fn iterate(&mut self, objects: &mut [BodyType]) {
+        for joint in self.joints.iter_mut().filter(|j| !j.is_broken) {
+            joint.solve(objects);
+        }
+    }
+
+    /// Breaks the joints that had to hold more than their break force or
+    /// break torque.
+    pub fn update_breaks(&mut self, timestep: f64) {
+        for joint in self.joints.iter_mut().filter(|j| !j.is_broken) {
+            joint.update_break(timestep);
+        }
+    }
+}
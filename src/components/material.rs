@@ -0,0 +1,151 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CombineRule {
+    Average,
+    Min,
+    Multiply,
+    Max,
+}
+
+impl CombineRule {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            CombineRule::Average => "Average",
+            CombineRule::Min => "Min",
+            CombineRule::Multiply => "Multiply",
+            CombineRule::Max => "Max",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CombineRule> {
+        match name.to_lowercase().as_str() {
+            "average" | "avg" => Some(CombineRule::Average),
+            "min" => Some(CombineRule::Min),
+            "multiply" | "mul" => Some(CombineRule::Multiply),
+            "max" => Some(CombineRule::Max),
+            _ => None,
+        }
+    }
+
+    fn get_priority(&self) -> usize {
+        match self {
+            CombineRule::Average => 0,
+            CombineRule::Min => 1,
+            CombineRule::Multiply => 2,
+            CombineRule::Max => 3,
+        }
+    }
+
+    // When two bodies disagree the rule with the higher priority wins, so an
+    // ice body stays slippery against average materials but a multiply or
+    // max rule on the other body overrides it.
+    pub fn resolve(&self, other: &CombineRule) -> CombineRule {
+        if other.get_priority() > self.get_priority() {
+            *other
+        } else {
+            *self
+        }
+    }
+
+    pub fn combine(&self, a: f64, b: f64) -> f64 {
+        match self {
+            CombineRule::Average => (a + b) / 2.0,
+            CombineRule::Min => a.min(b),
+            CombineRule::Multiply => a * b,
+            CombineRule::Max => a.max(b),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicsMaterial {
+    pub name: &'static str,
+    pub restitution: f64,
+    pub static_friction: f64,
+    pub dynamic_friction: f64,
+    pub density: Option<f64>,
+    pub restitution_combine: CombineRule,
+    pub friction_combine: CombineRule,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> PhysicsMaterial {
+        let mut material: PhysicsMaterial = PhysicsMaterial::new(0.6, 0.6, 0.4, None);
+        material.name = "Default";
+        material
+    }
+}
+
+impl PhysicsMaterial {
+    pub fn new(
+        restitution: f64,
+        static_friction: f64,
+        dynamic_friction: f64,
+        density: Option<f64>,
+    ) -> PhysicsMaterial {
+        PhysicsMaterial {
+            name: "Custom",
+            restitution,
+            static_friction,
+            dynamic_friction: dynamic_friction.min(static_friction),
+            density,
+            restitution_combine: CombineRule::Average,
+            friction_combine: CombineRule::Average,
+        }
+    }
+
+    pub fn rubber() -> PhysicsMaterial {
+        let mut material: PhysicsMaterial = PhysicsMaterial::new(0.85, 1.0, 0.8, Some(1_100.0));
+        material.name = "Rubber";
+        material.restitution_combine = CombineRule::Max;
+        material
+    }
+
+    pub fn steel() -> PhysicsMaterial {
+        let mut material: PhysicsMaterial = PhysicsMaterial::new(0.6, 0.74, 0.57, Some(7_850.0));
+        material.name = "Steel";
+        material
+    }
+
+    pub fn ice() -> PhysicsMaterial {
+        let mut material: PhysicsMaterial = PhysicsMaterial::new(0.1, 0.05, 0.03, Some(917.0));
+        material.name = "Ice";
+        material.friction_combine = CombineRule::Min;
+        material
+    }
+
+    pub fn wood() -> PhysicsMaterial {
+        let mut material: PhysicsMaterial = PhysicsMaterial::new(0.5, 0.5, 0.4, Some(700.0));
+        material.name = "Wood";
+        material
+    }
+
+    pub fn stone() -> PhysicsMaterial {
+        let mut material: PhysicsMaterial = PhysicsMaterial::new(0.3, 0.8, 0.6, Some(2_600.0));
+        material.name = "Stone";
+        material
+    }
+
+    pub fn from_name(name: &str) -> Option<PhysicsMaterial> {
+        match name.to_lowercase().as_str() {
+            "default" => Some(PhysicsMaterial::default()),
+            "rubber" => Some(PhysicsMaterial::rubber()),
+            "steel" => Some(PhysicsMaterial::steel()),
+            "ice" => Some(PhysicsMaterial::ice()),
+            "wood" => Some(PhysicsMaterial::wood()),
+            "stone" | "rock" => Some(PhysicsMaterial::stone()),
+            _ => None,
+        }
+    }
+
+    pub fn get_restitution(&self, other: &PhysicsMaterial) -> f64 {
+        let rule: CombineRule = self.restitution_combine.resolve(&other.restitution_combine);
+        rule.combine(self.restitution, other.restitution)
+    }
+
+    pub fn get_friction(&self, other: &PhysicsMaterial) -> (f64, f64) {
+        let rule: CombineRule = self.friction_combine.resolve(&other.friction_combine);
+        let static_friction: f64 = rule.combine(self.static_friction, other.static_friction);
+        let dynamic_friction: f64 = rule.combine(self.dynamic_friction, other.dynamic_friction);
+        (static_friction, dynamic_friction.min(static_friction))
+    }
+}
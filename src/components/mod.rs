@@ -1,18 +1,35 @@
+pub mod accretion;
 pub mod backface_culling;
+pub mod broad_phase;
 pub mod camera;
+pub mod ccd;
+pub mod collision_shape;
 pub mod color;
+pub mod contact;
+pub mod contact_solver;
+pub mod diagnostics;
 pub mod draw_call;
 pub mod font;
+pub mod forces;
 pub mod frametime;
 pub mod frustum;
+pub mod gjk;
 pub mod graphics;
+pub mod gravity;
+pub mod headless;
+pub mod integrator;
+pub mod joints;
+pub mod material;
 pub mod model;
 pub mod physics;
 pub mod polygons;
 pub mod shaders;
 pub mod shape;
 pub mod simulation;
+pub mod sleep;
 pub mod text_writer;
+pub mod timestep;
+pub mod transform;
 pub mod vectors;
 pub mod vertices;
 pub mod buffer_sort;
@@ -1,39 +1,65 @@
 use crate::components::bvh::BVHNode;
+use crate::components::bvh::NarrowPhase;
+use crate::components::collision_shape::CollisionShape;
 use crate::components::color::RGBA;
+use crate::components::contact::ContactManifold;
 use crate::components::decomposition::MeshDecompose;
+use crate::components::material::PhysicsMaterial;
 use crate::components::polygons::Mesh;
-use crate::components::shaders::Light;
+use crate::components::transform::Transform;
+use crate::components::vectors::Matrix3;
+use crate::components::vectors::Quaternion;
 use crate::components::vectors::Vector3D;
 
-use rand::rngs::ThreadRng;
-use rand::Rng;
-
 #[derive(Clone, Debug)]
 pub struct Physics {
     pub mesh: Mesh,
     pub mesh_cluster: Option<Vec<Mesh>>,
-    pub position: Vector3D,
+    local_mesh: Mesh,
+    local_mesh_cluster: Option<Vec<Mesh>>,
+    bounding_radius: f64,
     pub velocity: Vector3D,
     pub acceleration: Vector3D,
     pub angular_velocity: Vector3D,
     pub angular_acceleration: Vector3D,
-    pub moment_of_inertia: Vector3D,
+    pub transform: Transform,
+    world_transform: Transform,
+    pub inertia_tensor: Matrix3,
+    pub inverse_inertia_world: Matrix3,
     pub mass: f64,
     pub g_const: f64,
     pub gravity: f64,
     pub is_stationary: bool,
+    pub is_sleeping: bool,
+    pub sleep_timer: usize,
+    pub sleep_acceleration: Option<Vector3D>,
+    pub material: PhysicsMaterial,
     pub last_contact_point: Option<Vector3D>,
 }
 
 impl Physics {
     pub fn new(mesh: Mesh, mesh_cluster: Option<Vec<Mesh>>) -> Physics {
-        let position: Vector3D = Vector3D::default(0.0);
         let velocity: Vector3D = Vector3D::default(0.0);
         let acceleration: Vector3D = Vector3D::default(0.0);
         let angular_velocity: Vector3D = Vector3D::default(0.0);
         let angular_acceleration: Vector3D = Vector3D::default(0.0);
         let mass: f64 = 1.0;
-        let moment_of_inertia: Vector3D = Self::get_moment_of_inertia(&mesh, mass);
+
+        // Geometry is kept in body space around the center of mass, the world
+        // space meshes are only a cache rebuilt from it and the transform.
+        let transform: Transform = Transform::from_position(Self::get_local_origin(&mesh));
+        let to_local: Transform = Transform::from_position(transform.position.negate());
+        let local_mesh: Mesh = mesh.get_transformed(&to_local);
+        let local_mesh_cluster: Option<Vec<Mesh>> = mesh_cluster.as_ref().map(|meshes| {
+            meshes
+                .iter()
+                .map(|mesh| mesh.get_transformed(&to_local))
+                .collect()
+        });
+
+        let bounding_radius: f64 = Self::get_local_radius(&local_mesh);
+        let inertia_tensor: Matrix3 = Self::get_inertia_tensor(&local_mesh, mass);
+        let inverse_inertia_world: Matrix3 = inertia_tensor.inverse();
         let g_const: f64 = 0.8;
         let gravity: f64 = -9.8;
         let is_stationary: bool = false;
@@ -41,87 +67,188 @@ impl Physics {
         Physics {
             mesh,
             mesh_cluster,
-            position,
+            local_mesh,
+            local_mesh_cluster,
+            bounding_radius,
             velocity,
             acceleration,
             angular_velocity,
             angular_acceleration,
-            moment_of_inertia,
+            transform,
+            world_transform: transform,
+            inertia_tensor,
+            inverse_inertia_world,
             mass,
             g_const,
             gravity,
             is_stationary,
+            is_sleeping: false,
+            sleep_timer: 0,
+            sleep_acceleration: None,
+            material: PhysicsMaterial::default(),
             last_contact_point: None,
         }
     }
 
+    fn get_local_origin(mesh: &Mesh) -> Vector3D {
+        let center_of_mass: Vector3D = mesh.get_center_of_mass();
+        if center_of_mass.x.is_finite()
+            && center_of_mass.y.is_finite()
+            && center_of_mass.z.is_finite()
+        {
+            return center_of_mass;
+        }
+        mesh.get_mesh_centroid()
+    }
+
+    fn get_local_radius(mesh: &Mesh) -> f64 {
+        mesh.polygons
+            .iter()
+            .flat_map(|polygon| polygon.get_vertices())
+            .map(|vertex| vertex.get_length())
+            .fold(0.0, f64::max)
+    }
+
+    /// Radius of the bounding sphere around the center of mass.
+    pub fn get_bounding_radius(&self) -> f64 {
+        self.bounding_radius
+    }
+
+    pub fn get_local_mesh(&self) -> &Mesh {
+        &self.local_mesh
+    }
+
+    pub fn get_local_mesh_cluster(&self) -> &Option<Vec<Mesh>> {
+        &self.local_mesh_cluster
+    }
+
+    /// The position of the center of mass, the origin of the body space.
+    pub fn get_position(&self) -> Vector3D {
+        self.transform.position
+    }
+
+    /// Moves the center of mass of the body to the position.
     pub fn set_position(&mut self, x: f64, y: f64, z: f64) {
-        self.position = Vector3D::new(x, y, z);
+        self.transform.position = Vector3D::new(x, y, z);
+        self.update_world_geometry();
     }
 
     pub fn set_velocity(&mut self, x: f64, y: f64, z: f64) {
         self.velocity = Vector3D::new(x, y, z);
+        self.wake();
     }
 
     pub fn set_angular_velocity(&mut self, x: f64, y: f64, z: f64) {
         self.angular_velocity = Vector3D::new(x, y, z);
+        self.wake();
     }
 
     pub fn set_acceleration(&mut self, x: f64, y: f64, z: f64) {
         self.acceleration = Vector3D::new(x, y, z);
+        self.wake();
+    }
+
+    pub fn sleep(&mut self) {
+        self.is_sleeping = true;
+        self.sleep_acceleration = None;
+        self.velocity = Vector3D::default(0.0);
+        self.angular_velocity = Vector3D::default(0.0);
+    }
+
+    pub fn wake(&mut self) {
+        self.is_sleeping = false;
+        self.sleep_timer = 0;
+    }
+
+    /// Stationary and sleeping bodies are left out of the integration.
+    pub fn is_frozen(&self) -> bool {
+        self.is_stationary || self.is_sleeping
+    }
+
+    pub fn has_applied_force(&self) -> bool {
+        self.acceleration.get_length() > 0.0 || self.angular_acceleration.get_length() > 0.0
+    }
+
+    pub fn set_material(&mut self, material: PhysicsMaterial) {
+        self.material = material;
+        if let Some(density) = material.density {
+            self.set_density(density);
+        }
+    }
+
+    pub fn set_density(&mut self, density: f64) {
+        let volume: f64 = self.local_mesh.get_total_volume().abs();
+        if volume > 0.0 {
+            self.set_mass(density * volume);
+        }
     }
 
     pub fn set_mass(&mut self, mass: f64) {
         self.mass = mass;
-        let mesh: &Mesh = &self.mesh;
-        let moment_of_inertia: Vector3D = Self::get_moment_of_inertia(mesh, mass);
-        self.moment_of_inertia = moment_of_inertia;
-        println!("{:#?}", moment_of_inertia);
+        let mesh: &Mesh = &self.local_mesh;
+        self.inertia_tensor = Self::get_inertia_tensor(mesh, mass);
+        self.update_inverse_inertia_world();
     }
 
-    pub fn get_moment_of_inertia(mesh: &Mesh, mass: f64) -> Vector3D {
-        let mut moment_of_inertia: Vector3D = Vector3D::default(0.0);
+    // The tensor is taken about the center of mass in the mesh's own frame,
+    // for the local mesh that is the body space of the rigid body.
+    pub fn get_inertia_tensor(mesh: &Mesh, mass: f64) -> Matrix3 {
         let center_of_mass: Vector3D = mesh.get_center_of_mass();
+        let canonical: Matrix3 =
+            Matrix3::new([[2.0, 1.0, 1.0], [1.0, 2.0, 1.0], [1.0, 1.0, 2.0]]).scale(1.0 / 120.0);
 
-        let num_tetrahedrons = mesh
-            .polygons
-            .iter()
-            .filter(|p| p.get_vertices().len() == 3)
-            .count();
-        let tetrahedron_mass: f64 = mass / (num_tetrahedrons as f64);
+        let mut covariance: Matrix3 = Matrix3::zero();
+        let mut total_volume: f64 = 0.0;
 
         for polygon in &mesh.polygons {
             let vertices: &[Vector3D] = polygon.get_vertices();
 
-            if vertices.len() > 3 {
+            if vertices.len() != 3 {
                 continue;
             }
 
-            for i in 1..vertices.len() - 1 {
-                let b: Vector3D = vertices[i].subtract_vector(&center_of_mass);
-                let c: Vector3D = vertices[i + 1].subtract_vector(&center_of_mass);
-
-                let ia: f64 =
-                    (b.y * b.y + b.z * b.z + c.y * c.y + c.z * c.z + b.y * c.y + b.z * c.z) / 60.0;
-                let ib: f64 = (b.x * c.x + b.x * c.z + b.z * c.x) / 120.0;
-                let ic: f64 = (b.x * c.x + b.x * c.y + b.y * c.x) / 120.0;
-                let id: f64 =
-                    (b.x * b.x + b.z * b.z + c.x * c.x + c.z * c.z + b.x * c.x + b.z * c.z) / 60.0;
-                let ie: f64 = (b.y * c.y + b.y * c.z + b.z * c.y) / 120.0;
-                let ig: f64 =
-                    (b.x * b.x + b.y * b.y + c.x * c.x + c.y * c.y + b.x * c.x + b.y * c.y) / 60.0;
-
-                let tetrahedron_inertia: Vector3D = Vector3D::new(
-                    tetrahedron_mass * (id + ig - 2.0 * ie),
-                    tetrahedron_mass * (ia + ig - 2.0 * ic),
-                    tetrahedron_mass * (ia + id - 2.0 * ib),
-                );
+            let a: Vector3D = vertices[0].subtract_vector(&center_of_mass);
+            let b: Vector3D = vertices[1].subtract_vector(&center_of_mass);
+            let c: Vector3D = vertices[2].subtract_vector(&center_of_mass);
 
-                moment_of_inertia = moment_of_inertia.add_vector(&tetrahedron_inertia);
-            }
+            let transform: Matrix3 = Matrix3::from_columns(&a, &b, &c);
+            let determinant: f64 = transform.get_determinant();
+            let tetrahedron_covariance: Matrix3 = transform
+                .multiply(&canonical)
+                .multiply(&transform.transpose())
+                .scale(determinant);
+
+            covariance = covariance.add(&tetrahedron_covariance);
+            total_volume += determinant / 6.0;
         }
 
-        moment_of_inertia
+        if !total_volume.is_normal() {
+            return Matrix3::zero();
+        }
+
+        let covariance: Matrix3 = covariance.scale(mass / total_volume);
+        Matrix3::identity()
+            .scale(covariance.get_trace())
+            .subtract(&covariance)
+    }
+
+    pub fn update_inverse_inertia_world(&mut self) {
+        let rotation: Matrix3 = self.transform.get_rotation_matrix();
+        self.inverse_inertia_world = rotation
+            .multiply(&self.inertia_tensor.inverse())
+            .multiply(&rotation.transpose());
+    }
+
+    pub fn get_inertia_world(&self) -> Matrix3 {
+        let rotation: Matrix3 = self.transform.get_rotation_matrix();
+        rotation
+            .multiply(&self.inertia_tensor)
+            .multiply(&rotation.transpose())
+    }
+
+    pub fn get_angular_momentum(&self) -> Vector3D {
+        self.get_inertia_world()
+            .multiply_vector(&self.angular_velocity)
     }
 
     fn rotate_x(&self, xyz_point: &Vector3D, theta: f64) -> Vector3D {
@@ -155,37 +282,14 @@ impl Physics {
         f64::max(min_value, value).min(max_value)
     }
 
-    fn get_random_direction(&self) -> Vector3D {
-        println!("Retrieving Random Direction");
-        let mut rng: ThreadRng = rand::thread_rng();
-        let x_rnd: f64 = rng.gen_range(-1.0..1.0);
-        let y_rnd: f64 = rng.gen_range(-1.0..1.0);
-        let z_rnd: f64 = rng.gen_range(-1.0..1.0);
-
-        let direction = Vector3D::new(x_rnd, y_rnd, z_rnd);
-        direction
-    }
-
-    fn ensure_direction(&self, mut direction: Vector3D) -> Vector3D {
-        if direction.get_length() == 0.0 {
-            direction = self.get_random_direction();
-        }
-        direction
-    }
-
     pub fn freeze_velocity(&mut self) {
         self.velocity = Vector3D::default(0.0);
         self.acceleration = Vector3D::default(0.0);
     }
 
-    fn apply_gravity(&mut self) {
-        let mut acceleration = self.acceleration;
-        acceleration.y = acceleration.y + self.gravity;
-        self.acceleration = acceleration;
-    }
-
     fn set_cluster_color(&mut self, color: RGBA) {
-        if let Some(mesh_cluster) = self.mesh_cluster.as_mut() {
+        let clusters = [self.mesh_cluster.as_mut(), self.local_mesh_cluster.as_mut()];
+        for mesh_cluster in clusters.into_iter().flatten() {
             for mesh in mesh_cluster.iter_mut() {
                 mesh.set_uniform_color(color);
             }
@@ -204,18 +308,6 @@ impl Physics {
         }
     }
 
-    fn get_mass_ratio(&self, target: &Physics) -> f64 {
-        if self.mass > target.mass {
-            let mass_ratio: f64 = target.mass / self.mass;
-            return mass_ratio;
-        } else if self.mass < target.mass {
-            let mass_ratio: f64 = self.mass / target.mass;
-            let mass_ratio: f64 = 1.0 - mass_ratio;
-            return mass_ratio;
-        }
-        0.5
-    }
-
     fn get_mesh_from_idx(&self, idx: usize) -> &Mesh {
         if idx != 0 {
             if let Some(mesh_cluster) = &self.mesh_cluster {
@@ -246,6 +338,78 @@ impl Physics {
         0
     }
 
+    pub fn get_aabb(&self) -> ([f64; 3], [f64; 3]) {
+        let (mut min, mut max): ([f64; 3], [f64; 3]) = self.mesh.bvh_node.get_bounds();
+
+        for mesh in self.get_ref_mesh_or_cluster() {
+            let (mesh_min, mesh_max): ([f64; 3], [f64; 3]) = mesh.bvh_node.get_bounds();
+            for i in 0..3 {
+                min[i] = min[i].min(mesh_min[i]);
+                max[i] = max[i].max(mesh_max[i]);
+            }
+        }
+
+        // A tessellated sphere sits inside its analytic shape.
+        let shape_aabb = self
+            .mesh
+            .collision_shape
+            .as_ref()
+            .and_then(|shape| shape.get_aabb());
+        if let Some((shape_min, shape_max)) = shape_aabb {
+            for i in 0..3 {
+                min[i] = min[i].min(shape_min[i]);
+                max[i] = max[i].max(shape_max[i]);
+            }
+        }
+
+        (min, max)
+    }
+
+    // The analytic shape of the body, or the hulls of its mesh cluster, with
+    // the mesh index each one stands for.
+    fn get_collision_shapes(&self) -> Option<Vec<(usize, &CollisionShape)>> {
+        if let Some(collision_shape) = &self.mesh.collision_shape {
+            return Some(vec![(0, collision_shape)]);
+        }
+        let shift: usize = self.get_mesh_or_cluster_idx_shift();
+        self.mesh_cluster
+            .as_ref()?
+            .iter()
+            .enumerate()
+            .map(|(idx, mesh)| Some((idx + shift, mesh.collision_shape.as_ref()?)))
+            .collect()
+    }
+
+    // Pairs where one body has an analytic shape skip the polygons as long
+    // as the other body is made of convex shapes too.
+    fn get_shape_manifolds(
+        &self,
+        target: &Physics,
+        previous: &[ContactManifold],
+    ) -> Option<Vec<ContactManifold>> {
+        if self.mesh.collision_shape.is_none() && target.mesh.collision_shape.is_none() {
+            return None;
+        }
+        let self_shapes: Vec<(usize, &CollisionShape)> = self.get_collision_shapes()?;
+        let target_shapes: Vec<(usize, &CollisionShape)> = target.get_collision_shapes()?;
+
+        let mut manifolds: Vec<ContactManifold> = Vec::new();
+        for (self_idx, self_shape) in &self_shapes {
+            for (target_idx, target_shape) in &target_shapes {
+                if let Some(mut manifold) = self_shape.get_contact(target_shape) {
+                    manifold.mesh_pair = (*self_idx, *target_idx);
+                    let previous: Option<&ContactManifold> =
+                        previous.iter().find(|m| m.mesh_pair == manifold.mesh_pair);
+                    if let Some(previous) = previous {
+                        manifold.warm_start(previous);
+                    }
+                    manifolds.push(manifold);
+                }
+            }
+        }
+        Some(manifolds)
+    }
+
     pub fn get_bounding_collisions(&self, target: &Physics) -> Vec<(usize, usize)> {
         let mut collision_idxs: Vec<(usize, usize)> = Vec::new();
         let self_idx_shift: usize = self.get_mesh_or_cluster_idx_shift();
@@ -270,15 +434,8 @@ impl Physics {
         collision_idxs
     }
 
-    pub fn apply_forces(&mut self, target: &mut Physics, timestep: f64) {
-        // if !target.is_stationary {
-        self.apply_collision(target, timestep);
-        // self.apply_attraction(target);
-        // }
-    }
-
     pub fn apply_attraction(&mut self, target: &mut Physics) {
-        let tts_distance: Vector3D = target.position.subtract_vector(&self.position);
+        let tts_distance: Vector3D = target.get_position().subtract_vector(&self.get_position());
         let distance: f64 = tts_distance.get_length();
 
         if distance > 0.0 {
@@ -292,226 +449,179 @@ impl Physics {
         }
     }
 
-    pub fn apply_collision(&mut self, target: &mut Physics, timestep: f64) {
+    /// Finds the contact manifolds between the two bodies, points that were
+    /// in the previous manifolds keep their impulses for warm starting.
+    pub fn find_contacts(
+        &mut self,
+        target: &Physics,
+        narrow_phase: NarrowPhase,
+        manifolds: &mut Vec<ContactManifold>,
+    ) {
+        if let Some(shape_manifolds) = self.get_shape_manifolds(target, manifolds) {
+            if let Some(manifold) = shape_manifolds.last() {
+                self.last_contact_point = Some(manifold.get_center());
+            }
+            *manifolds = shape_manifolds;
+            return;
+        }
+
         let bounding_collisions: Vec<(usize, usize)> = self.get_bounding_collisions(target);
         // self.set_bounding_color(target, bounding_collided);
 
         if bounding_collisions.is_empty() {
+            manifolds.clear();
             return;
         }
 
+        let mut current_manifolds: Vec<ContactManifold> = Vec::new();
+
         for (self_idx, target_idx) in bounding_collisions {
             let self_mesh: &Mesh = self.get_mesh_from_idx(self_idx);
             let target_mesh: &Mesh = target.get_mesh_from_idx(target_idx);
 
             let intersection: Option<(Vector3D, Vector3D)> =
-                self_mesh.is_intersecting_bvh(target_mesh);
+                self_mesh.is_intersecting_bvh(target_mesh, narrow_phase);
 
             if let Some((mtv, contact_point)) = intersection {
-                self.last_contact_point = Some(contact_point);
-                let direction: Vector3D = mtv.multiply(-1.0);
-                let direction: Vector3D = direction.normalize();
-                self.apply_collision_velocity(target, direction);
-                self.apply_collision_angular_velocity(target, direction, contact_point);
-                // let distance: f64 = self_mesh.get_distance_bvh(&target_mesh);
-                let distance = mtv.get_length();
-
-                if distance > 0.0 {
-                    let distance = -distance - 100.0;
-                    // let distance:f64 = distance - (1.0 / timestep);
-                    let direction: Vector3D = self.ensure_direction(direction);
-                    let mass_ratio: f64 = self.get_mass_ratio(target);
-                    let self_correction: f64 = distance * mass_ratio;
-                    let target_correction: f64 = distance - self_correction;
-
-                    self.apply_shift_correction(direction, -self_correction);
-                    target.apply_shift_correction(direction, target_correction);
+                let mut manifold: ContactManifold = ContactManifold::new(
+                    (self_idx, target_idx),
+                    &self_mesh.bvh_node,
+                    &target_mesh.bvh_node,
+                    mtv,
+                    contact_point,
+                );
+                let previous: Option<&ContactManifold> =
+                    manifolds.iter().find(|m| m.mesh_pair == manifold.mesh_pair);
+                if let Some(previous) = previous {
+                    manifold.warm_start(previous);
                 }
+
+                self.last_contact_point = Some(manifold.get_center());
+                current_manifolds.push(manifold);
             }
         }
-    }
-
-    pub fn apply_shift_correction(&mut self, direction: Vector3D, distance: f64) {
-        let self_vec: Vector3D = direction.multiply(distance);
-        self.position = self.position.add_vector(&self_vec);
-        self.update_mesh_position(&self_vec);
-    }
-
-    pub fn apply_collision_velocity(&mut self, target: &mut Physics, direction: Vector3D) {
-        let e: f64 = 0.6;
-        let v1i: f64 = self.velocity.dot_product(&direction);
-        let v2i: f64 = target.velocity.dot_product(&direction);
-
-        let m1: f64 = self.mass;
-        let m2: f64 = target.mass;
-
-        let v1f: f64 = ((m1 - e * m2) * v1i + (1.0 + e) * m2 * v2i) / (m1 + m2);
-        let v2f: f64 = ((m2 - e * m1) * v2i + (1.0 + e) * m1 * v1i) / (m1 + m2);
-
-        let v1f_vec: Vector3D = direction.multiply(v1f);
-        let v2f_vec: Vector3D = direction.multiply(v2f);
 
-        let v1i_vec: Vector3D = direction.multiply(v1i);
-        let v2i_vec: Vector3D = direction.multiply(v2i);
-
-        let v1p: Vector3D = self.velocity.subtract_vector(&v1i_vec);
-        let v2p: Vector3D = target.velocity.subtract_vector(&v2i_vec);
-
-        let v1: Vector3D = v1p.add_vector(&v1f_vec);
-        let v2: Vector3D = v2p.add_vector(&v2f_vec);
-
-        self.velocity = v1;
-        target.velocity = v2;
+        *manifolds = current_manifolds;
     }
 
-    pub fn apply_collision_angular_velocity(
-        &mut self,
-        target: &mut Physics,
-        direction: Vector3D,
-        contact_point: Vector3D,
-    ) {
-        let e: f64 = 0.6;
-
-        let m1_inv: f64 = 1.0 / self.mass;
-        let m2_inv: f64 = 1.0 / target.mass;
-
-        let r1: Vector3D = contact_point.subtract_vector(&self.get_center_of_mass());
-        let r2: Vector3D = contact_point.subtract_vector(&target.get_center_of_mass());
-
-        let angular_vel_r1: Vector3D = self.angular_velocity.cross_product(&r1);
-        let angular_vel_r2: Vector3D = target.angular_velocity.cross_product(&r2);
-
-        let v_rel1: Vector3D = self.velocity.add_vector(&angular_vel_r1);
-        let v_rel2: Vector3D = target.velocity.add_vector(&angular_vel_r2);
-
-        let v_rel_dir: f64 = (v_rel1.subtract_vector(&v_rel2)).dot_product(&direction);
-
-        let inv_inertia1: Vector3D = self.moment_of_inertia.inverse();
-        let inv_inertia2: Vector3D = target.moment_of_inertia.inverse();
-        let neg_direction: Vector3D = direction.negate();
-
-        let r1_cross_dir: Vector3D = r1.cross_product(&direction);
-        let r2_cross_dir: Vector3D = r2.cross_product(&neg_direction);
-
-        let r1_cross_dir_in1: Vector3D = r1_cross_dir.multiply_vector(&inv_inertia1);
-        let r2_cross_dir_in2: Vector3D = r2_cross_dir.multiply_vector(&inv_inertia2);
-
-        let in1_cross: Vector3D = r1_cross_dir_in1.cross_product(&r1);
-        let in2_cross: Vector3D = r2_cross_dir_in2.cross_product(&r2);
-
-        let eff_mass_rot1: f64 = in1_cross.dot_product(&direction);
-        let eff_mass_rot2: f64 = in2_cross.dot_product(&neg_direction);
-
-        let eff_mass_total: f64 = m1_inv + m2_inv + eff_mass_rot1 + eff_mass_rot2;
-
-        let j: f64 = -(1.0 + e) * v_rel_dir / eff_mass_total;
-
-        let impulse: Vector3D = direction.multiply(j);
-        let impulse_neg: Vector3D = neg_direction.multiply(j);
-
-        let angular_impulse1: Vector3D = r1.cross_product(&impulse);
-        let angular_impulse2: Vector3D = r2.cross_product(&impulse_neg);
-
-        let ang_vel_change1: Vector3D = angular_impulse1.multiply_vector(&inv_inertia1);
-        let ang_vel_change2: Vector3D = angular_impulse2.multiply_vector(&inv_inertia2);
-
-        self.angular_velocity = self.angular_velocity.add_vector(&ang_vel_change1);
-        target.angular_velocity = target.angular_velocity.add_vector(&ang_vel_change2);
-
-        self.angular_acceleration = inv_inertia1.multiply_vector(&angular_impulse1);
-        target.angular_acceleration = inv_inertia2.multiply_vector(&angular_impulse2);
+    pub fn translate(&mut self, translation: &Vector3D) {
+        self.transform.translate(translation);
     }
 
-    fn get_center_of_mass(&self) -> Vector3D {
-        let mut center_of_mass: Vector3D = Vector3D::default(0.0);
-        let mesh: &Mesh = &self.mesh;
-        center_of_mass = center_of_mass.add_vector(&mesh.get_center_of_mass());
-
-        center_of_mass
+    pub fn set_linear_state(&mut self, position: Vector3D, velocity: Vector3D) {
+        let translation: Vector3D = position.subtract_vector(&self.get_position());
+        self.translate(&translation);
+        self.velocity = velocity;
     }
 
-    fn update_position(&mut self, timestep: f64) {
-        let accel_change: Vector3D = self.acceleration.multiply(timestep);
-        self.velocity = self.velocity.add_vector(&accel_change);
-
-        let velocity_change: Vector3D = self.velocity.multiply(timestep);
-        self.position = self.position.add_vector(&velocity_change);
-
+    pub fn update_rotation(&mut self, timestep: f64) {
         let angular_accel_change: Vector3D = self.angular_acceleration.multiply(timestep);
         self.angular_velocity = self.angular_velocity.add_vector(&angular_accel_change);
+        let angular_momentum: Vector3D = self.get_angular_momentum();
 
-        let angular_velocity: Vector3D = self.angular_velocity.multiply(timestep);
+        let angular_velocity: Vector3D = self
+            .get_midpoint_angular_velocity(&angular_momentum, timestep)
+            .multiply(timestep);
         let rotation_axis: Vector3D = angular_velocity.normalize();
         let rotation_angle: f64 = angular_velocity.get_length();
 
-        self.update_mesh_position(&velocity_change);
-        self.update_mesh_rotation(&rotation_axis, rotation_angle);
-        self.update_light_position(&velocity_change);
+        self.update_orientation(&rotation_axis, rotation_angle);
+        self.update_world_geometry();
+        self.update_angular_velocity(&angular_momentum);
     }
 
-    fn set_light_position(mesh: &mut Mesh, translation: &Vector3D) {
-        if mesh.light.is_some() {
-            let mut light: Light = mesh.light.unwrap();
-            light.position = light.position.add_vector(translation);
-            light.target = light.target.add_vector(translation);
-            mesh.light = Some(light);
+    fn update_orientation(&mut self, axis: &Vector3D, angle: f64) {
+        if angle == 0.0 {
+            return;
         }
-    }
-
-    fn update_light_position(&mut self, translation: &Vector3D) {
-        Self::set_light_position(&mut self.mesh, translation);
-
-        if let Some(mesh_cluster) = &mut self.mesh_cluster {
-            for mesh in mesh_cluster {
-                Self::set_light_position(mesh, translation)
-            }
+        self.transform.rotate(axis, angle);
+        self.update_inverse_inertia_world();
+    }
+
+    // Rotating with the angular velocity at the middle of the step keeps the
+    // rotational energy of tumbling bodies from drifting.
+    fn get_midpoint_angular_velocity(
+        &self,
+        angular_momentum: &Vector3D,
+        timestep: f64,
+    ) -> Vector3D {
+        let mut angular_velocity: Vector3D = self.angular_velocity;
+        if self.inertia_tensor.get_determinant() == 0.0 {
+            return angular_velocity;
         }
-    }
-
-    fn update_mesh_rotation(&mut self, axis: &Vector3D, angle: f64) {
-        let centroid: &Vector3D = &self.mesh.get_mesh_centroid();
-        self.mesh.rotate_polygons_around_axis(axis, centroid, angle);
 
-        if let Some(mesh_cluster) = &mut self.mesh_cluster {
-            for mesh in mesh_cluster.iter_mut() {
-                mesh.rotate_polygons_around_axis(axis, centroid, angle);
-            }
+        let inverse_inertia: Matrix3 = self.inertia_tensor.inverse();
+        for _ in 0..3 {
+            let half_angle: f64 = angular_velocity.get_length() * timestep / 2.0;
+            let half_rotation: Quaternion =
+                Quaternion::from_axis_angle(&angular_velocity, half_angle);
+            let rotation: Matrix3 = half_rotation
+                .multiply(&self.transform.orientation)
+                .to_matrix();
+            let inverse_inertia_world: Matrix3 = rotation
+                .multiply(&inverse_inertia)
+                .multiply(&rotation.transpose());
+            angular_velocity = inverse_inertia_world.multiply_vector(angular_momentum);
         }
+        angular_velocity
     }
 
-    fn update_mesh_position_decompose(&mut self, translation: &Vector3D) {
-        let mesh_decompose = MeshDecompose::new(1.0);
-        self.mesh.translate_polygons(translation);
-
-        let meshes = mesh_decompose.decompose(&self.mesh);
-        self.mesh_cluster = Some(meshes);
+    // Without torque the angular momentum is fixed, so the angular velocity
+    // follows the rotated inertia tensor and asymmetric bodies tumble.
+    fn update_angular_velocity(&mut self, angular_momentum: &Vector3D) {
+        if self.inertia_tensor.get_determinant() == 0.0 {
+            return;
+        }
+        self.angular_velocity = self.inverse_inertia_world.multiply_vector(angular_momentum);
     }
 
-    fn update_mesh_position(&mut self, translation: &Vector3D) {
-        self.mesh.translate_polygons(translation);
+    // World space geometry is rebuilt from the local meshes whenever the
+    // transform moved, so rotation error never accumulates in the vertices.
+    pub fn update_world_geometry(&mut self) {
+        if self.world_transform == self.transform {
+            return;
+        }
 
-        if let Some(mesh_cluster) = &mut self.mesh_cluster {
-            for mesh in mesh_cluster.iter_mut() {
-                mesh.translate_polygons(translation);
+        self.mesh.set_transformed(&self.local_mesh, &self.transform);
+        if let (Some(mesh_cluster), Some(local_mesh_cluster)) =
+            (&mut self.mesh_cluster, &self.local_mesh_cluster)
+        {
+            for (mesh, local_mesh) in mesh_cluster.iter_mut().zip(local_mesh_cluster) {
+                mesh.set_transformed(local_mesh, &self.transform);
             }
         }
+        self.world_transform = self.transform;
     }
 
-    fn reset_accelerations(&mut self) {
+    fn update_mesh_position_decompose(&mut self, translation: &Vector3D) {
+        let mesh_decompose = MeshDecompose::new(0.02);
+        self.translate(translation);
+
+        let meshes: Vec<Mesh> = mesh_decompose.decompose(&self.local_mesh);
+        let transform: Transform = self.transform;
+        self.mesh_cluster = Some(
+            meshes
+                .iter()
+                .map(|m| m.get_transformed(&transform))
+                .collect(),
+        );
+        self.local_mesh_cluster = Some(meshes);
+        self.update_world_geometry();
+    }
+
+    pub fn reset_accelerations(&mut self) {
         self.acceleration = Vector3D::default(0.0);
         self.angular_acceleration = Vector3D::default(0.0);
     }
 
-    fn handle_stationary_update(&mut self) {
+    pub fn handle_stationary_update(&mut self) {
         if self.is_stationary {
             self.acceleration = Vector3D::default(0.0);
             self.velocity = Vector3D::default(0.0);
         }
-    }
-
-    pub fn update(&mut self, timestep: f64) {
-        self.handle_stationary_update();
-        self.update_position(timestep);
-        self.reset_accelerations();
+        if self.is_sleeping {
+            self.velocity = Vector3D::default(0.0);
+            self.angular_velocity = Vector3D::default(0.0);
+        }
     }
 }
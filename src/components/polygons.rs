@@ -1,6 +1,11 @@
 use crate::components::bvh::BVHNode;
+use crate::components::bvh::NarrowPhase;
+use crate::components::bvh::RayHit;
+use crate::components::collision_shape::CollisionShape;
 use crate::components::color::RGBA;
 use crate::components::shaders::Light;
+use crate::components::transform::Transform;
+use crate::components::vectors::Matrix3;
 use crate::components::vectors::Vector3D;
 
 #[derive(Clone, Copy, Debug)]
@@ -98,6 +103,12 @@ impl Triangle {
         }
     }
 
+    pub fn transform(&mut self, rotation: &Matrix3, translation: &Vector3D) {
+        for vertex in self.vertices.iter_mut() {
+            *vertex = rotation.multiply_vector(vertex).add_vector(translation);
+        }
+    }
+
     pub fn get_normal(&self) -> Vector3D {
         let v1: Vector3D = self.vertices[0];
         let v2: Vector3D = self.vertices[1];
@@ -231,6 +242,12 @@ impl Quad {
         }
     }
 
+    pub fn transform(&mut self, rotation: &Matrix3, translation: &Vector3D) {
+        for vertex in self.vertices.iter_mut() {
+            *vertex = rotation.multiply_vector(vertex).add_vector(translation);
+        }
+    }
+
     pub fn get_normal(&self) -> Vector3D {
         let v1 = self.vertices[0];
         let v2 = self.vertices[1];
@@ -354,6 +371,13 @@ impl Polygon {
         }
     }
 
+    pub fn transform(&mut self, rotation: &Matrix3, translation: &Vector3D) {
+        match self {
+            Polygon::Triangle(triangle) => triangle.transform(rotation, translation),
+            Polygon::Quad(quad) => quad.transform(rotation, translation),
+        }
+    }
+
     pub fn get_bounding_box(&self) -> ([f64; 3], [f64; 3]) {
         let infinity: f64 = f64::INFINITY;
         let neg_infinity: f64 = f64::NEG_INFINITY;
@@ -434,6 +458,10 @@ pub struct Mesh {
     pub polygons: Vec<Polygon>,
     pub bvh_node: BVHNode,
     pub light: Option<Light>,
+    pub collision_shape: Option<CollisionShape>,
+    // Index pairs into the BVH vertices, set for meshes built as a convex
+    // hull so their outline is drawn without rebuilding the hull.
+    pub hull_edges: Vec<(usize, usize)>,
 }
 
 impl Mesh {
@@ -444,9 +472,20 @@ impl Mesh {
             polygons,
             bvh_node,
             light: None,
+            collision_shape: None,
+            hull_edges: Vec::new(),
         }
     }
 
+    /// Edges of a mesh built as a convex hull, following its transform.
+    pub fn get_hull_edges(&self) -> Vec<(Vector3D, Vector3D)> {
+        let vertices: &[Vector3D] = &self.bvh_node.vertices;
+        self.hull_edges
+            .iter()
+            .map(|&(a, b)| (vertices[a], vertices[b]))
+            .collect()
+    }
+
     // pub fn get_total_volume(&self) -> f64 {
     //     let mut total_volume: f64 = 0.0;
     //     let center_of_mass: Vector3D = self.get_center_of_mass();
@@ -538,11 +577,51 @@ impl Mesh {
         self.light = Some(light);
     }
 
+    /// Bodies built from this mesh collide with the shape instead of the
+    /// polygons.
+    pub fn set_collision_shape(&mut self, collision_shape: CollisionShape) {
+        self.collision_shape = Some(collision_shape);
+    }
+
     pub fn translate_polygons(&mut self, translation: &Vector3D) {
         for polygon in &mut self.polygons {
             polygon.translate(translation);
         }
         self.bvh_node.translate_bvh(translation);
+        if let Some(collision_shape) = &mut self.collision_shape {
+            collision_shape.translate(translation);
+        }
+    }
+
+    // Overwrites this mesh with a transformed copy of the local space mesh it
+    // was cloned from. Lights only follow the position, as they did when
+    // meshes were moved incrementally.
+    pub fn set_transformed(&mut self, local: &Mesh, transform: &Transform) {
+        let rotation: Matrix3 = transform.get_rotation_matrix();
+        let translation: Vector3D = transform.position;
+
+        for (polygon, local_polygon) in self.polygons.iter_mut().zip(&local.polygons) {
+            *polygon = *local_polygon;
+            polygon.transform(&rotation, &translation);
+        }
+        self.bvh_node
+            .set_transformed(&local.bvh_node, &rotation, &translation);
+
+        self.light = local.light.map(|mut light| {
+            light.position = light.position.add_vector(&translation);
+            light.target = light.target.add_vector(&translation);
+            light
+        });
+        self.collision_shape = local.collision_shape.clone().map(|mut collision_shape| {
+            collision_shape.transform(&rotation, &translation);
+            collision_shape
+        });
+    }
+
+    pub fn get_transformed(&self, transform: &Transform) -> Mesh {
+        let mut mesh: Mesh = self.clone();
+        mesh.set_transformed(self, transform);
+        mesh
     }
 
     pub fn get_mesh_centroid(&self) -> Vector3D {
@@ -567,6 +646,9 @@ impl Mesh {
         for polygon in &mut self.polygons {
             polygon.rotate(&axis, angle);
         }
+        if let Some(collision_shape) = &mut self.collision_shape {
+            collision_shape.rotate(axis, angle);
+        }
 
         self.translate_polygons(&centroid);
 
@@ -594,8 +676,16 @@ impl Mesh {
         self.bvh_node.get_distance(&other.bvh_node)
     }
 
-    pub fn is_intersecting_bvh(&self, other: &Mesh) -> Option<(Vector3D, Vector3D)> {
-        self.bvh_node.is_intersecting(&other.bvh_node)
+    pub fn is_intersecting_bvh(
+        &self,
+        other: &Mesh,
+        narrow_phase: NarrowPhase,
+    ) -> Option<(Vector3D, Vector3D)> {
+        self.bvh_node.is_intersecting(&other.bvh_node, narrow_phase)
+    }
+
+    pub fn get_ray_hit(&self, origin: &Vector3D, direction: &Vector3D) -> Option<RayHit> {
+        self.bvh_node.get_ray_hit(origin, direction, f64::INFINITY)
     }
 
     pub fn get_distance(&self, other: &Mesh) -> f64 {
@@ -1,50 +1,134 @@
 use crate::abstracts::body::{Body, BodyType};
+use crate::components::accretion::CollisionOutcome;
+use crate::components::accretion::CollisionPolicy;
+use crate::components::broad_phase::BroadPhase;
+use crate::components::broad_phase::BroadPhaseType;
+use crate::components::broad_phase::SweepAndPrune;
+use crate::components::broad_phase::AABB;
+use crate::components::bvh::NarrowPhase;
 use crate::components::camera::Camera;
+use crate::components::ccd::ContinuousCollision;
+use crate::components::ccd::SweptVolume;
 use crate::components::color::RGBA;
+use crate::components::contact::ContactManifold;
+use crate::components::contact_solver::ContactSolver;
+use crate::components::diagnostics::ConservationDiagnostics;
 
 use super::vectors::Vector3D;
 use crate::components::font::ArialFont;
+use crate::components::forces::ForceRegistry;
 use crate::components::font::FontSettings;
 use crate::components::font::FontType;
 use crate::components::graphics::Graphics;
+use crate::components::gravity::Gravity;
+use crate::components::gravity::GravitySolver;
+use crate::components::integrator::Integrator;
+use crate::components::integrator::IntegratorType;
+use crate::components::integrator::LinearState;
+use crate::components::integrator::SemiImplicitEuler;
+use crate::components::joints::JointSolver;
 use crate::components::physics::Physics;
 use crate::components::polygons::Mesh;
 use crate::components::shape::Shape;
+use crate::components::sleep::SleepManager;
 use crate::components::text_writer::TextWriter;
+use crate::components::timestep::AdaptiveTimestep;
+use crate::components::timestep::TimestepCriterion;
+use crate::components::timestep::TimestepMode;
 use crate::components::vertices::Cuboid;
 use crate::components::vertices::Sphere;
 use crate::configurations::body_configurations;
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+
 pub struct Simulation {
     pub camera: Camera,
     pub objects: Vec<BodyType>,
     pub polygon_count: usize,
     pub draw_polygons: bool,
     pub draw_mesh: bool,
+    pub gravity: Gravity,
+    pub forces: ForceRegistry,
+    pub integrator: IntegratorType,
+    pub adaptive_timestep: AdaptiveTimestep,
+    pub broad_phase: BroadPhaseType,
+    pub narrow_phase: NarrowPhase,
+    pub continuous_collision: ContinuousCollision,
+    pub sleep: SleepManager,
+    pub contact_solver: ContactSolver,
+    pub joint_solver: JointSolver,
+    pub collision_policy: CollisionPolicy,
+    pub contact_manifolds: HashMap<(usize, usize), Vec<ContactManifold>>,
+    pub diagnostics: ConservationDiagnostics,
+    pub candidate_pairs: usize,
+    pub total_pairs: usize,
+    pub sim_time: f64,
+    pub step_count: usize,
+    pub real_time_factor: f64,
+    pub max_steps_per_frame: usize,
+    pub steps_per_frame: usize,
     timestep_hz: f64,
-    text_writer: TextWriter,
+    accumulator: f64,
+    collision_outcomes: Vec<(usize, usize, CollisionOutcome)>,
+    text_writer: Option<TextWriter>,
 }
 
 impl Simulation {
     pub fn new(camera: Camera, resolution: (u32, u32)) -> Simulation {
-        let objects: Vec<BodyType> = vec![];
-        let timestep_hz: f64 = 5.0;
-        let polygon_count: usize = 0;
-
         let arial_font: ArialFont = ArialFont::new();
         let font_type: FontType = FontType::ArialFont(arial_font);
         let font_color: RGBA = RGBA::new(1.0, 1.0, 1.0, 1.0);
         let font: FontSettings = FontSettings::new(font_type, 14, font_color, 1.8, 1);
         let text_writer: TextWriter = TextWriter::new(resolution, font);
 
+        let mut simulation: Simulation = Simulation::new_headless(camera);
+        simulation.draw_polygons = true;
+        simulation.text_writer = Some(text_writer);
+        simulation
+    }
+
+    pub fn new_headless(camera: Camera) -> Simulation {
+        let objects: Vec<BodyType> = vec![];
+        let timestep_hz: f64 = 5.0;
+        let polygon_count: usize = 0;
+
         Simulation {
             camera,
             objects,
             polygon_count,
-            draw_polygons: true,
+            draw_polygons: false,
             draw_mesh: false,
+            gravity: Gravity::new(GravitySolver::BarnesHut, 0.5, 1_000.0),
+            forces: ForceRegistry::new(),
+            integrator: IntegratorType::SemiImplicitEuler(SemiImplicitEuler::new()),
+            adaptive_timestep: AdaptiveTimestep::new(
+                TimestepMode::Fixed,
+                TimestepCriterion::Acceleration,
+                0.1,
+            ),
+            broad_phase: BroadPhaseType::SweepAndPrune(SweepAndPrune::new()),
+            narrow_phase: NarrowPhase::SeparatingAxis,
+            continuous_collision: ContinuousCollision::new(true),
+            sleep: SleepManager::new(true),
+            contact_solver: ContactSolver::new(10, 0.2),
+            joint_solver: JointSolver::new(10, 0.2),
+            collision_policy: CollisionPolicy::Bounce,
+            contact_manifolds: HashMap::new(),
+            // Every snapshot sums the potential over all body pairs, so the
+            // diagnostics only run once they are switched on.
+            diagnostics: ConservationDiagnostics::new(false),
+            candidate_pairs: 0,
+            total_pairs: 0,
+            sim_time: 0.0,
+            step_count: 0,
+            real_time_factor: 10.0,
+            max_steps_per_frame: 10,
+            steps_per_frame: 0,
             timestep_hz,
-            text_writer,
+            accumulator: 0.0,
+            collision_outcomes: Vec::new(),
+            text_writer: None,
         }
     }
 
@@ -63,16 +147,16 @@ impl Simulation {
         // let grid_cuboid = body_configurations::get_grid_cuboid();
         // self.objects.push(grid_cuboid);
 
-        // let obj = body_configurations::get_obj("./assets/town_square.obj", 2.0);
+        // let (obj, _) = body_configurations::get_obj("./assets/town_square.obj", 0.01);
         // self.objects.push(obj);
 
-        // let obj = body_configurations::get_obj("./assets/cottage.obj", 1.0);
+        // let (obj, _) = body_configurations::get_obj("./assets/cottage.obj", 0.02);
         // self.objects.push(obj);
 
-        // let obj = body_configurations::get_obj("./assets/plane.obj", 3.0);
+        // let (obj, _) = body_configurations::get_obj("./assets/plane.obj", 0.05);
         // self.objects.push(obj);
 
-        // let obj = body_configurations::get_obj("./assets/TerrorTubby_01.obj", 1.0);
+        // let (obj, _) = body_configurations::get_obj("./assets/TerrorTubby_01.obj", 0.02);
         // self.objects.push(obj);
 
         // let sphere = body_configurations::get_sphere_light_highmass();
@@ -93,6 +177,7 @@ impl Simulation {
 
         let system = body_configurations::orbiting_system(Vector3D::new(0.0, 0.0, 0.0));
         self.objects.extend(system);
+        self.collision_policy = CollisionPolicy::get_accretion();
 
         // // let system =
         //     body_configurations::orbiting_system2(Vector3D::new(8_000_000.0, 4_000_000.0, 0.0));
@@ -101,12 +186,28 @@ impl Simulation {
         let camera_position = Vector3D::new(-250_000.0, 200.0, -2_000_000.0);
         self.camera.set_camera_position(camera_position);
 
-        for object in self.objects.iter_mut() {
-            let physics = object.physics_mut();
-            let mut mesh_vec = Vec::new();
-            let mesh = &physics.mesh;
-            mesh_vec.push(mesh.clone());
-            let mesh_cluster = physics.mesh_cluster.as_ref().unwrap_or(&mesh_vec);
+        self.update_polygon_count();
+    }
+
+    pub fn add_object(&mut self, object: BodyType) {
+        self.objects.push(object);
+        self.update_polygon_count();
+    }
+
+    pub fn extend_objects(&mut self, objects: Vec<BodyType>) {
+        self.objects.extend(objects);
+        self.update_polygon_count();
+    }
+
+    fn update_polygon_count(&mut self) {
+        self.polygon_count = 0;
+
+        for object in self.objects.iter() {
+            let physics = object.physics();
+            let mesh_cluster: Vec<&Mesh> = match &physics.mesh_cluster {
+                Some(mesh_cluster) => mesh_cluster.iter().collect(),
+                None => vec![&physics.mesh],
+            };
             for mesh in mesh_cluster {
                 let polygon_len = mesh.polygons.len();
                 self.polygon_count += polygon_len;
@@ -125,6 +226,92 @@ impl Simulation {
         self.timestep_hz = timestep;
     }
 
+    pub fn cycle_gravity_solver(&mut self) {
+        let solver: GravitySolver = self.gravity.solver.next();
+        self.gravity.set_solver(solver);
+    }
+
+    pub fn cycle_integrator(&mut self) {
+        self.integrator = self.integrator.next();
+    }
+
+    pub fn cycle_timestep_mode(&mut self) {
+        let mode: TimestepMode = self.adaptive_timestep.mode.next();
+        self.adaptive_timestep.set_mode(mode);
+    }
+
+    pub fn cycle_broad_phase(&mut self) {
+        self.broad_phase = self.broad_phase.next();
+    }
+
+    pub fn cycle_narrow_phase(&mut self) {
+        self.narrow_phase = self.narrow_phase.next();
+    }
+
+    pub fn toggle_continuous_collision(&mut self) {
+        self.continuous_collision.toggle();
+    }
+
+    pub fn toggle_sleeping(&mut self) {
+        self.sleep.enabled = !self.sleep.enabled;
+    }
+
+    pub fn toggle_sleep_tint(&mut self) {
+        self.sleep.toggle_draw_tint();
+    }
+
+    pub fn toggle_draw_joints(&mut self) {
+        self.joint_solver.toggle_draw_joints();
+    }
+
+    pub fn toggle_force_generators(&mut self) {
+        self.forces.toggle_all();
+    }
+
+    pub fn toggle_diagnostics(&mut self) {
+        self.diagnostics.toggle();
+    }
+
+    pub fn cycle_collision_policy(&mut self) {
+        self.collision_policy = self.collision_policy.next();
+    }
+
+    pub fn increment_real_time_factor(&mut self, direction: i32) {
+        let min_real_time_factor: f64 = 0.01;
+        let max_real_time_factor: f64 = 10.0 * 1000.0;
+        let proportion: f64 = 0.05;
+        let increment: f64 = self.real_time_factor * proportion * direction as f64;
+        let real_time_factor: f64 = self.real_time_factor + increment;
+        let real_time_factor: f64 = real_time_factor.max(min_real_time_factor);
+        let real_time_factor: f64 = real_time_factor.min(max_real_time_factor);
+        self.real_time_factor = real_time_factor;
+    }
+
+    pub fn set_real_time_factor(&mut self, real_time_factor: f64) {
+        self.real_time_factor = real_time_factor;
+    }
+
+    pub fn set_max_steps_per_frame(&mut self, max_steps_per_frame: usize) {
+        self.max_steps_per_frame = max_steps_per_frame;
+    }
+
+    /// Simulation time carried over to the next frame.
+    pub fn get_accumulator(&self) -> f64 {
+        self.accumulator
+    }
+
+    /// Rates that are not positive and finite are ignored, they would never
+    /// advance the simulation time.
+    pub fn set_timestep_hz(&mut self, timestep_hz: f64) {
+        if timestep_hz.is_finite() && timestep_hz > 0.0 {
+            self.timestep_hz = timestep_hz;
+        }
+    }
+
+    pub fn get_timestep(&self) -> f64 {
+        1.0 / self.timestep_hz
+    }
+
     pub fn shoot(&mut self) {
         let camera_position: Vector3D = self.camera.camera_position;
         let camera_target: Vector3D = self.camera.camera_target;
@@ -155,23 +342,517 @@ impl Simulation {
     }
 
     pub fn compute_objects(&mut self) {
-        let timestep: f64 = 1.0 / self.timestep_hz;
+        let timestep: f64 = self.get_timestep();
+        self.continuous_collision.impact_count = 0;
+        let positions: Vec<Vector3D> = self
+            .objects
+            .iter()
+            .map(|o| o.physics().get_position())
+            .collect();
+        let velocities: Vec<Vector3D> = self.objects.iter().map(|o| o.physics().velocity).collect();
+        let state: LinearState = LinearState::new(positions, velocities);
+        let force_accelerations: Vec<Vector3D> =
+            self.forces.get_accelerations(&self.objects, &state);
+        self.sleep
+            .wake_forced(&mut self.objects, &force_accelerations);
+        if self.diagnostics.initial.is_none() {
+            self.diagnostics
+                .update(&self.objects, &self.gravity, &self.forces, self.sim_time);
+        }
+        match self.adaptive_timestep.mode {
+            TimestepMode::Fixed => {
+                self.compute_collisions();
+                self.advance_objects(timestep, None);
+                self.adaptive_timestep.effective_timestep = timestep;
+            }
+            TimestepMode::Global => self.compute_global_refinement(timestep, &force_accelerations),
+            TimestepMode::Block => self.compute_block_timesteps(timestep, &force_accelerations),
+        }
+        self.apply_collision_outcomes();
+
+        // Jointed bodies share an island so they fall asleep and wake together.
+        let mut contact_pairs: Vec<(usize, usize)> =
+            self.contact_manifolds.keys().copied().collect();
+        contact_pairs.extend(self.joint_solver.get_connected_pairs());
+        self.sleep.update(&mut self.objects, &contact_pairs);
+
+        self.sim_time += timestep;
+        self.step_count += 1;
+        self.diagnostics
+            .update(&self.objects, &self.gravity, &self.forces, self.sim_time);
+    }
+
+    // Merged and shattered bodies are swapped in at the end of the step, so
+    // the body indices stay fixed while the step is computed. Joints and
+    // contacts of replaced bodies are dropped, the rest follow the new
+    // indices.
+    fn apply_collision_outcomes(&mut self) {
+        let CollisionPolicy::Accretion(model) = &mut self.collision_policy else {
+            self.collision_outcomes.clear();
+            return;
+        };
+        if self.collision_outcomes.is_empty() {
+            return;
+        }
+
+        let body_count: usize = self.objects.len();
+        let mut is_replaced: Vec<bool> = vec![false; body_count];
+        let mut is_removed: Vec<bool> = vec![false; body_count];
+        let mut is_shattered: Vec<bool> = vec![false; body_count];
+        let mut fragments: Vec<BodyType> = Vec::new();
+
+        // A body merged away points to the body that took it in, so a clump
+        // of touching bodies merges into one within a single step.
+        let mut merged_into: Vec<usize> = (0..body_count).collect();
+        let get_target = |merged_into: &[usize], idx: usize| -> usize {
+            let mut idx: usize = idx;
+            while merged_into[idx] != idx {
+                idx = merged_into[idx];
+            }
+            idx
+        };
+
+        for (i, j, outcome) in self.collision_outcomes.drain(..) {
+            let i: usize = get_target(&merged_into, i);
+            let j: usize = get_target(&merged_into, j);
+            if i == j || is_shattered[i] || is_shattered[j] {
+                continue;
+            }
+            let a: &Physics = self.objects[i].physics();
+            let b: &Physics = self.objects[j].physics();
+            let (larger, smaller): (usize, usize) = if a.mass >= b.mass { (i, j) } else { (j, i) };
+            match outcome {
+                CollisionOutcome::Merge => {
+                    self.objects[larger] = model.merge(a, b);
+                    merged_into[smaller] = larger;
+                    is_replaced[larger] = true;
+                    is_removed[smaller] = true;
+                }
+                CollisionOutcome::Shatter => {
+                    if is_replaced[i] || is_replaced[j] {
+                        continue;
+                    }
+                    let (velocity, spin, mut pieces): (Vector3D, Vector3D, Vec<BodyType>) = model
+                        .shatter(
+                            self.objects[larger].physics(),
+                            self.objects[smaller].physics(),
+                        );
+                    let physics: &mut Physics = self.objects[larger].physics_mut();
+                    physics.set_velocity(velocity.x, velocity.y, velocity.z);
+                    physics.set_angular_velocity(spin.x, spin.y, spin.z);
+                    self.objects[smaller] = pieces.remove(0);
+                    is_shattered[smaller] = true;
+                    fragments.extend(pieces);
+                }
+                CollisionOutcome::Bounce => continue,
+            }
+            is_replaced[smaller] = true;
+        }
+
+        let mut next_idx: usize = 0;
+        let mut body_map: Vec<Option<usize>> = Vec::with_capacity(body_count);
+        for (is_replaced, is_removed) in is_replaced.iter().zip(&is_removed) {
+            body_map.push(if *is_replaced { None } else { Some(next_idx) });
+            if !is_removed {
+                next_idx += 1;
+            }
+        }
+        self.joint_solver.remap_bodies(&body_map);
+        self.forces.remap_bodies(&body_map);
+        self.contact_manifolds = self
+            .contact_manifolds
+            .drain()
+            .filter_map(|((i, j), manifolds)| Some(((body_map[i]?, body_map[j]?), manifolds)))
+            .collect();
+
+        let mut idx: usize = 0;
+        self.objects.retain(|_| {
+            idx += 1;
+            !is_removed[idx - 1]
+        });
+        self.objects.extend(fragments);
+        self.adaptive_timestep.clear_history();
+        self.update_polygon_count();
+    }
+
+    fn get_tick_time(&self, timestep: f64, tick: u64) -> f64 {
+        let tick_count: u64 = self.adaptive_timestep.get_tick_count();
+        self.sim_time + timestep * tick as f64 / tick_count as f64
+    }
+
+    fn get_level_timestep(timestep: f64, level: u32) -> f64 {
+        timestep / 2.0_f64.powi(level as i32)
+    }
+
+    // The whole scene takes substeps sized for its most demanding body, the
+    // levels are re-evaluated after every substep. The force accelerations
+    // are those at the start of the step.
+    fn compute_global_refinement(&mut self, timestep: f64, force_accelerations: &[Vector3D]) {
+        let tick_count: u64 = self.adaptive_timestep.get_tick_count();
+        let mut tick: u64 = 0;
+        let mut finest_level: u32 = 0;
+
+        while tick < tick_count {
+            let time: f64 = self.get_tick_time(timestep, tick);
+            let adaptive_timestep: &mut AdaptiveTimestep = &mut self.adaptive_timestep;
+            let levels: Vec<u32> = adaptive_timestep.get_levels(
+                &self.objects,
+                &self.gravity,
+                force_accelerations,
+                timestep,
+                time,
+                None,
+            );
+            let level: u32 = levels.iter().copied().max().unwrap_or(0);
+            let level: u32 = adaptive_timestep.get_aligned_level(level, tick);
+
+            let substep: f64 = Self::get_level_timestep(timestep, level);
+            self.compute_collisions();
+            self.advance_objects(substep, None);
+
+            tick += self.adaptive_timestep.get_tick_interval(level);
+            finest_level = finest_level.max(level);
+        }
+        let effective_timestep: f64 = Self::get_level_timestep(timestep, finest_level);
+        self.adaptive_timestep.effective_timestep = effective_timestep;
+    }
+
+    // Every body advances in blocks of timestep / 2^level and picks its next
+    // level when its block ends. Bodies that are not due keep their last
+    // positions for the gravity evaluation.
+    fn compute_block_timesteps(&mut self, timestep: f64, force_accelerations: &[Vector3D]) {
+        let tick_count: u64 = self.adaptive_timestep.get_tick_count();
+        let mut next_ticks: Vec<u64> = vec![0; self.objects.len()];
+        let mut finest_level: u32 = 0;
+
+        while let Some(tick) = next_ticks.iter().copied().filter(|t| *t < tick_count).min() {
+            let time: f64 = self.get_tick_time(timestep, tick);
+            let due: Vec<bool> = next_ticks.iter().map(|t| *t == tick).collect();
+            let adaptive_timestep: &mut AdaptiveTimestep = &mut self.adaptive_timestep;
+            let levels: Vec<u32> = adaptive_timestep.get_levels(
+                &self.objects,
+                &self.gravity,
+                force_accelerations,
+                timestep,
+                time,
+                Some(&due),
+            );
+
+            let mut active_levels: Vec<Option<u32>> = vec![None; levels.len()];
+            for (idx, level) in levels.iter().enumerate() {
+                if !due[idx] {
+                    continue;
+                }
+                let level: u32 = adaptive_timestep.get_aligned_level(*level, tick);
+                next_ticks[idx] += adaptive_timestep.get_tick_interval(level);
+                active_levels[idx] = Some(level);
+            }
+
+            let tick_level: u32 = active_levels.iter().flatten().copied().max().unwrap_or(0);
+            self.compute_collisions();
+            for level in 0..=tick_level {
+                let active: Vec<bool> = active_levels.iter().map(|l| *l == Some(level)).collect();
+                if active.contains(&true) {
+                    let level_timestep: f64 = Self::get_level_timestep(timestep, level);
+                    self.advance_objects(level_timestep, Some(&active));
+                }
+            }
+            finest_level = finest_level.max(tick_level);
+        }
+        let effective_timestep: f64 = Self::get_level_timestep(timestep, finest_level);
+        self.adaptive_timestep.effective_timestep = effective_timestep;
+    }
+
+    fn compute_collisions(&mut self) {
+        let narrow_phase: NarrowPhase = self.narrow_phase;
+        let ignored_pairs: HashSet<(usize, usize)> = self.joint_solver.get_ignored_pairs();
         let objects = &mut self.objects;
 
-        for i in 0..objects.len() {
-            for j in (i + 1)..objects.len() {
-                let (physics1, physics2) = {
-                    let (left, right) = objects.split_at_mut(j);
-                    (left[i].physics_mut(), right[0].physics_mut())
+        let aabbs: Vec<AABB> = objects.iter().map(|o| o.physics().get_aabb()).collect();
+        let candidate_pairs: Vec<(usize, usize)> = self.broad_phase.get_candidate_pairs(&aabbs);
+        self.candidate_pairs = candidate_pairs.len();
+        self.total_pairs = objects.len() * objects.len().saturating_sub(1) / 2;
+
+        // Resting contacts of sleeping bodies are kept as they were so their
+        // islands stay together, those pairs are not tested again.
+        let is_sleeping: Vec<bool> = objects.iter().map(|o| o.physics().is_sleeping).collect();
+        let is_frozen: Vec<bool> = objects.iter().map(|o| o.physics().is_frozen()).collect();
+        let is_resting_pair = |i: usize, j: usize| -> bool {
+            (is_sleeping[i] || is_sleeping[j]) && is_frozen[i] && is_frozen[j]
+        };
+        type Manifolds = HashMap<(usize, usize), Vec<ContactManifold>>;
+        let (mut contact_manifolds, mut previous_manifolds): (Manifolds, Manifolds) = self
+            .contact_manifolds
+            .drain()
+            .partition(|((i, j), _)| is_resting_pair(*i, *j));
+
+        for (i, j) in candidate_pairs {
+            if is_resting_pair(i, j) || ignored_pairs.contains(&(i, j)) {
+                continue;
+            }
+            let (physics1, physics2) = {
+                let (left, right) = objects.split_at_mut(j);
+                (left[i].physics_mut(), right[0].physics_mut())
+            };
+
+            let mut manifolds: Vec<ContactManifold> =
+                previous_manifolds.remove(&(i, j)).unwrap_or_default();
+            physics1.find_contacts(physics2, narrow_phase, &mut manifolds);
+            if manifolds.is_empty() {
+                continue;
+            }
+
+            // Merging and shattering pairs get no contact response, they are
+            // replaced once the step is done.
+            let outcome: CollisionOutcome = self.collision_policy.get_outcome(physics1, physics2);
+            if outcome != CollisionOutcome::Bounce {
+                if !self
+                    .collision_outcomes
+                    .iter()
+                    .any(|(a, b, _)| (*a, *b) == (i, j))
+                {
+                    self.collision_outcomes.push((i, j, outcome));
+                }
+                continue;
+            }
+
+            if is_sleeping[i] || is_sleeping[j] {
+                physics1.wake();
+                physics2.wake();
+            }
+            contact_manifolds.insert((i, j), manifolds);
+        }
+        self.contact_manifolds = contact_manifolds;
+    }
+
+    // Fast bodies are stopped at their first impact within the step, the
+    // contact is resolved there and the rest of the step is integrated after.
+    fn advance_objects(&mut self, timestep: f64, active: Option<&[bool]>) {
+        let mut remaining: f64 = timestep;
+
+        if self.continuous_collision.enabled {
+            for _ in 0..self.continuous_collision.max_substeps {
+                let fraction: f64 = match self.get_impact_fraction(remaining, active) {
+                    Some(fraction) => fraction,
+                    None => break,
                 };
+                let substep: f64 = remaining * fraction;
+                self.solve_constraints(substep, active);
+                self.integrate_objects(substep, active);
+                remaining -= substep;
+                self.continuous_collision.impact_count += 1;
+                self.compute_collisions();
+            }
+        }
 
-                physics1.apply_forces(physics2, timestep);
+        if remaining > 0.0 {
+            self.solve_constraints(remaining, active);
+            self.integrate_objects(remaining, active);
+        }
+    }
+
+    // Contacts and joints are solved together against the velocities the
+    // bodies will have once the forces of the step are applied, so gravity
+    // does not push resting bodies into each other or pull joints apart a
+    // little every step.
+    fn solve_constraints(&mut self, timestep: f64, active: Option<&[bool]>) {
+        let has_joints: bool = self
+            .joint_solver
+            .joints
+            .iter()
+            .any(|joint| !joint.is_broken);
+        if self.contact_manifolds.is_empty() && !has_joints {
+            return;
+        }
+        let is_active = |idx: usize| -> bool { active.is_none_or(|active| active[idx]) };
+        let is_movable: Vec<bool> = self
+            .objects
+            .iter()
+            .enumerate()
+            .map(|(idx, object)| !object.physics().is_frozen() && is_active(idx))
+            .collect();
+
+        let positions: Vec<Vector3D> = self
+            .objects
+            .iter()
+            .map(|o| o.physics().get_position())
+            .collect();
+        let velocities: Vec<Vector3D> = self.objects.iter().map(|o| o.physics().velocity).collect();
+        let state: LinearState = LinearState::new(positions, velocities);
+        let gravity_accelerations: Vec<Vector3D> =
+            self.gravity.get_accelerations_at(&self.objects, &state.positions);
+        let force_accelerations: Vec<Vector3D> =
+            self.forces.get_accelerations(&self.objects, &state);
+        let velocity_changes: Vec<Vector3D> = self
+            .objects
+            .iter()
+            .zip(gravity_accelerations)
+            .zip(force_accelerations)
+            .map(|((object, gravity_acceleration), force_acceleration)| {
+                let acceleration: Vector3D = object.physics().acceleration;
+                acceleration
+                    .add_vector(&gravity_acceleration)
+                    .add_vector(&force_acceleration)
+                    .multiply(timestep)
+            })
+            .collect();
+
+        let movable = self.objects.iter_mut().zip(&velocity_changes).zip(&is_movable);
+        for ((object, change), _) in movable.filter(|(_, is_movable)| **is_movable) {
+            let physics: &mut Physics = object.physics_mut();
+            physics.velocity = physics.velocity.add_vector(change);
+        }
+
+        let objects: &mut [BodyType] = &mut self.objects;
+        let contact_solver: &mut ContactSolver = &mut self.contact_solver;
+        let joint_solver: &mut JointSolver = &mut self.joint_solver;
+        contact_solver.prepare(objects, &self.contact_manifolds, &is_movable, timestep);
+        joint_solver.prepare(objects, &is_movable, timestep);
+        for iteration in 0..contact_solver.iterations.max(joint_solver.iterations) {
+            if iteration < contact_solver.iterations {
+                contact_solver.iterate(objects);
+            }
+            if iteration < joint_solver.iterations {
+                joint_solver.iterate(objects);
             }
         }
+        contact_solver.store_impulses(&mut self.contact_manifolds);
+        joint_solver.update_breaks(timestep);
 
+        let movable = self.objects.iter_mut().zip(&velocity_changes).zip(&is_movable);
+        for ((object, change), _) in movable.filter(|(_, is_movable)| **is_movable) {
+            let physics: &mut Physics = object.physics_mut();
+            physics.velocity = physics.velocity.subtract_vector(change);
+        }
+    }
+
+    // Returns the fraction of the step until the earliest impact of a fast
+    // body, pairs that already overlap are left to the discrete test.
+    fn get_impact_fraction(&mut self, timestep: f64, active: Option<&[bool]>) -> Option<f64> {
+        let is_active = |idx: usize| -> bool { active.is_none_or(|active| active[idx]) };
+        let volumes: Vec<SweptVolume> = self
+            .objects
+            .iter()
+            .enumerate()
+            .map(|(idx, object)| {
+                let physics: &Physics = object.physics();
+                let displacement: Vector3D = if physics.is_frozen() || !is_active(idx) {
+                    Vector3D::default(0.0)
+                } else {
+                    physics.velocity.multiply(timestep)
+                };
+                let radius: f64 = physics.get_bounding_radius();
+                let center: Vector3D = physics.transform.position;
+                SweptVolume::new(center, radius, physics.get_aabb(), displacement)
+            })
+            .collect();
+        let is_fast: Vec<bool> = volumes
+            .iter()
+            .map(|v| self.continuous_collision.is_fast(v))
+            .collect();
+        if !is_fast.contains(&true) {
+            return None;
+        }
+
+        let swept_aabbs: Vec<AABB> = volumes.iter().map(|v| v.get_swept_aabb()).collect();
+        let candidate_pairs: Vec<(usize, usize)> =
+            self.broad_phase.get_candidate_pairs(&swept_aabbs);
+
+        candidate_pairs
+            .into_iter()
+            .filter(|(i, j)| is_fast[*i] || is_fast[*j])
+            .filter_map(|(i, j)| {
+                self.continuous_collision
+                    .get_time_of_impact(&volumes[i], &volumes[j])
+            })
+            .filter(|time| *time > 0.0)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    // Only the linear state goes through the integrator, rotation keeps the
+    // midpoint update in Physics.
+    fn integrate_objects(&mut self, timestep: f64, active: Option<&[bool]>) {
+        let is_active = |idx: usize| -> bool { active.is_none_or(|active| active[idx]) };
         for object in self.objects.iter_mut() {
+            object.physics_mut().handle_stationary_update();
+        }
+
+        let objects: &[BodyType] = &self.objects;
+        let gravity: &Gravity = &self.gravity;
+        let forces: &ForceRegistry = &self.forces;
+        let physics: Vec<&Physics> = objects.iter().map(|o| o.physics()).collect();
+        let is_frozen: Vec<bool> = physics
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| p.is_frozen() || !is_active(idx))
+            .collect();
+        let positions: Vec<Vector3D> = physics.iter().map(|p| p.get_position()).collect();
+        let velocities: Vec<Vector3D> = physics
+            .iter()
+            .zip(&is_frozen)
+            .map(|(p, frozen)| if *frozen { Vector3D::default(0.0) } else { p.velocity })
+            .collect();
+        let mut state: LinearState = LinearState::new(positions, velocities);
+
+        let accelerations = |state: &LinearState| -> Vec<Vector3D> {
+            let gravity_accelerations: Vec<Vector3D> =
+                gravity.get_accelerations_at(objects, &state.positions);
+            let force_accelerations: Vec<Vector3D> = forces.get_accelerations(objects, state);
+            physics
+                .iter()
+                .zip(&is_frozen)
+                .zip(gravity_accelerations)
+                .zip(force_accelerations)
+                .map(|(((p, frozen), gravity_acceleration), force_acceleration)| {
+                    if *frozen {
+                        return Vector3D::default(0.0);
+                    }
+                    p.acceleration
+                        .add_vector(&gravity_acceleration)
+                        .add_vector(&force_acceleration)
+                })
+                .collect()
+        };
+        self.integrator
+            .integrate(&mut state, &accelerations, timestep);
+
+        let linear_states = state.positions.into_iter().zip(state.velocities);
+        for (idx, (object, (position, velocity))) in
+            self.objects.iter_mut().zip(linear_states).enumerate()
+        {
             let physics: &mut Physics = object.physics_mut();
-            physics.update(timestep);
+            if !is_active(idx) || physics.is_sleeping {
+                continue;
+            }
+            physics.set_linear_state(position, velocity);
+            physics.update_rotation(timestep);
+            physics.reset_accelerations();
+        }
+    }
+
+    pub fn advance(&mut self, frame_time: f64) {
+        let timestep: f64 = self.get_timestep();
+        self.accumulator += frame_time * self.real_time_factor;
+
+        let mut steps: usize = 0;
+        while self.accumulator >= timestep && steps < self.max_steps_per_frame {
+            self.compute_objects();
+            self.accumulator -= timestep;
+            steps += 1;
+        }
+
+        // Drop the backlog when the step cap is hit, otherwise slow frames
+        // queue ever more steps for the frames that follow.
+        if steps == self.max_steps_per_frame {
+            self.accumulator = self.accumulator.min(timestep);
+        }
+
+        self.steps_per_frame = steps;
+    }
+
+    fn add_text(&mut self, text: String) {
+        if let Some(text_writer) = self.text_writer.as_mut() {
+            text_writer.add_text_top_left(text, None);
         }
     }
 
@@ -189,13 +870,90 @@ impl Simulation {
     fn write_fps_text(&mut self, fps: f64) {
         let header_text = format!("Engine information");
         let text = format!("{:.2} FPS", fps);
-        self.text_writer.add_text_top_left(header_text, None);
-        self.text_writer.add_text_top_left(text, None);
+        self.add_text(header_text);
+        self.add_text(text);
     }
 
     fn write_timestep_text(&mut self) {
         let text = self.get_timestep_text();
-        self.text_writer.add_text_top_left(text, None);
+        let integrator = format!("Integrator:  {}", self.integrator.get_name());
+        let adaptive_timestep: &AdaptiveTimestep = &self.adaptive_timestep;
+        let timestep_mode = format!(
+            "Timestep Mode:  {} ({}, tolerance {:.2})",
+            adaptive_timestep.mode.get_name(),
+            adaptive_timestep.criterion.get_name(),
+            adaptive_timestep.tolerance
+        );
+        let effective_timestep = format!(
+            "Effective dt:  {:.6} s",
+            adaptive_timestep.effective_timestep
+        );
+        let sim_time = format!("Sim Time:  {:.2} s", self.sim_time);
+        let real_time_factor = format!("Real-Time Factor:  {:.2}x", self.real_time_factor);
+        let steps_per_frame = format!(
+            "Steps / Frame:  {} (max {})",
+            self.steps_per_frame, self.max_steps_per_frame
+        );
+        self.add_text(text);
+        self.add_text(integrator);
+        self.add_text(timestep_mode);
+        self.add_text(effective_timestep);
+        self.add_text(sim_time);
+        self.add_text(real_time_factor);
+        self.add_text(steps_per_frame);
+    }
+
+    fn write_gravity_text(&mut self) {
+        let gravity: &Gravity = &self.gravity;
+        let text = format!(
+            "Gravity:  {} (theta {:.2}, softening {:.2})",
+            gravity.solver.get_name(),
+            gravity.theta,
+            gravity.softening
+        );
+        let forces = format!(
+            "Force Generators:  {} ({} / {} active)",
+            if self.forces.enabled { "On" } else { "Off" },
+            self.forces.get_active_count(),
+            self.forces.entries.len()
+        );
+        self.add_text(text);
+        self.add_text(forces);
+    }
+
+    fn write_broad_phase_text(&mut self) {
+        let broad_phase = format!("Broad Phase:  {}", self.broad_phase.get_name());
+        let candidate_pairs = format!(
+            "Candidate Pairs:  {} / {}",
+            self.candidate_pairs, self.total_pairs
+        );
+        let narrow_phase = format!("Narrow Phase:  {}", self.narrow_phase.get_name());
+        let continuous_collision = format!(
+            "Continuous Collision:  {} ({} impacts)",
+            if self.continuous_collision.enabled {
+                "On"
+            } else {
+                "Off"
+            },
+            self.continuous_collision.impact_count
+        );
+        self.add_text(broad_phase);
+        self.add_text(candidate_pairs);
+        let contact_solver = format!(
+            "Contact Solver:  {} iterations, bias {:.2}",
+            self.contact_solver.iterations, self.contact_solver.bias_factor
+        );
+        self.add_text(narrow_phase);
+        let collision_policy = match &self.collision_policy {
+            CollisionPolicy::Bounce => String::from("Collisions:  Bounce"),
+            CollisionPolicy::Accretion(model) => format!(
+                "Collisions:  Accretion ({} merged, {} shattered)",
+                model.merge_count, model.shatter_count
+            ),
+        };
+        self.add_text(contact_solver);
+        self.add_text(continuous_collision);
+        self.add_text(collision_policy);
     }
 
     fn write_object_count(&mut self) {
@@ -204,10 +962,80 @@ impl Simulation {
         let text_polygon_count = format!("Polygon Count: {}", self.polygon_count);
         let text_draw_polygons = format!("Show Polygons: {}", self.draw_polygons);
         let text_draw_mesh = format!("Show Mesh: {}", self.draw_mesh);
-        self.text_writer.add_text_top_left(text_object_count, None);
-        self.text_writer.add_text_top_left(text_polygon_count, None);
-        self.text_writer.add_text_top_left(text_draw_polygons, None);
-        self.text_writer.add_text_top_left(text_draw_mesh, None);
+        let text_sleeping = format!(
+            "Awake / Sleeping:  {} / {} ({} islands)",
+            self.sleep.awake_count, self.sleep.sleeping_count, self.sleep.island_count
+        );
+        let text_joints = format!(
+            "Joints:  {} ({} broken)",
+            self.joint_solver.joints.len(),
+            self.joint_solver.get_broken_count()
+        );
+        self.add_text(text_object_count);
+        self.add_text(text_sleeping);
+        self.add_text(text_joints);
+        self.add_text(text_polygon_count);
+        self.add_text(text_draw_polygons);
+        self.add_text(text_draw_mesh);
+    }
+
+    fn write_diagnostics_text(&mut self) {
+        if !self.diagnostics.enabled {
+            self.add_text(String::from("Diagnostics:  Off"));
+            return;
+        }
+        let (Some(current), Some(drift)) = (self.diagnostics.current, self.diagnostics.get_drift())
+        else {
+            return;
+        };
+        let text_energy = format!(
+            "Energy:  {:.3e} (K {:.3e}, R {:.3e}, U {:.3e})",
+            current.get_total_energy(),
+            current.kinetic_energy,
+            current.rotational_energy,
+            current.potential_energy
+        );
+        let text_energy_drift = format!("Energy Drift:  {:+.3e}", drift.energy);
+        let text_momentum = format!(
+            "Momentum Drift:  linear {:.3e}, angular {:.3e}",
+            drift.linear_momentum, drift.angular_momentum
+        );
+        let text_center_of_mass = format!(
+            "Center of Mass:  {} (drift {:.3e})",
+            current.center_of_mass.to_string(),
+            drift.center_of_mass
+        );
+        self.add_text(text_energy);
+        let unconserved: Vec<&'static str> = self.forces.get_unconserved_names();
+        if !unconserved.is_empty() {
+            self.add_text(format!("Energy Leaves Out:  {}", unconserved.join(", ")));
+        }
+        self.add_text(text_energy_drift);
+        self.add_text(text_momentum);
+        self.add_text(text_center_of_mass);
+    }
+
+    // The energy drift history sits in the bottom left corner, the grey line
+    // marks zero drift.
+    fn draw_energy_sparkline(&self, graphics: &mut Graphics) {
+        let height: f64 = self.camera.frustum.height as f64;
+        let origin: (f64, f64) = (20.0, height - 80.0);
+        let size: (f64, f64) = (240.0, 50.0);
+        let (points, zero_y): (Vec<(f64, f64)>, f64) = self.diagnostics.get_sparkline(origin, size);
+        if points.len() < 2 {
+            return;
+        }
+
+        let v1: Vector3D = Vector3D::new(origin.0, zero_y, 0.0);
+        let v2: Vector3D = Vector3D::new(origin.0 + size.0, zero_y, 0.0);
+        graphics.draw_line(v1, v2, RGBA::from_rgb(0.4, 0.4, 0.4), 1.0);
+
+        let color: RGBA = RGBA::from_rgb(1.0, 0.85, 0.2);
+        for pair in points.windows(2) {
+            let v1: Vector3D = Vector3D::new(pair[0].0, pair[0].1, 0.0);
+            let v2: Vector3D = Vector3D::new(pair[1].0, pair[1].1, 0.0);
+            graphics.draw_line(v1, v2, color, 1.5);
+        }
     }
 
     fn write_camera_information(&mut self) {
@@ -231,32 +1059,38 @@ impl Simulation {
         let up_dir = format!("Up (d):  {}", clu.to_string());
         let side_dir = format!("Side (d):  {}", cls.to_string());
 
-        self.text_writer.add_text_top_left("".to_string(), None);
-        self.text_writer.add_text_top_left(info_header, None);
-        self.text_writer.add_text_top_left(y_lock, None);
-        self.text_writer.add_text_top_left(fov, None);
-        self.text_writer.add_text_top_left(near_plane, None);
-        self.text_writer.add_text_top_left(far_plane, None);
-        self.text_writer.add_text_top_left(yaw, None);
-        self.text_writer.add_text_top_left(pitch, None);
-        self.text_writer.add_text_top_left(position, None);
-        self.text_writer.add_text_top_left(target, None);
-        self.text_writer.add_text_top_left(look_dir, None);
-        self.text_writer.add_text_top_left(up_dir, None);
-        self.text_writer.add_text_top_left(side_dir, None);
+        self.add_text("".to_string());
+        self.add_text(info_header);
+        self.add_text(y_lock);
+        self.add_text(fov);
+        self.add_text(near_plane);
+        self.add_text(far_plane);
+        self.add_text(yaw);
+        self.add_text(pitch);
+        self.add_text(position);
+        self.add_text(target);
+        self.add_text(look_dir);
+        self.add_text(up_dir);
+        self.add_text(side_dir);
     }
 
     fn draw_text(&mut self, graphics: &mut Graphics) {
-        self.text_writer.draw(graphics);
+        if let Some(text_writer) = self.text_writer.as_mut() {
+            text_writer.draw(graphics);
+        }
     }
 
-    pub fn simulate(&mut self, graphics: &mut Graphics, fps: f64) {
-        self.compute_objects();
+    pub fn simulate(&mut self, graphics: &mut Graphics, fps: f64, frame_time: f64) {
+        self.advance(frame_time);
 
         self.write_fps_text(fps);
         self.write_timestep_text();
+        self.write_gravity_text();
+        self.write_broad_phase_text();
         self.write_object_count();
+        self.write_diagnostics_text();
         self.write_camera_information();
         self.draw_text(graphics);
+        self.draw_energy_sparkline(graphics);
     }
 }
@@ -0,0 +1,182 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::color::RGBA;
+use crate::components::physics::Physics;
+use crate::components::vectors::Vector3D;
+
+// Relative change of the force generator acceleration that wakes a body.
+const ACCELERATION_TOLERANCE: f64 = 1e-6;
+
+#[derive(Clone, Debug)]
+pub struct SleepManager {
+    pub enabled: bool,
+    pub linear_threshold: f64,
+    pub angular_threshold: f64,
+    pub sleep_steps: usize,
+    pub draw_tint: bool,
+    pub tint: RGBA,
+    pub awake_count: usize,
+    pub sleeping_count: usize,
+    pub island_count: usize,
+}
+
+impl SleepManager {
+    pub fn new(enabled: bool) -> SleepManager {
+        SleepManager {
+            enabled,
+            linear_threshold: 0.002,
+            angular_threshold: 0.05,
+            sleep_steps: 30,
+            draw_tint: false,
+            tint: RGBA::from_rgb(0.35, 0.45, 0.8),
+            awake_count: 0,
+            sleeping_count: 0,
+            island_count: 0,
+        }
+    }
+
+    pub fn set_thresholds(&mut self, linear_threshold: f64, angular_threshold: f64) {
+        self.linear_threshold = linear_threshold;
+        self.angular_threshold = angular_threshold;
+    }
+
+    pub fn set_sleep_steps(&mut self, sleep_steps: usize) {
+        self.sleep_steps = sleep_steps;
+    }
+
+    pub fn toggle_draw_tint(&mut self) {
+        self.draw_tint = !self.draw_tint;
+    }
+
+    // The linear threshold is in body radii per second, so it holds for the
+    // very different scales of the scenes.
+    fn is_resting(&self, physics: &Physics) -> bool {
+        let linear_threshold: f64 = self.linear_threshold * physics.get_bounding_radius();
+        physics.velocity.get_length() < linear_threshold
+            && physics.angular_velocity.get_length() < self.angular_threshold
+    }
+
+    /// Wakes sleeping bodies that had a force applied since the last step,
+    /// or whose force generator acceleration changed since they fell asleep.
+    pub fn wake_forced(&self, objects: &mut [BodyType], force_accelerations: &[Vector3D]) {
+        for (object, acceleration) in objects.iter_mut().zip(force_accelerations) {
+            let physics: &mut Physics = object.physics_mut();
+            if !physics.is_sleeping {
+                continue;
+            }
+            // The first acceleration after falling asleep is held by the
+            // resting contacts, like a uniform gravity. Wind, fields or
+            // springs that come up or change later wake the body again.
+            let sleep_acceleration: Vector3D =
+                *physics.sleep_acceleration.get_or_insert(*acceleration);
+            let tolerance: f64 = ACCELERATION_TOLERANCE * sleep_acceleration.get_length().max(1.0);
+            let is_changed: bool = acceleration.get_distance(&sleep_acceleration) > tolerance;
+            if physics.has_applied_force() || is_changed {
+                physics.wake();
+            }
+        }
+    }
+
+    // An island only sleeps when every body in it has rested long enough and
+    // it touches something, so free bodies at the top of an orbit keep going.
+    // Otherwise the whole island is woken, which also wakes sleeping bodies
+    // that an awake body has come into contact with.
+    pub fn update(&mut self, objects: &mut [BodyType], contact_pairs: &[(usize, usize)]) {
+        for object in objects.iter_mut() {
+            let physics: &mut Physics = object.physics_mut();
+            if physics.is_frozen() {
+                continue;
+            }
+            if self.is_resting(physics) {
+                physics.sleep_timer += 1;
+            } else {
+                physics.sleep_timer = 0;
+            }
+        }
+
+        let is_static: Vec<bool> = objects.iter().map(|o| o.physics().is_stationary).collect();
+        let mut in_contact: Vec<bool> = vec![false; objects.len()];
+        for (i, j) in contact_pairs {
+            in_contact[*i] = true;
+            in_contact[*j] = true;
+        }
+
+        let islands: Vec<Vec<usize>> = get_islands(objects.len(), contact_pairs, &is_static);
+        for island in islands.iter() {
+            let can_sleep: bool = self.enabled
+                && island.iter().any(|idx| in_contact[*idx])
+                && island.iter().all(|idx| {
+                    let physics: &Physics = objects[*idx].physics();
+                    physics.is_sleeping || physics.sleep_timer >= self.sleep_steps
+                });
+
+            for idx in island {
+                let physics: &mut Physics = objects[*idx].physics_mut();
+                if can_sleep && !physics.is_sleeping {
+                    physics.sleep();
+                } else if !can_sleep && physics.is_sleeping {
+                    physics.wake();
+                }
+            }
+        }
+
+        self.island_count = islands.len();
+        self.sleeping_count = objects.iter().filter(|o| o.physics().is_sleeping).count();
+        self.awake_count =
+            islands.iter().map(|island| island.len()).sum::<usize>() - self.sleeping_count;
+    }
+}
+
+fn find_root(parents: &mut [usize], idx: usize) -> usize {
+    let mut root: usize = idx;
+    while parents[root] != root {
+        root = parents[root];
+    }
+
+    let mut idx: usize = idx;
+    while parents[idx] != root {
+        let next: usize = parents[idx];
+        parents[idx] = root;
+        idx = next;
+    }
+    root
+}
+
+/// Groups the dynamic bodies into islands of bodies connected through
+/// contacts. Static bodies do not join islands, so bodies resting on the
+/// same ground stay independent.
+pub fn get_islands(
+    body_count: usize,
+    contact_pairs: &[(usize, usize)],
+    is_static: &[bool],
+) -> Vec<Vec<usize>> {
+    let mut parents: Vec<usize> = (0..body_count).collect();
+
+    for (i, j) in contact_pairs {
+        if is_static[*i] || is_static[*j] {
+            continue;
+        }
+        let root_i: usize = find_root(&mut parents, *i);
+        let root_j: usize = find_root(&mut parents, *j);
+        parents[root_i] = root_j;
+    }
+
+    let mut island_idxs: Vec<Option<usize>> = vec![None; body_count];
+    let mut islands: Vec<Vec<usize>> = Vec::new();
+    for (idx, is_static) in is_static.iter().enumerate() {
+        if *is_static {
+            continue;
+        }
+        let root: usize = find_root(&mut parents, idx);
+        let island_idx: usize = match island_idxs[root] {
+            Some(island_idx) => island_idx,
+            None => {
+                islands.push(Vec::new());
+                island_idxs[root] = Some(islands.len() - 1);
+                islands.len() - 1
+            }
+        };
+        islands[island_idx].push(idx);
+    }
+    islands
+}
@@ -0,0 +1,299 @@
+use crate::abstracts::body::Body;
+use crate::abstracts::body::BodyType;
+use crate::components::gravity::Gravity;
+use crate::components::physics::Physics;
+use crate::components::vectors::Vector3D;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimestepMode {
+    Fixed,
+    Global,
+    Block,
+}
+
+impl TimestepMode {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TimestepMode::Fixed => "Fixed",
+            TimestepMode::Global => "Global Refinement",
+            TimestepMode::Block => "Block",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TimestepMode> {
+        match name.to_lowercase().as_str() {
+            "fixed" => Some(TimestepMode::Fixed),
+            "global" | "refine" => Some(TimestepMode::Global),
+            "block" | "individual" => Some(TimestepMode::Block),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> TimestepMode {
+        match self {
+            TimestepMode::Fixed => TimestepMode::Global,
+            TimestepMode::Global => TimestepMode::Block,
+            TimestepMode::Block => TimestepMode::Fixed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimestepCriterion {
+    Acceleration,
+    Jerk,
+    Separation,
+}
+
+impl TimestepCriterion {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TimestepCriterion::Acceleration => "Acceleration",
+            TimestepCriterion::Jerk => "Jerk",
+            TimestepCriterion::Separation => "Separation",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TimestepCriterion> {
+        match name.to_lowercase().as_str() {
+            "acceleration" | "accel" => Some(TimestepCriterion::Acceleration),
+            "jerk" => Some(TimestepCriterion::Jerk),
+            "separation" | "approach" => Some(TimestepCriterion::Separation),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AdaptiveTimestep {
+    pub mode: TimestepMode,
+    pub criterion: TimestepCriterion,
+    pub tolerance: f64,
+    pub max_level: u32,
+    pub effective_timestep: f64,
+    previous_accelerations: Vec<Vector3D>,
+    previous_times: Vec<f64>,
+}
+
+impl AdaptiveTimestep {
+    pub fn new(
+        mode: TimestepMode,
+        criterion: TimestepCriterion,
+        tolerance: f64,
+    ) -> AdaptiveTimestep {
+        AdaptiveTimestep {
+            mode,
+            criterion,
+            tolerance,
+            max_level: 10,
+            effective_timestep: 0.0,
+            previous_accelerations: Vec::new(),
+            previous_times: Vec::new(),
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: TimestepMode) {
+        self.mode = mode;
+    }
+
+    pub fn set_criterion(&mut self, criterion: TimestepCriterion) {
+        self.criterion = criterion;
+        self.clear_history();
+    }
+
+    /// Forgets the accelerations of the previous steps, which no longer
+    /// match once bodies were added or removed.
+    pub fn clear_history(&mut self) {
+        self.previous_accelerations.clear();
+        self.previous_times.clear();
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f64) {
+        self.tolerance = tolerance;
+    }
+
+    pub fn set_max_level(&mut self, max_level: u32) {
+        self.max_level = max_level.min(30);
+    }
+
+    pub fn get_tick_count(&self) -> u64 {
+        1 << self.max_level
+    }
+
+    pub fn get_tick_interval(&self, level: u32) -> u64 {
+        1 << (self.max_level - level)
+    }
+
+    // A body may only move to a coarser level where the coarser blocks line
+    // up with the current tick, otherwise it would overshoot the step.
+    pub fn get_aligned_level(&self, level: u32, tick: u64) -> u32 {
+        let mut level: u32 = level;
+        while !tick.is_multiple_of(self.get_tick_interval(level)) {
+            level += 1;
+        }
+        level
+    }
+
+    fn get_radius(physics: &Physics) -> f64 {
+        let (min, max): ([f64; 3], [f64; 3]) = physics.get_aabb();
+        let diagonal: Vector3D =
+            Vector3D::from_array(max).subtract_vector(&Vector3D::from_array(min));
+        diagonal.get_length() / 2.0
+    }
+
+    fn get_accelerations(
+        objects: &[BodyType],
+        gravity: &Gravity,
+        force_accelerations: &[Vector3D],
+    ) -> Vec<Vector3D> {
+        let gravity_accelerations: Vec<Vector3D> = gravity.get_accelerations(objects);
+        objects
+            .iter()
+            .zip(gravity_accelerations)
+            .zip(force_accelerations)
+            .map(|((o, a), force_acceleration)| {
+                o.physics()
+                    .acceleration
+                    .add_vector(&a)
+                    .add_vector(force_acceleration)
+            })
+            .collect()
+    }
+
+    // A body crosses its own size within a step when dt = sqrt(r / |a|).
+    fn get_acceleration_timesteps(
+        &self,
+        objects: &[BodyType],
+        accelerations: &[Vector3D],
+    ) -> Vec<f64> {
+        objects
+            .iter()
+            .zip(accelerations)
+            .map(|(o, a)| {
+                let radius: f64 = Self::get_radius(o.physics());
+                self.tolerance * (radius / a.get_length()).sqrt()
+            })
+            .collect()
+    }
+
+    // Uses the change in acceleration since the body was last evaluated,
+    // bodies without a history fall back to the acceleration criterion.
+    fn get_jerk_timesteps(
+        &self,
+        objects: &[BodyType],
+        accelerations: &[Vector3D],
+        time: f64,
+    ) -> Vec<f64> {
+        let fallback: Vec<f64> = self.get_acceleration_timesteps(objects, accelerations);
+        if self.previous_accelerations.len() != accelerations.len() {
+            return fallback;
+        }
+
+        accelerations
+            .iter()
+            .zip(&self.previous_accelerations)
+            .zip(&self.previous_times)
+            .zip(fallback)
+            .map(|(((a, previous), previous_time), fallback)| {
+                let elapsed: f64 = time - previous_time;
+                if elapsed <= 0.0 {
+                    return fallback;
+                }
+                let jerk: f64 = a.subtract_vector(previous).get_length() / elapsed;
+                let timestep: f64 = self.tolerance * a.get_length() / jerk;
+                if timestep.is_finite() {
+                    timestep
+                } else {
+                    fallback
+                }
+            })
+            .collect()
+    }
+
+    fn record_accelerations(
+        &mut self,
+        accelerations: Vec<Vector3D>,
+        time: f64,
+        due: Option<&[bool]>,
+    ) {
+        if self.previous_accelerations.len() != accelerations.len() {
+            self.previous_times = vec![time; accelerations.len()];
+            self.previous_accelerations = accelerations;
+            return;
+        }
+        for (idx, acceleration) in accelerations.into_iter().enumerate() {
+            if due.is_none_or(|due| due[idx]) {
+                self.previous_accelerations[idx] = acceleration;
+                self.previous_times[idx] = time;
+            }
+        }
+    }
+
+    fn get_separation_timesteps(&self, objects: &[BodyType]) -> Vec<f64> {
+        let physics: Vec<&Physics> = objects.iter().map(|o| o.physics()).collect();
+        let mut timesteps: Vec<f64> = vec![f64::INFINITY; physics.len()];
+
+        for i in 0..physics.len() {
+            for j in (i + 1)..physics.len() {
+                let separation: f64 = physics[i]
+                    .get_position()
+                    .get_distance(&physics[j].get_position());
+                let relative_velocity: Vector3D =
+                    physics[i].velocity.subtract_vector(&physics[j].velocity);
+                let timestep: f64 = self.tolerance * separation / relative_velocity.get_length();
+                timesteps[i] = timesteps[i].min(timestep);
+                timesteps[j] = timesteps[j].min(timestep);
+            }
+        }
+        timesteps
+    }
+
+    fn get_level(&self, timestep: f64, body_timestep: f64) -> u32 {
+        if body_timestep.is_nan() || body_timestep >= timestep {
+            return 0;
+        }
+        let level: f64 = (timestep / body_timestep).log2().ceil();
+        (level as u32).min(self.max_level)
+    }
+
+    /// Returns the refinement level of every body at the given time, a body
+    /// at level k takes steps of timestep / 2^k. The force accelerations come
+    /// from the force registry. Only the due bodies record their
+    /// accelerations for the jerk criterion.
+    pub fn get_levels(
+        &mut self,
+        objects: &[BodyType],
+        gravity: &Gravity,
+        force_accelerations: &[Vector3D],
+        timestep: f64,
+        time: f64,
+        due: Option<&[bool]>,
+    ) -> Vec<u32> {
+        let body_timesteps: Vec<f64> = match self.criterion {
+            TimestepCriterion::Acceleration => {
+                let accelerations: Vec<Vector3D> =
+                    Self::get_accelerations(objects, gravity, force_accelerations);
+                self.get_acceleration_timesteps(objects, &accelerations)
+            }
+            TimestepCriterion::Jerk => {
+                let accelerations: Vec<Vector3D> =
+                    Self::get_accelerations(objects, gravity, force_accelerations);
+                let timesteps: Vec<f64> = self.get_jerk_timesteps(objects, &accelerations, time);
+                self.record_accelerations(accelerations, time, due);
+                timesteps
+            }
+            TimestepCriterion::Separation => self.get_separation_timesteps(objects),
+        };
+
+        objects
+            .iter()
+            .zip(body_timesteps)
+            .map(|(o, body_timestep)| {
+                if o.physics().is_frozen() {
+                    return 0;
+                }
+                self.get_level(timestep, body_timestep)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::components::vectors::Matrix3;
+use crate::components::vectors::Quaternion;
+use crate::components::vectors::Vector3D;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub position: Vector3D,
+    pub orientation: Quaternion,
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::new(Vector3D::default(0.0), Quaternion::identity())
+    }
+}
+
+impl Transform {
+    pub fn new(position: Vector3D, orientation: Quaternion) -> Transform {
+        Transform {
+            position,
+            orientation,
+        }
+    }
+
+    pub fn from_position(position: Vector3D) -> Transform {
+        Transform::new(position, Quaternion::identity())
+    }
+
+    pub fn get_rotation_matrix(&self) -> Matrix3 {
+        self.orientation.to_matrix()
+    }
+
+    pub fn translate(&mut self, translation: &Vector3D) {
+        self.position = self.position.add_vector(translation);
+    }
+
+    pub fn rotate(&mut self, axis: &Vector3D, angle: f64) {
+        if angle == 0.0 {
+            return;
+        }
+        let rotation: Quaternion = Quaternion::from_axis_angle(axis, angle);
+        self.orientation = rotation.multiply(&self.orientation).normalize();
+    }
+
+    pub fn apply_point(&self, point: &Vector3D) -> Vector3D {
+        self.orientation
+            .rotate_vector(point)
+            .add_vector(&self.position)
+    }
+
+    pub fn apply_direction(&self, direction: &Vector3D) -> Vector3D {
+        self.orientation.rotate_vector(direction)
+    }
+
+    pub fn inverse_apply_point(&self, point: &Vector3D) -> Vector3D {
+        let local: Vector3D = point.subtract_vector(&self.position);
+        self.orientation.conjugate().rotate_vector(&local)
+    }
+}
@@ -368,7 +368,7 @@ impl PartialOrd for Vector3D {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Quaternion {
     pub w: f64,
     pub x: f64,
@@ -393,4 +393,240 @@ impl Quaternion {
     pub fn conjugate(&self) -> Self {
         Self::new(self.w, -self.x, -self.y, -self.z)
     }
+
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    pub fn from_axis_angle(axis: &Vector3D, angle: f64) -> Self {
+        let axis: Vector3D = axis.normalize();
+        let half_angle: f64 = angle / 2.0;
+        let sin_half_angle: f64 = half_angle.sin();
+
+        Self::new(
+            half_angle.cos(),
+            axis.x * sin_half_angle,
+            axis.y * sin_half_angle,
+            axis.z * sin_half_angle,
+        )
+    }
+
+    pub fn to_axis_angle(&self) -> (Vector3D, f64) {
+        let q: Quaternion = self.normalize();
+        let w: f64 = q.w.clamp(-1.0, 1.0);
+        let angle: f64 = 2.0 * w.acos();
+        let sin_half_angle: f64 = (1.0 - w * w).sqrt();
+
+        if sin_half_angle < f64::EPSILON {
+            return (Vector3D::new(1.0, 0.0, 0.0), 0.0);
+        }
+        let axis: Vector3D = Vector3D::new(q.x, q.y, q.z).divide(sin_half_angle);
+        (axis, angle)
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn get_length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let length: f64 = self.get_length();
+        if length == 0.0 {
+            return Self::identity();
+        }
+        Self::new(
+            self.w / length,
+            self.x / length,
+            self.y / length,
+            self.z / length,
+        )
+    }
+
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Self {
+        let mut other: Quaternion = *other;
+        let mut cos_theta: f64 = self.dot(&other);
+
+        // Take the short way around the hypersphere.
+        if cos_theta < 0.0 {
+            other = Self::new(-other.w, -other.x, -other.y, -other.z);
+            cos_theta = -cos_theta;
+        }
+
+        let (scale_self, scale_other): (f64, f64) = if cos_theta > 1.0 - 1e-6 {
+            (1.0 - t, t)
+        } else {
+            let theta: f64 = cos_theta.acos();
+            let sin_theta: f64 = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+
+        Self::new(
+            self.w * scale_self + other.w * scale_other,
+            self.x * scale_self + other.x * scale_other,
+            self.y * scale_self + other.y * scale_other,
+            self.z * scale_self + other.z * scale_other,
+        )
+        .normalize()
+    }
+
+    pub fn rotate_vector(&self, vector: &Vector3D) -> Vector3D {
+        let p: Quaternion = Self::new(0.0, vector.x, vector.y, vector.z);
+        let rotated_p: Quaternion = self.multiply(&p).multiply(&self.conjugate());
+        Vector3D::new(rotated_p.x, rotated_p.y, rotated_p.z)
+    }
+
+    pub fn to_matrix(&self) -> Matrix3 {
+        let q: Quaternion = self.normalize();
+        let (w, x, y, z): (f64, f64, f64, f64) = (q.w, q.x, q.y, q.z);
+
+        Matrix3::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ])
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix3 {
+    pub m: [[f64; 3]; 3],
+}
+
+impl Matrix3 {
+    pub fn new(m: [[f64; 3]; 3]) -> Self {
+        Self { m }
+    }
+
+    pub fn zero() -> Self {
+        Self::new([[0.0; 3]; 3])
+    }
+
+    pub fn identity() -> Self {
+        Self::from_diagonal(&Vector3D::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn from_diagonal(diagonal: &Vector3D) -> Self {
+        Self::new([
+            [diagonal.x, 0.0, 0.0],
+            [0.0, diagonal.y, 0.0],
+            [0.0, 0.0, diagonal.z],
+        ])
+    }
+
+    pub fn from_columns(a: &Vector3D, b: &Vector3D, c: &Vector3D) -> Self {
+        Self::new([[a.x, b.x, c.x], [a.y, b.y, c.y], [a.z, b.z, c.z]])
+    }
+
+    pub fn get_diagonal(&self) -> Vector3D {
+        Vector3D::new(self.m[0][0], self.m[1][1], self.m[2][2])
+    }
+
+    pub fn get_trace(&self) -> f64 {
+        self.m[0][0] + self.m[1][1] + self.m[2][2]
+    }
+
+    pub fn add(&self, other: &Matrix3) -> Self {
+        let mut m: [[f64; 3]; 3] = self.m;
+        for (row, other_row) in m.iter_mut().zip(other.m.iter()) {
+            for (value, other_value) in row.iter_mut().zip(other_row.iter()) {
+                *value += other_value;
+            }
+        }
+        Self::new(m)
+    }
+
+    pub fn subtract(&self, other: &Matrix3) -> Self {
+        self.add(&other.scale(-1.0))
+    }
+
+    pub fn scale(&self, num: f64) -> Self {
+        let mut m: [[f64; 3]; 3] = self.m;
+        for row in m.iter_mut() {
+            for value in row.iter_mut() {
+                *value *= num;
+            }
+        }
+        Self::new(m)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m: [[f64; 3]; 3] = [[0.0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Self::new(m)
+    }
+
+    pub fn multiply(&self, other: &Matrix3) -> Self {
+        let mut m: [[f64; 3]; 3] = [[0.0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            }
+        }
+        Self::new(m)
+    }
+
+    pub fn multiply_vector(&self, vec: &Vector3D) -> Vector3D {
+        let m: &[[f64; 3]; 3] = &self.m;
+        Vector3D::new(
+            m[0][0] * vec.x + m[0][1] * vec.y + m[0][2] * vec.z,
+            m[1][0] * vec.x + m[1][1] * vec.y + m[1][2] * vec.z,
+            m[2][0] * vec.x + m[2][1] * vec.y + m[2][2] * vec.z,
+        )
+    }
+
+    pub fn get_determinant(&self) -> f64 {
+        let m: &[[f64; 3]; 3] = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn inverse(&self) -> Self {
+        let determinant: f64 = self.get_determinant();
+        if determinant == 0.0 {
+            return Self::zero();
+        }
+
+        let m: &[[f64; 3]; 3] = &self.m;
+        let adjugate: Matrix3 = Self::new([
+            [
+                m[1][1] * m[2][2] - m[1][2] * m[2][1],
+                m[0][2] * m[2][1] - m[0][1] * m[2][2],
+                m[0][1] * m[1][2] - m[0][2] * m[1][1],
+            ],
+            [
+                m[1][2] * m[2][0] - m[1][0] * m[2][2],
+                m[0][0] * m[2][2] - m[0][2] * m[2][0],
+                m[0][2] * m[1][0] - m[0][0] * m[1][2],
+            ],
+            [
+                m[1][0] * m[2][1] - m[1][1] * m[2][0],
+                m[0][1] * m[2][0] - m[0][0] * m[2][1],
+                m[0][0] * m[1][1] - m[0][1] * m[1][0],
+            ],
+        ]);
+        adjugate.scale(1.0 / determinant)
+    }
 }
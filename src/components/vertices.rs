@@ -1,3 +1,4 @@
+use crate::components::collision_shape::CollisionShape;
 use crate::components::color::RGBA;
 use crate::components::polygons::Mesh;
 use crate::components::polygons::Polygon;
@@ -51,6 +52,13 @@ impl Sphere {
         self.shader = shader;
     }
 
+    pub fn get_collision_shape(&self) -> CollisionShape {
+        CollisionShape::Sphere {
+            center: Vector3D::new(self.x_offset, self.y_offset, self.z_offset),
+            radius: self.radius,
+        }
+    }
+
     fn get_vertices(&self) -> Vec<Vector3D> {
         let mut vertices: Vec<Vector3D> = vec![];
 
@@ -126,7 +134,8 @@ impl Sphere {
             "{} {:?}, {}{:?}",
             "Sphere Vertices:", vertices_count, "Faces:", faces_count
         );
-        let mesh = Mesh::new(triangle_polygons);
+        let mut mesh = Mesh::new(triangle_polygons);
+        mesh.set_collision_shape(self.get_collision_shape());
         mesh
     }
 
@@ -146,7 +155,8 @@ impl Sphere {
             let polygon: Polygon = Polygon::Quad(triangle);
             quad_polygons.push(polygon);
         }
-        let mesh = Mesh::new(quad_polygons);
+        let mut mesh = Mesh::new(quad_polygons);
+        mesh.set_collision_shape(self.get_collision_shape());
         mesh
     }
 }
@@ -196,6 +206,12 @@ impl Cuboid {
         self.shader = shader;
     }
 
+    pub fn get_collision_shape(&self) -> CollisionShape {
+        let min: Vector3D = Vector3D::new(self.x_offset, self.y_offset, self.z_offset);
+        let size: Vector3D = Vector3D::new(self.width, self.height, self.depth);
+        CollisionShape::from_bounds(min, min.add_vector(&size))
+    }
+
     fn get_vertices(&self) -> Vec<Vector3D> {
         let mut vertices: Vec<Vector3D> = vec![];
 
@@ -262,7 +278,8 @@ impl Cuboid {
             "{} {:?}, {}{:?}",
             "Cuboid Vertices:", vertices_count, "Faces:", faces_count
         );
-        let mesh = Mesh::new(triangle_polygons);
+        let mut mesh = Mesh::new(triangle_polygons);
+        mesh.set_collision_shape(self.get_collision_shape());
         mesh
     }
 }
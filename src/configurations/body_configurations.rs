@@ -2,6 +2,7 @@ use crate::abstracts::body::Body;
 use crate::abstracts::body::BodyType;
 use crate::components::color::RGBA;
 use crate::components::decomposition::MeshDecompose;
+use crate::components::material::PhysicsMaterial;
 use crate::components::model::OBJModelFormat;
 use crate::components::shaders::Light;
 use crate::components::shape::Shape;
@@ -13,6 +14,42 @@ use crate::components::vertices::Sphere;
 use rand::rngs::ThreadRng;
 use rand::Rng;
 
+pub fn set_material(body: &mut BodyType, material_name: &str) {
+    match PhysicsMaterial::from_name(material_name) {
+        Some(material) => body.physics_mut().set_material(material),
+        None => println!("Unknown material: {:?}", material_name),
+    }
+}
+
+pub fn get_material_sphere(position: Vector3D, radius: f64, material_name: &str) -> BodyType {
+    let mut sphere = Sphere::new(radius, 10, 10);
+    sphere.set_offset(position.x, position.y, position.z);
+    sphere.set_color(RGBA::from_random());
+    sphere.set_shader(RGBA::from_rgb(0.5, 0.5, 0.5));
+    let mesh = sphere.get_triangle_mesh();
+
+    let mut body = Shape::new(mesh, None);
+    body.physics_mut()
+        .set_position(position.x, position.y, position.z);
+    let mut body_type = BodyType::Shape(body);
+    set_material(&mut body_type, material_name);
+    body_type
+}
+
+pub fn material_spheres(position: Vector3D) -> Vec<BodyType> {
+    let materials = ["rubber", "steel", "ice", "wood", "stone"];
+    let spacing = 5_000.0;
+
+    let mut objects: Vec<BodyType> = Vec::new();
+    for (i, material_name) in materials.iter().enumerate() {
+        let offset = position.add_elements(i as f64 * spacing, 0.0, 0.0);
+        let mut sphere = get_material_sphere(offset, 1_000.0, material_name);
+        sphere.physics_mut().set_velocity(0.0, -500.0, 0.0);
+        objects.push(sphere);
+    }
+    objects
+}
+
 pub fn get_grid() -> BodyType {
     // let mut grid = GridHorizontal::new(50, 50, 10_000_00.0);
     let mut grid = GridHorizontal::new(25, 25, 1_000_00.0);
@@ -25,7 +62,6 @@ pub fn get_grid() -> BodyType {
     let mut body = Shape::new(mesh, None);
     let physics = body.physics_mut();
     physics.is_stationary = true;
-    physics.set_position(position.0, position.1, position.2);
     physics.set_mass(100_000.0);
     let body_type = BodyType::Shape(body);
     body_type
@@ -40,13 +76,14 @@ pub fn get_grid_cuboid() -> BodyType {
     let mut body = Shape::new(mesh, None);
     let physics = body.physics_mut();
     physics.is_stationary = true;
-    physics.set_position(position.0, position.1, position.2);
     physics.set_mass(1_000_000_000.0);
     let body_type = BodyType::Shape(body);
     body_type
 }
 
-pub fn get_obj(file_path: &str, decomp_tolerance: f64) -> BodyType {
+/// The model with its convex decomposition, and the concavity error the
+/// decomposition was left with.
+pub fn get_obj(file_path: &str, concavity: f64) -> (BodyType, f64) {
     let mut obj = OBJModelFormat::new(file_path, 200.0);
     let position = (2000.0, -500_000.0, 500_000.0);
     obj.set_offset(position.0, position.1, position.2);
@@ -57,18 +94,19 @@ pub fn get_obj(file_path: &str, decomp_tolerance: f64) -> BodyType {
 
     let mut mesh = obj.get_polygons();
     mesh.set_uniform_color(RGBA::from_rgb(1.0, 0.4, 0.4));
-    let meshes = MeshDecompose::new(decomp_tolerance).decompose(&mesh);
+    let cache_path = format!("{}.hulls", file_path);
+    let decomposition = MeshDecompose::new(concavity).get_cached_decomposition(&mesh, &cache_path);
+    let concavity_error: f64 = decomposition.concavity;
+    let meshes = decomposition.hulls;
 
     let mut body = Shape::new(mesh, Some(meshes));
     let physics = body.physics_mut();
     // physics.is_stationary = true;
     body.physics_mut().set_mass(100_000_000_000.0);
-    body.physics_mut()
-        .set_position(position.0, position.1, position.2);
     body.physics_mut().set_velocity(2500.0, 1.0, 500.0);
     let body_type = BodyType::Shape(body);
 
-    body_type
+    (body_type, concavity_error)
 }
 
 pub fn get_sphere() -> BodyType {
@@ -0,0 +1,123 @@
+mod abstracts;
+mod components;
+mod configurations;
+mod debug;
+
+pub mod scene {
+    pub use crate::components::headless::BodyState;
+    pub use crate::components::headless::HeadlessRunner;
+    pub use crate::components::simulation::Simulation;
+    pub use crate::configurations::body_configurations;
+}
+
+pub mod bodies {
+    pub use crate::abstracts::body::Body;
+    pub use crate::abstracts::body::BodyType;
+    pub use crate::components::physics::Physics;
+    pub use crate::components::shape::Shape;
+}
+
+pub mod physics {
+    pub use crate::components::accretion::AccretionModel;
+    pub use crate::components::accretion::CollisionOutcome;
+    pub use crate::components::accretion::CollisionPolicy;
+    pub use crate::components::broad_phase::AllPairs;
+    pub use crate::components::broad_phase::BroadPhase;
+    pub use crate::components::broad_phase::BroadPhaseType;
+    pub use crate::components::broad_phase::SpatialHash;
+    pub use crate::components::broad_phase::SweepAndPrune;
+    pub use crate::components::broad_phase::AABB;
+    pub use crate::components::bvh::NarrowPhase;
+    pub use crate::components::ccd::ContinuousCollision;
+    pub use crate::components::ccd::SweptVolume;
+    pub use crate::components::collision_shape::CollisionShape;
+    pub use crate::components::contact::ContactManifold;
+    pub use crate::components::contact::ContactPoint;
+    pub use crate::components::contact_solver::ContactSolver;
+    pub use crate::components::diagnostics::ConservationDiagnostics;
+    pub use crate::components::diagnostics::ConservationDrift;
+    pub use crate::components::diagnostics::ConservationSnapshot;
+    pub use crate::components::forces::BodyFilter;
+    pub use crate::components::forces::BuoyancyVolume;
+    pub use crate::components::forces::Drag;
+    pub use crate::components::forces::ForceGenerator;
+    pub use crate::components::forces::ForceGeneratorType;
+    pub use crate::components::forces::ForceRegistry;
+    pub use crate::components::forces::HookeSpring;
+    pub use crate::components::forces::RadialField;
+    pub use crate::components::forces::UniformGravity;
+    pub use crate::components::forces::Wind;
+    pub use crate::components::gravity::Gravity;
+    pub use crate::components::gravity::GravitySolver;
+    pub use crate::components::integrator::AccelerationFn;
+    pub use crate::components::integrator::Integrator;
+    pub use crate::components::integrator::IntegratorType;
+    pub use crate::components::integrator::LinearState;
+    pub use crate::components::integrator::RungeKutta4;
+    pub use crate::components::integrator::SemiImplicitEuler;
+    pub use crate::components::integrator::VelocityVerlet;
+    pub use crate::components::joints::BallSocketJoint;
+    pub use crate::components::joints::Constraint;
+    pub use crate::components::joints::DistanceJoint;
+    pub use crate::components::joints::FixedJoint;
+    pub use crate::components::joints::HingeJoint;
+    pub use crate::components::joints::Joint;
+    pub use crate::components::joints::JointSolver;
+    pub use crate::components::joints::JointType;
+    pub use crate::components::joints::SpringJoint;
+    pub use crate::components::material::CombineRule;
+    pub use crate::components::material::PhysicsMaterial;
+    pub use crate::components::sleep::get_islands;
+    pub use crate::components::sleep::SleepManager;
+    pub use crate::components::timestep::AdaptiveTimestep;
+    pub use crate::components::timestep::TimestepCriterion;
+    pub use crate::components::timestep::TimestepMode;
+}
+
+pub mod meshes {
+    pub use crate::components::bvh::BVHNode;
+    pub use crate::components::bvh::RayHit;
+    pub use crate::components::convex_hull::ChansHull;
+    pub use crate::components::convex_hull::QuickHull;
+    pub use crate::components::decomposition::ConvexDecomposition;
+    pub use crate::components::decomposition::MeshDecompose;
+    pub use crate::components::polygons::Mesh;
+    pub use crate::components::polygons::Polygon;
+    pub use crate::components::polygons::Quad;
+    pub use crate::components::polygons::Triangle;
+    pub use crate::components::vertices::Cuboid;
+    pub use crate::components::vertices::GridHorizontal;
+    pub use crate::components::vertices::MeshConverter;
+    pub use crate::components::vertices::Sphere;
+}
+
+pub mod math {
+    pub use crate::components::vectors::Matrix3;
+    pub use crate::components::transform::Transform;
+    pub use crate::components::vectors::Quaternion;
+    pub use crate::components::vectors::Vector3D;
+}
+
+pub mod camera {
+    pub use crate::components::camera::Camera;
+    pub use crate::components::frustum::Frustum;
+    pub use crate::components::frustum::Plane;
+}
+
+pub mod renderer {
+    pub use crate::components::color::RGBA;
+    pub use crate::components::draw_call::DrawCall;
+    pub use crate::components::font::ArialFont;
+    pub use crate::components::font::FontSettings;
+    pub use crate::components::font::FontType;
+    pub use crate::components::frametime::FrameTimeHandler;
+    pub use crate::components::graphics::CursorGrab;
+    pub use crate::components::graphics::Graphics;
+    pub use crate::components::shaders::Light;
+    pub use crate::components::shaders::Shaders;
+    pub use crate::components::text_writer::TextWriter;
+}
+
+pub mod loaders {
+    pub use crate::components::model::OBJModelFormat;
+}
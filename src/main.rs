@@ -1,9 +1,4 @@
 // #![windows_subsystem = "windows"]
-mod abstracts;
-mod components;
-mod configurations;
-mod debug;
-
 use speedy2d::dimen::Vec2;
 use speedy2d::window::KeyScancode;
 
@@ -17,17 +12,36 @@ use speedy2d::Graphics2D;
 use speedy2d::Window;
 
 use std::collections::HashSet;
-
-use crate::components::camera::Camera;
-use crate::components::draw_call::DrawCall;
-use crate::components::graphics::Graphics;
-use crate::components::simulation::Simulation;
+use std::env;
+
+use rust_g_engine::camera::Camera;
+use rust_g_engine::physics::BodyFilter;
+use rust_g_engine::physics::BroadPhaseType;
+use rust_g_engine::physics::CollisionPolicy;
+use rust_g_engine::physics::Drag;
+use rust_g_engine::physics::ForceGeneratorType;
+use rust_g_engine::physics::NarrowPhase;
+use rust_g_engine::physics::TimestepCriterion;
+use rust_g_engine::physics::TimestepMode;
+use rust_g_engine::physics::UniformGravity;
+use rust_g_engine::physics::GravitySolver;
+use rust_g_engine::physics::IntegratorType;
+use rust_g_engine::renderer::DrawCall;
+use rust_g_engine::renderer::Graphics;
+use rust_g_engine::scene::HeadlessRunner;
+use rust_g_engine::scene::Simulation;
 
 fn main() {
     let width: u32 = 1760;
     let height: u32 = 960;
     let resolution: (u32, u32) = (width, height);
 
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|arg| arg == "--headless") {
+        run_headless(&args, resolution);
+        return;
+    }
+
     let window: Window = Window::new_centered("G-Engine", resolution).unwrap();
     let mut camera: Camera = Camera::new(width, height);
     camera.calibrate();
@@ -42,6 +56,164 @@ fn main() {
     window.run_loop(handler);
 }
 
+fn get_arg_string<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    let idx: usize = args.iter().position(|arg| arg == name)?;
+    args.get(idx + 1)
+}
+
+fn get_arg_value(args: &[String], name: &str) -> Option<f64> {
+    let value: &String = get_arg_string(args, name)?;
+    match value.parse::<f64>() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            println!("Invalid value for {}: {:?}", name, value);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn get_positive_arg_value(args: &[String], name: &str) -> Option<f64> {
+    let value: f64 = get_arg_value(args, name)?;
+    if !value.is_finite() || value <= 0.0 {
+        let text: &String = get_arg_string(args, name)?;
+        println!("Invalid value for {}: {:?}", name, text);
+        std::process::exit(1);
+    }
+    Some(value)
+}
+
+fn run_headless(args: &[String], resolution: (u32, u32)) {
+    let (width, height): (u32, u32) = resolution;
+    let camera: Camera = Camera::new(width, height);
+
+    let mut simulation: Simulation = Simulation::new_headless(camera);
+    if let Some(timestep_hz) = get_positive_arg_value(args, "--timestep-hz") {
+        simulation.set_timestep_hz(timestep_hz);
+    }
+    if let Some(name) = get_arg_string(args, "--gravity") {
+        match GravitySolver::from_name(name) {
+            Some(solver) => simulation.gravity.set_solver(solver),
+            None => {
+                println!("Unknown gravity solver: {:?}", name);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(name) = get_arg_string(args, "--integrator") {
+        match IntegratorType::from_name(name) {
+            Some(integrator) => simulation.integrator = integrator,
+            None => {
+                println!("Unknown integrator: {:?}", name);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(name) = get_arg_string(args, "--timestep-mode") {
+        match TimestepMode::from_name(name) {
+            Some(mode) => simulation.adaptive_timestep.set_mode(mode),
+            None => {
+                println!("Unknown timestep mode: {:?}", name);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(name) = get_arg_string(args, "--timestep-criterion") {
+        match TimestepCriterion::from_name(name) {
+            Some(criterion) => simulation.adaptive_timestep.set_criterion(criterion),
+            None => {
+                println!("Unknown timestep criterion: {:?}", name);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(tolerance) = get_arg_value(args, "--timestep-tolerance") {
+        simulation.adaptive_timestep.set_tolerance(tolerance);
+    }
+    if let Some(max_level) = get_arg_value(args, "--max-timestep-level") {
+        simulation.adaptive_timestep.set_max_level(max_level as u32);
+    }
+    if let Some(name) = get_arg_string(args, "--broad-phase") {
+        match BroadPhaseType::from_name(name) {
+            Some(broad_phase) => simulation.broad_phase = broad_phase,
+            None => {
+                println!("Unknown broad phase: {:?}", name);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(name) = get_arg_string(args, "--narrow-phase") {
+        match NarrowPhase::from_name(name) {
+            Some(narrow_phase) => simulation.narrow_phase = narrow_phase,
+            None => {
+                println!("Unknown narrow phase: {:?}", name);
+                std::process::exit(1);
+            }
+        }
+    }
+    if args.iter().any(|arg| arg == "--no-ccd") {
+        simulation.continuous_collision.enabled = false;
+    }
+    if let Some(threshold) = get_arg_value(args, "--ccd-threshold") {
+        simulation.continuous_collision.set_threshold(threshold);
+    }
+    if args.iter().any(|arg| arg == "--no-sleep") {
+        simulation.sleep.enabled = false;
+    }
+    if let Some(iterations) = get_arg_value(args, "--contact-iterations") {
+        simulation.contact_solver.set_iterations(iterations as usize);
+    }
+    if let Some(bias_factor) = get_arg_value(args, "--contact-bias") {
+        simulation.contact_solver.set_bias_factor(bias_factor);
+    }
+    if let Some(iterations) = get_arg_value(args, "--joint-iterations") {
+        simulation.joint_solver.set_iterations(iterations as usize);
+    }
+    if args.iter().any(|arg| arg == "--uniform-gravity") {
+        let gravity: ForceGeneratorType = ForceGeneratorType::UniformGravity(UniformGravity::new());
+        simulation.forces.add(gravity, BodyFilter::All);
+    }
+    if let Some(linear) = get_arg_value(args, "--drag") {
+        let drag: ForceGeneratorType = ForceGeneratorType::Drag(Drag::new(linear, 0.0));
+        simulation.forces.add(drag, BodyFilter::All);
+    }
+    if let Some(theta) = get_arg_value(args, "--theta") {
+        simulation.gravity.set_theta(theta);
+    }
+    if let Some(softening) = get_arg_value(args, "--softening") {
+        simulation.gravity.set_softening(softening);
+    }
+    simulation.setup_objects();
+
+    // The scene picks its own collision policy, the flag overrides it.
+    if let Some(name) = get_arg_string(args, "--collision-policy") {
+        match CollisionPolicy::from_name(name) {
+            Some(policy) => simulation.collision_policy = policy,
+            None => {
+                println!("Unknown collision policy: {:?}", name);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut runner: HeadlessRunner = HeadlessRunner::new(simulation);
+    if let Some(sim_time) = get_positive_arg_value(args, "--time") {
+        runner.run_for(sim_time);
+    } else {
+        let steps: f64 = get_arg_value(args, "--steps").unwrap_or(1000.0);
+        runner.run_steps(steps as usize);
+    }
+    runner.report();
+
+    if args.iter().any(|arg| arg == "--compare-gravity") {
+        let simulation: &Simulation = &runner.simulation;
+        let (max_error, mean_error) = simulation.gravity.compare_solvers(&simulation.objects);
+        println!(
+            "Barnes-Hut error vs pairwise:  max {:.6}, mean {:.6}",
+            max_error, mean_error
+        );
+    }
+}
+
 pub struct Handler {
     draw_call: DrawCall,
     keycodes: HashSet<VirtualKeyCode>,
@@ -80,6 +252,14 @@ impl Handler {
             self.draw_call.simulation.increment_timestep(-1);
         }
 
+        if Self::check_keycode(keys, VirtualKeyCode::Equals) {
+            self.draw_call.simulation.increment_real_time_factor(1);
+        }
+
+        if Self::check_keycode(keys, VirtualKeyCode::Minus) {
+            self.draw_call.simulation.increment_real_time_factor(-1);
+        }
+
         let step_val: f64 = 50_000.0;
         let camera: &mut Camera = &mut self.draw_call.simulation.camera;
 
@@ -123,6 +303,26 @@ impl Handler {
     }
 }
 
+impl Handler {
+    pub fn address_keycode_press(&mut self, key: VirtualKeyCode) {
+        match key {
+            VirtualKeyCode::G => self.draw_call.simulation.cycle_gravity_solver(),
+            VirtualKeyCode::B => self.draw_call.simulation.cycle_broad_phase(),
+            VirtualKeyCode::N => self.draw_call.simulation.cycle_narrow_phase(),
+            VirtualKeyCode::I => self.draw_call.simulation.cycle_integrator(),
+            VirtualKeyCode::T => self.draw_call.simulation.cycle_timestep_mode(),
+            VirtualKeyCode::C => self.draw_call.simulation.toggle_continuous_collision(),
+            VirtualKeyCode::Z => self.draw_call.simulation.toggle_sleeping(),
+            VirtualKeyCode::X => self.draw_call.simulation.toggle_sleep_tint(),
+            VirtualKeyCode::J => self.draw_call.simulation.toggle_draw_joints(),
+            VirtualKeyCode::K => self.draw_call.simulation.cycle_collision_policy(),
+            VirtualKeyCode::F => self.draw_call.simulation.toggle_force_generators(),
+            VirtualKeyCode::E => self.draw_call.simulation.toggle_diagnostics(),
+            _ => (),
+        }
+    }
+}
+
 impl WindowHandler for Handler {
     fn on_start(
         &mut self,
@@ -142,12 +342,16 @@ impl WindowHandler for Handler {
 
     fn on_draw(&mut self, helper: &mut WindowHelper, graphics_2d: &mut Graphics2D) {
         self.address_keycodes();
-        let fps: f64 = self.draw_call.frame_timing.get_frames_per_second();
+        let frame_timing = &self.draw_call.frame_timing;
+        let fps: f64 = frame_timing.get_frames_per_second();
+        let frame_time: f64 = frame_timing.get_last_frame_time().as_secs_f64();
         let graphics: &mut Graphics = &mut self.draw_call.graphics;
         graphics.execute_helper_functions(helper);
         graphics.execute_buffer(graphics_2d);
         graphics.clear_screen();
-        self.draw_call.simulation.simulate(graphics, fps);
+        self.draw_call
+            .simulation
+            .simulate(graphics, fps, frame_time);
         self.draw_call.draw();
         self.draw_call.frame_timing.tick();
         helper.request_redraw();
@@ -186,6 +390,9 @@ impl WindowHandler for Handler {
         _scancode: KeyScancode,
     ) {
         if let Some(keycode) = virtual_key_code {
+            if !self.keycodes.contains(&keycode) {
+                self.address_keycode_press(keycode);
+            }
             self.add_keycode(keycode);
         }
     }
@@ -0,0 +1,146 @@
+mod common;
+
+use common::get_headless_simulation;
+use common::sphere;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::AccretionModel;
+use rust_g_engine::physics::BodyFilter;
+use rust_g_engine::physics::CollisionPolicy;
+use rust_g_engine::physics::ConservationDrift;
+use rust_g_engine::physics::ConservationSnapshot;
+use rust_g_engine::physics::DistanceJoint;
+use rust_g_engine::physics::Drag;
+use rust_g_engine::physics::ForceGeneratorType;
+use rust_g_engine::physics::HookeSpring;
+use rust_g_engine::physics::Joint;
+use rust_g_engine::physics::JointType;
+use rust_g_engine::scene::Simulation;
+
+// Every impact merges, or every impact fast enough shatters the smaller body.
+fn get_simulation(is_merging: bool) -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(60.0);
+    simulation.diagnostics.enabled = true;
+
+    let mut model: AccretionModel = if is_merging {
+        AccretionModel::new(f64::MAX, 0.1)
+    } else {
+        AccretionModel::new(1.0, 0.1)
+    };
+    model.merge_factor = if is_merging { 1e9 } else { 0.0 };
+    model.set_fragment_count(4);
+    simulation.collision_policy = CollisionPolicy::Accretion(model);
+    simulation
+}
+
+// Two spinning bodies meeting off center, so the impact has an orbital
+// angular momentum as well as the spins.
+fn add_impact(simulation: &mut Simulation) {
+    let left: Vector3D = Vector3D::new(-3.0, 0.0, 0.3);
+    let mut body: BodyType = sphere(left, 1.0, 4.0, Vector3D::new(6.0, 0.5, 0.0));
+    body.physics_mut().set_angular_velocity(0.0, 1.0, 2.0);
+    simulation.add_object(body);
+
+    let right: Vector3D = Vector3D::new(3.0, 0.4, 0.0);
+    let mut body: BodyType = sphere(right, 0.8, 2.0, Vector3D::new(-6.0, 0.0, 0.2));
+    body.physics_mut().set_angular_velocity(1.0, 0.0, 0.0);
+    simulation.add_object(body);
+}
+
+fn assert_conserved(simulation: &Simulation) {
+    let initial: ConservationSnapshot = simulation.diagnostics.initial.unwrap();
+    let current: ConservationSnapshot = simulation.diagnostics.current.unwrap();
+    let drift: ConservationDrift = simulation.diagnostics.get_drift().unwrap();
+    assert!((current.total_mass - initial.total_mass).abs() < 1e-9);
+    assert!(
+        drift.linear_momentum < 1e-9,
+        "momentum drift {}",
+        drift.linear_momentum
+    );
+    assert!(
+        drift.angular_momentum < 1e-9,
+        "angular momentum drift {}",
+        drift.angular_momentum
+    );
+}
+
+#[test]
+fn merge_conserves_mass_and_momentum() {
+    let mut simulation: Simulation = get_simulation(true);
+    add_impact(&mut simulation);
+    for _ in 0..60 {
+        simulation.compute_objects();
+    }
+    assert_eq!(simulation.objects.len(), 1);
+    assert_conserved(&simulation);
+}
+
+#[test]
+fn shatter_conserves_mass_and_momentum() {
+    let mut simulation: Simulation = get_simulation(false);
+    add_impact(&mut simulation);
+    for _ in 0..60 {
+        simulation.compute_objects();
+    }
+    assert!(simulation.objects.len() > 2);
+    assert_conserved(&simulation);
+}
+
+#[test]
+fn merge_remaps_joints_springs_and_contacts() {
+    let mut simulation: Simulation = get_simulation(true);
+    let zero: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+    // The first two overlap and merge into the heavier second one, the
+    // third is stationary and only bounces the fourth resting against it.
+    simulation.add_object(sphere(Vector3D::new(-0.9, 0.0, 0.0), 1.0, 2.0, zero));
+    simulation.add_object(sphere(Vector3D::new(0.9, 0.0, 0.0), 1.0, 4.0, zero));
+    simulation.add_object(sphere(Vector3D::new(20.0, 0.0, 0.0), 1.0, 1.0, zero));
+    simulation.add_object(sphere(Vector3D::new(21.9, 0.0, 0.0), 1.0, 1.0, zero));
+    simulation.add_object(sphere(Vector3D::new(30.0, 0.0, 0.0), 1.0, 1.0, zero));
+    simulation.objects[2].physics_mut().is_stationary = true;
+
+    let rod = |simulation: &Simulation, a: usize, b: usize| -> Joint {
+        let anchor_a: Vector3D = simulation.objects[a].physics().get_position();
+        let anchor_b: Vector3D = simulation.objects[b].physics().get_position();
+        let length: f64 = anchor_a.get_distance(&anchor_b);
+        let joint_type: JointType = JointType::Distance(DistanceJoint::new(length));
+        Joint::new(&simulation.objects, a, b, anchor_a, anchor_b, joint_type)
+    };
+    let kept: Joint = rod(&simulation, 3, 4);
+    let dropped: Joint = rod(&simulation, 0, 4);
+    simulation.joint_solver.add_joint(kept);
+    simulation.joint_solver.add_joint(dropped);
+
+    let spring = |a: usize, b: usize| -> ForceGeneratorType {
+        ForceGeneratorType::HookeSpring(HookeSpring::new(a, b, 10.0, 0.0, 0.0))
+    };
+    simulation.forces.add(spring(2, 4), BodyFilter::All);
+    simulation.forces.add(spring(1, 2), BodyFilter::All);
+    let drag: ForceGeneratorType = ForceGeneratorType::Drag(Drag::new(0.0, 0.0));
+    simulation.forces.add(drag, BodyFilter::Only(vec![1, 2, 3]));
+
+    simulation.compute_objects();
+    assert_eq!(simulation.objects.len(), 4);
+
+    let joints: Vec<(usize, usize)> = simulation
+        .joint_solver
+        .joints
+        .iter()
+        .map(|joint| (joint.body_a, joint.body_b))
+        .collect();
+    assert_eq!(joints, vec![(2, 3)]);
+
+    assert_eq!(simulation.forces.entries.len(), 2);
+    let ForceGeneratorType::HookeSpring(spring) = &simulation.forces.entries[0].generator else {
+        panic!("the spring between kept bodies should stay first");
+    };
+    assert_eq!((spring.body_a, spring.body_b), (1, 3));
+    let BodyFilter::Only(idxs) = &simulation.forces.entries[1].filter else {
+        panic!("the drag filter should stay a list of bodies");
+    };
+    assert_eq!(idxs, &vec![1, 2]);
+
+    let pairs: Vec<(usize, usize)> = simulation.contact_manifolds.keys().copied().collect();
+    assert_eq!(pairs, vec![(1, 2)]);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::get_headless_simulation;
+use common::sphere;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::scene::Simulation;
+
+// Steps of a quarter second, so the frame times below add up exactly.
+fn get_simulation(real_time_factor: f64, max_steps_per_frame: usize) -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(4.0);
+    simulation.set_real_time_factor(real_time_factor);
+    simulation.set_max_steps_per_frame(max_steps_per_frame);
+    let zero: Vector3D = Vector3D::default(0.0);
+    simulation.add_object(sphere(zero, 1.0, 1.0, Vector3D::new(1.0, 0.0, 0.0)));
+    simulation
+}
+
+fn assert_frame(simulation: &Simulation, steps: usize, step_count: usize, accumulator: f64) {
+    assert_eq!(simulation.steps_per_frame, steps);
+    assert_eq!(simulation.step_count, step_count);
+    assert!(
+        (simulation.get_accumulator() - accumulator).abs() < 1e-12,
+        "expected {}, got {}",
+        accumulator,
+        simulation.get_accumulator()
+    );
+}
+
+#[test]
+fn frame_time_is_scaled_into_steps() {
+    let mut simulation: Simulation = get_simulation(2.0, 10);
+    simulation.advance(0.375);
+    assert_frame(&simulation, 3, 3, 0.0);
+    assert!((simulation.sim_time - 0.75).abs() < 1e-12);
+
+    let mut slow: Simulation = get_simulation(0.5, 10);
+    slow.advance(0.375);
+    assert_frame(&slow, 0, 0, 0.1875);
+    assert_eq!(slow.sim_time, 0.0);
+}
+
+#[test]
+fn leftover_time_carries_to_the_next_frame() {
+    let mut simulation: Simulation = get_simulation(1.0, 10);
+    simulation.advance(0.375);
+    assert_frame(&simulation, 1, 1, 0.125);
+    simulation.advance(0.375);
+    assert_frame(&simulation, 2, 3, 0.0);
+    simulation.advance(0.125);
+    assert_frame(&simulation, 0, 3, 0.125);
+    simulation.advance(0.0);
+    assert_frame(&simulation, 0, 3, 0.125);
+    simulation.advance(0.125);
+    assert_frame(&simulation, 1, 4, 0.0);
+    assert!((simulation.sim_time - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn steps_are_clamped_per_frame() {
+    let mut simulation: Simulation = get_simulation(1.0, 3);
+    simulation.advance(5.0);
+    // Only one step of the backlog is kept for the next frame.
+    assert_frame(&simulation, 3, 3, 0.25);
+    simulation.advance(0.0);
+    assert_frame(&simulation, 1, 4, 0.0);
+
+    // A remainder shorter than a step is kept even when the cap is hit.
+    simulation.advance(0.875);
+    assert_frame(&simulation, 3, 7, 0.125);
+}
@@ -0,0 +1,94 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use rust_g_engine::physics::AllPairs;
+use rust_g_engine::physics::BroadPhase;
+use rust_g_engine::physics::SpatialHash;
+use rust_g_engine::physics::SweepAndPrune;
+use rust_g_engine::physics::AABB;
+
+fn overlaps(a: &AABB, b: &AABB) -> bool {
+    (0..3).all(|i| a.0[i] <= b.1[i] && b.0[i] <= a.1[i])
+}
+
+// The reference answer is every pair of all pairs whose boxes overlap.
+fn get_overlapping_pairs(aabbs: &[AABB]) -> Vec<(usize, usize)> {
+    let mut pairs: Vec<(usize, usize)> = AllPairs::new()
+        .get_candidate_pairs(aabbs)
+        .into_iter()
+        .filter(|&(a, b)| overlaps(&aabbs[a], &aabbs[b]))
+        .collect();
+    pairs.sort();
+    pairs
+}
+
+fn get_random_scene(rng: &mut StdRng, count: usize) -> Vec<AABB> {
+    let mut aabbs: Vec<AABB> = Vec::new();
+    for idx in 0..count {
+        let min: [f64; 3] = [
+            rng.gen_range(-100.0..100.0),
+            rng.gen_range(-100.0..100.0),
+            rng.gen_range(-100.0..100.0),
+        ];
+        // A few large bodies end up in the oversized list of the hash.
+        let size: f64 = if idx % 25 == 0 {
+            rng.gen_range(50.0..150.0)
+        } else {
+            rng.gen_range(0.5..12.0)
+        };
+        let max: [f64; 3] = [
+            min[0] + size * rng.gen_range(0.2..1.0),
+            min[1] + size * rng.gen_range(0.2..1.0),
+            min[2] + size * rng.gen_range(0.2..1.0),
+        ];
+        aabbs.push((min, max));
+    }
+    aabbs
+}
+
+#[test]
+fn broad_phases_match_all_pairs_on_random_scenes() {
+    let mut rng: StdRng = StdRng::seed_from_u64(5);
+    let mut sweep: SweepAndPrune = SweepAndPrune::new();
+    let mut hash: SpatialHash = SpatialHash::new(None);
+
+    for _ in 0..5 {
+        let mut aabbs: Vec<AABB> = get_random_scene(&mut rng, 300);
+        let expected: Vec<(usize, usize)> = get_overlapping_pairs(&aabbs);
+        assert!(!expected.is_empty());
+        assert_eq!(sweep.get_candidate_pairs(&aabbs), expected);
+        assert_eq!(hash.get_candidate_pairs(&aabbs), expected);
+
+        // Small moves reuse the sorted order of the previous step.
+        for (min, max) in aabbs.iter_mut() {
+            let shift: f64 = rng.gen_range(-2.0..2.0);
+            for i in 0..3 {
+                min[i] += shift;
+                max[i] += shift;
+            }
+        }
+        let expected: Vec<(usize, usize)> = get_overlapping_pairs(&aabbs);
+        assert_eq!(sweep.get_candidate_pairs(&aabbs), expected);
+        assert_eq!(hash.get_candidate_pairs(&aabbs), expected);
+    }
+}
+
+#[test]
+fn spatial_hash_handles_point_bodies() {
+    // Most bodies have no extent, so the median size is zero.
+    let mut aabbs: Vec<AABB> = Vec::new();
+    for idx in 0..20 {
+        let point: [f64; 3] = [idx as f64 * 1e6, 0.0, -(idx as f64) * 1e6];
+        aabbs.push((point, point));
+    }
+    aabbs.push(([0.0; 3], [0.0; 3]));
+    aabbs.push(([-1e7; 3], [1e7; 3]));
+
+    let expected: Vec<(usize, usize)> = get_overlapping_pairs(&aabbs);
+    assert!(expected.contains(&(0, 20)));
+    assert_eq!(SpatialHash::new(None).get_candidate_pairs(&aabbs), expected);
+    assert_eq!(SweepAndPrune::new().get_candidate_pairs(&aabbs), expected);
+
+    let same: Vec<AABB> = vec![([3.0; 3], [3.0; 3]); 4];
+    assert_eq!(SpatialHash::new(None).get_candidate_pairs(&same).len(), 6);
+}
@@ -0,0 +1,262 @@
+mod common;
+
+use common::cuboid_mesh;
+use common::sphere_mesh;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rust_g_engine::math::Quaternion;
+use rust_g_engine::math::Transform;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::BVHNode;
+use rust_g_engine::meshes::Mesh;
+use rust_g_engine::meshes::Polygon;
+use rust_g_engine::meshes::RayHit;
+use rust_g_engine::meshes::Sphere;
+use rust_g_engine::meshes::Triangle;
+use rust_g_engine::renderer::RGBA;
+
+fn get_rebuilt(bvh: &BVHNode) -> BVHNode {
+    let mut rebuilt: BVHNode = bvh.clone();
+    rebuilt.rebuild();
+    rebuilt
+}
+
+// Rays from a ring around the center towards points scattered over the box.
+fn get_rays(center: Vector3D, radius: f64) -> Vec<(Vector3D, Vector3D)> {
+    let mut rays: Vec<(Vector3D, Vector3D)> = Vec::new();
+    for i in 0..24 {
+        let angle: f64 = i as f64 * std::f64::consts::TAU / 24.0;
+        let origin: Vector3D = center.add_vector(&Vector3D::new(
+            3.0 * radius * angle.cos(),
+            radius * (i as f64 * 0.7).sin(),
+            3.0 * radius * angle.sin(),
+        ));
+        for j in 0..8 {
+            let offset: Vector3D = Vector3D::new(
+                (j as f64 * 1.3).sin() * radius,
+                (j as f64 * 2.1).cos() * radius,
+                (j as f64 * 0.9).sin() * radius,
+            );
+            let target: Vector3D = center.add_vector(&offset);
+            rays.push((origin, target.subtract_vector(&origin).normalize()));
+        }
+    }
+    rays
+}
+
+fn assert_same_ray_hits(a: &BVHNode, b: &BVHNode, radius: f64) {
+    let mut hit_count: usize = 0;
+    for (origin, direction) in get_rays(a.get_center(), radius) {
+        let hit_a: Option<RayHit> = a.get_ray_hit(&origin, &direction, f64::INFINITY);
+        let hit_b: Option<RayHit> = b.get_ray_hit(&origin, &direction, f64::INFINITY);
+        match (hit_a, hit_b) {
+            // Rays through a shared edge may report either polygon, so only
+            // the hit itself is compared.
+            (Some(hit_a), Some(hit_b)) => {
+                hit_count += 1;
+                assert!((hit_a.distance - hit_b.distance).abs() < 1e-9);
+                assert!(hit_a.position.get_distance(&hit_b.position) < 1e-9);
+            }
+            (None, None) => {}
+            _ => panic!("only one tree was hit by the ray from {:?}", origin),
+        }
+    }
+    assert!(hit_count > 0, "no ray hit the mesh");
+}
+
+fn get_sorted_pairs(a: &BVHNode, b: &BVHNode) -> Vec<(usize, usize)> {
+    let mut pairs: Vec<(usize, usize)> = a.get_overlapping_polygons(b);
+    pairs.sort();
+    pairs
+}
+
+#[test]
+fn ray_hit_is_nearest_polygon() {
+    let mesh: Mesh = sphere_mesh(10.0, 0.0, 0.0, 0.0);
+    let origin: Vector3D = Vector3D::new(-50.0, 0.1, 0.2);
+    let hit: RayHit = mesh
+        .get_ray_hit(&origin, &Vector3D::new(1.0, 0.0, 0.0))
+        .expect("the ray points at the sphere");
+
+    // The flat faces of the tessellated sphere sit just inside the radius.
+    assert!(
+        hit.distance >= 40.0 && hit.distance < 41.0,
+        "distance {}",
+        hit.distance
+    );
+    assert!(hit.normal.x < 0.0);
+
+    let miss: Option<RayHit> = mesh.get_ray_hit(&origin, &Vector3D::new(-1.0, 0.0, 0.0));
+    assert!(miss.is_none());
+    assert!(mesh.bvh_node.get_depth() > 1);
+}
+
+#[test]
+fn refit_after_transform_matches_rebuild() {
+    let local: Mesh = sphere_mesh(10.0, 0.0, 0.0, 0.0);
+    let other: Mesh = cuboid_mesh(12.0, 42.0, -3.0, 35.0);
+    let mut world: Mesh = local.clone();
+
+    let mut transform: Transform = Transform::new(Vector3D::default(0.0), Quaternion::identity());
+    for step in 0..10 {
+        transform.rotate(&Vector3D::new(0.3, 1.0, 0.2).normalize(), 0.35);
+        transform.translate(&Vector3D::new(4.0, -0.5, 3.0));
+        world.set_transformed(&local, &transform);
+
+        let rebuilt: BVHNode = get_rebuilt(&world.bvh_node);
+        assert_eq!(
+            world.bvh_node.get_bounds(),
+            rebuilt.get_bounds(),
+            "step {}",
+            step
+        );
+        assert_same_ray_hits(&world.bvh_node, &rebuilt, 10.0);
+        assert_eq!(
+            get_sorted_pairs(&world.bvh_node, &other.bvh_node),
+            get_sorted_pairs(&rebuilt, &other.bvh_node)
+        );
+    }
+    assert!(world.bvh_node.get_refit_quality() <= world.bvh_node.rebuild_threshold);
+}
+
+#[test]
+fn translation_offset_matches_rebuild() {
+    let local: Mesh = sphere_mesh(10.0, 0.0, 0.0, 0.0);
+    let other: Mesh = sphere_mesh(8.0, 15.0, 4.0, 0.0);
+    let mut world: Mesh = local.clone();
+
+    // Only the translation changes, so the tree is moved through its offset.
+    let mut transform: Transform = Transform::new(Vector3D::default(0.0), Quaternion::identity());
+    transform.rotate(&Vector3D::new(0.0, 1.0, 0.0), 0.5);
+    for _ in 0..5 {
+        transform.translate(&Vector3D::new(1.5, 0.5, -0.5));
+        world.set_transformed(&local, &transform);
+    }
+    world.translate_polygons(&Vector3D::new(-2.0, 0.0, 1.0));
+
+    let rebuilt: BVHNode = get_rebuilt(&world.bvh_node);
+    let (min, max): ([f64; 3], [f64; 3]) = world.bvh_node.get_bounds();
+    let (rebuilt_min, rebuilt_max): ([f64; 3], [f64; 3]) = rebuilt.get_bounds();
+    for i in 0..3 {
+        assert!((min[i] - rebuilt_min[i]).abs() < 1e-9);
+        assert!((max[i] - rebuilt_max[i]).abs() < 1e-9);
+    }
+    assert_same_ray_hits(&world.bvh_node, &rebuilt, 10.0);
+
+    let pairs: Vec<(usize, usize)> = get_sorted_pairs(&world.bvh_node, &other.bvh_node);
+    assert!(!pairs.is_empty());
+    assert_eq!(pairs, get_sorted_pairs(&rebuilt, &other.bvh_node));
+    assert_eq!(
+        get_sorted_pairs(&other.bvh_node, &world.bvh_node).len(),
+        pairs.len()
+    );
+}
+
+#[test]
+fn partial_refit_matches_rebuild() {
+    let mesh: Mesh = sphere_mesh(10.0, 0.0, 0.0, 0.0);
+    let mut bvh: BVHNode = mesh.bvh_node.clone();
+
+    // Pushes a few polygons outwards like a local dent.
+    let moved: Vec<usize> = vec![3, 4, 5, 40, 41];
+    for &idx in &moved {
+        let normal: Vector3D = bvh.polygons[idx].get_normal();
+        bvh.polygons[idx].translate(&normal.multiply(2.0));
+    }
+    bvh.refit_polygons(&moved);
+
+    let rebuilt: BVHNode = get_rebuilt(&bvh);
+    assert_eq!(bvh.get_bounds(), rebuilt.get_bounds());
+    assert_same_ray_hits(&bvh, &rebuilt, 12.0);
+}
+
+#[test]
+fn degraded_refit_triggers_rebuild() {
+    let mesh: Mesh = sphere_mesh(10.0, 0.0, 0.0, 0.0);
+    let mut refitted: BVHNode = mesh.bvh_node.clone();
+    refitted.rebuild_threshold = f64::INFINITY;
+    let mut rebuilt: BVHNode = mesh.bvh_node.clone();
+
+    // Scattering every other polygon makes the old tree a poor fit.
+    for bvh in [&mut refitted, &mut rebuilt] {
+        for idx in (0..bvh.polygons.len()).step_by(2) {
+            let centroid: Vector3D = bvh.polygons[idx].get_centroid();
+            bvh.polygons[idx].translate(&centroid.multiply(-2.0));
+        }
+        bvh.refit();
+    }
+
+    assert!(refitted.get_refit_quality() > rebuilt.rebuild_threshold);
+    assert!(rebuilt.get_refit_quality() <= rebuilt.rebuild_threshold);
+    assert_same_ray_hits(&refitted, &rebuilt, 12.0);
+}
+
+fn get_average_visit_count(bvh: &BVHNode, radius: f64) -> f64 {
+    let rays: Vec<(Vector3D, Vector3D)> = get_rays(bvh.get_center(), radius);
+    let visit_count: usize = rays
+        .iter()
+        .map(|(origin, direction)| bvh.get_ray_visit_count(origin, direction, f64::INFINITY))
+        .sum();
+    visit_count as f64 / rays.len() as f64
+}
+
+// The polygon count grows 64 times over while the nodes visited per ray
+// stay within a small multiple of its logarithm.
+#[test]
+fn ray_visits_grow_logarithmically() {
+    let mut visit_counts: Vec<f64> = Vec::new();
+    for resolution in [8, 16, 32, 64] {
+        let mesh: Mesh = Sphere::new(10.0, resolution, resolution).get_triangle_mesh();
+        let bvh: &BVHNode = &mesh.bvh_node;
+        let visit_count: f64 = get_average_visit_count(bvh, 10.0);
+        let log_count: f64 = (bvh.polygons.len() as f64).log2();
+        assert!(
+            visit_count < 2.0 * log_count,
+            "{} visits for {} polygons",
+            visit_count,
+            bvh.polygons.len()
+        );
+        visit_counts.push(visit_count);
+    }
+    assert!(visit_counts[3] < 2.0 * visit_counts[0]);
+}
+
+// Long thin triangles along x whose centroids lie close together, so no
+// split pays off and every branch falls back to the median.
+fn get_overlapping_triangles(count: usize, rng: &mut StdRng) -> Vec<Polygon> {
+    let white: RGBA = RGBA::new(1.0, 1.0, 1.0, 1.0);
+    let mut centers: Vec<f64> = (0..count).map(|idx| idx as f64 / count as f64).collect();
+    centers.shuffle(rng);
+    centers
+        .into_iter()
+        .map(|x| {
+            let vertices: [Vector3D; 3] = [
+                Vector3D::new(x - 50.0, 0.0, 0.0),
+                Vector3D::new(x + 50.0, 0.0, 0.0),
+                Vector3D::new(x, 1.0, 0.0),
+            ];
+            Polygon::Triangle(Triangle::new(vertices, (0, 1, 2), white, white))
+        })
+        .collect()
+}
+
+#[test]
+fn median_split_keeps_nearby_polygons_together() {
+    let mut rng: StdRng = StdRng::seed_from_u64(7);
+    let polygons: Vec<Polygon> = get_overlapping_triangles(64, &mut rng);
+    let bvh: BVHNode = BVHNode::new(&polygons, &[]);
+    assert_eq!(bvh.get_node_count(), 31);
+    assert_eq!(bvh.get_depth(), 5);
+
+    // Only the last few triangles reach this far, and sorted halves lead
+    // the ray down to their leaf and at most one neighbour.
+    let origin: Vector3D = Vector3D::new(50.9, 0.001, -5.0);
+    let direction: Vector3D = Vector3D::new(0.0, 0.0, 1.0);
+    let hit: RayHit = bvh
+        .get_ray_hit(&origin, &direction, f64::INFINITY)
+        .expect("the ray crosses the last triangles");
+    assert!((hit.distance - 5.0).abs() < 1e-9);
+    let visit_count: usize = bvh.get_ray_visit_count(&origin, &direction, f64::INFINITY);
+    assert!(visit_count <= bvh.get_depth() + 1, "{} visits", visit_count);
+}
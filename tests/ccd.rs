@@ -0,0 +1,48 @@
+mod common;
+
+use common::cuboid;
+use common::get_headless_simulation;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::scene::body_configurations;
+use rust_g_engine::scene::Simulation;
+
+// A small sphere fired at a thin wall moves six wall thicknesses per step.
+fn get_wall_simulation(continuous_collision: bool) -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(60.0);
+    simulation.continuous_collision.enabled = continuous_collision;
+
+    let min: Vector3D = Vector3D::new(-20.0, -20.0, 0.0);
+    simulation.add_object(cuboid(min, Vector3D::new(40.0, 40.0, 1.0), true));
+
+    let start: Vector3D = Vector3D::new(0.0, 0.0, -20.0);
+    let mut bullet: BodyType = body_configurations::get_material_sphere(start, 1.0, "steel");
+    bullet.physics_mut().set_velocity(0.0, 0.0, 360.0);
+    simulation.add_object(bullet);
+    simulation
+}
+
+fn run(simulation: &mut Simulation) -> f64 {
+    for _ in 0..30 {
+        simulation.compute_objects();
+    }
+    simulation.objects[1].physics().get_position().z
+}
+
+#[test]
+fn fast_bodies_tunnel_without_continuous_collision() {
+    let mut simulation: Simulation = get_wall_simulation(false);
+    let z: f64 = run(&mut simulation);
+    assert!(z > 100.0, "the bullet should have passed the wall: {}", z);
+}
+
+#[test]
+fn continuous_collision_stops_fast_bodies() {
+    let mut simulation: Simulation = get_wall_simulation(true);
+    let z: f64 = run(&mut simulation);
+    assert!(z < 0.0, "the bullet went through the wall: {}", z);
+    assert!(simulation.objects[1].physics().velocity.z <= 0.0);
+    let wall_z: f64 = simulation.objects[0].physics().get_position().z;
+    assert!((wall_z - 0.5).abs() < 1e-9, "the wall moved to {}", wall_z);
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use common::assert_vector;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::Shape;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::Cuboid;
+use rust_g_engine::meshes::Mesh;
+use rust_g_engine::meshes::Sphere;
+use rust_g_engine::physics::CollisionShape;
+use rust_g_engine::physics::ContactManifold;
+
+fn sphere(x: f64, y: f64, z: f64, radius: f64) -> CollisionShape {
+    CollisionShape::Sphere {
+        center: Vector3D::new(x, y, z),
+        radius,
+    }
+}
+
+fn cube(x: f64, y: f64, z: f64, size: f64) -> CollisionShape {
+    let min: Vector3D = Vector3D::new(x, y, z);
+    CollisionShape::from_bounds(min, min.add(size))
+}
+
+fn get_box_corners(x: f64, y: f64, z: f64, size: f64) -> Vec<Vector3D> {
+    let mut corners: Vec<Vector3D> = Vec::new();
+    for i in 0..8 {
+        let corner: [f64; 3] = [(i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64];
+        corners
+            .push(Vector3D::new(x, y, z).add_vector(&Vector3D::from_array(corner).multiply(size)));
+    }
+    corners
+}
+
+fn get_contact(a: &CollisionShape, b: &CollisionShape) -> ContactManifold {
+    let manifold: ContactManifold = a.get_contact(b).expect("the shapes should touch");
+    let flipped: ContactManifold = b.get_contact(a).expect("the test should be symmetric");
+    assert_vector(flipped.normal, manifold.normal.negate(), 1e-9);
+    assert!((flipped.get_max_depth() - manifold.get_max_depth()).abs() < 1e-9);
+    manifold
+}
+
+#[test]
+fn round_shapes_touch_along_the_center_line() {
+    let a: CollisionShape = sphere(0.0, 0.0, 0.0, 2.0);
+    let manifold: ContactManifold = get_contact(&a, &sphere(3.0, 0.0, 0.0, 2.0));
+    assert_vector(manifold.normal, Vector3D::new(1.0, 0.0, 0.0), 1e-12);
+    assert!((manifold.get_max_depth() - 1.0).abs() < 1e-12);
+    assert_vector(
+        manifold.points[0].position,
+        Vector3D::new(1.5, 0.0, 0.0),
+        1e-12,
+    );
+    assert!(a.get_contact(&sphere(4.5, 0.0, 0.0, 2.0)).is_none());
+
+    let capsule: CollisionShape = CollisionShape::Capsule {
+        start: Vector3D::new(-5.0, 2.5, 0.0),
+        end: Vector3D::new(5.0, 2.5, 0.0),
+        radius: 1.0,
+    };
+    let manifold: ContactManifold = get_contact(&a, &capsule);
+    assert_vector(manifold.normal, Vector3D::new(0.0, 1.0, 0.0), 1e-12);
+    assert!((manifold.get_max_depth() - 0.5).abs() < 1e-12);
+
+    // Crossing capsules meet where their segments pass closest.
+    let crossing: CollisionShape = CollisionShape::Capsule {
+        start: Vector3D::new(1.0, 4.0, -5.0),
+        end: Vector3D::new(1.0, 4.0, 5.0),
+        radius: 1.0,
+    };
+    let manifold: ContactManifold = get_contact(&capsule, &crossing);
+    assert_vector(manifold.normal, Vector3D::new(0.0, 1.0, 0.0), 1e-12);
+    assert!((manifold.get_max_depth() - 0.5).abs() < 1e-12);
+    assert_vector(manifold.get_center(), Vector3D::new(1.0, 3.25, 0.0), 1e-12);
+}
+
+#[test]
+fn sphere_box_uses_the_closest_feature() {
+    let block: CollisionShape = cube(0.0, 0.0, 0.0, 10.0);
+
+    let face: ContactManifold = get_contact(&sphere(5.0, 12.0, 5.0, 3.0), &block);
+    assert_vector(face.normal, Vector3D::new(0.0, -1.0, 0.0), 1e-12);
+    assert!((face.get_max_depth() - 1.0).abs() < 1e-12);
+
+    let corner: ContactManifold = get_contact(&sphere(11.0, 11.0, 11.0, 2.0), &block);
+    assert_vector(
+        corner.normal,
+        Vector3D::new(-1.0, -1.0, -1.0).normalize(),
+        1e-12,
+    );
+    assert!((corner.get_max_depth() - (2.0 - 3.0_f64.sqrt())).abs() < 1e-12);
+    assert!(sphere(12.0, 12.0, 12.0, 2.0).get_contact(&block).is_none());
+
+    // A center inside the box leaves through the nearest face.
+    let inside: ContactManifold = get_contact(&sphere(9.0, 5.0, 4.0, 1.0), &block);
+    assert_vector(inside.normal, Vector3D::new(-1.0, 0.0, 0.0), 1e-12);
+    assert!((inside.get_max_depth() - 2.0).abs() < 1e-12);
+}
+
+#[test]
+fn stacked_boxes_touch_on_the_whole_face() {
+    let lower: CollisionShape = cube(0.0, 0.0, 0.0, 10.0);
+    let upper: CollisionShape = cube(2.0, 9.5, 1.0, 6.0);
+    let manifold: ContactManifold = get_contact(&lower, &upper);
+
+    assert_vector(manifold.normal, Vector3D::new(0.0, 1.0, 0.0), 1e-12);
+    assert_eq!(manifold.points.len(), 4);
+    for point in &manifold.points {
+        assert!((point.depth - 0.5).abs() < 1e-9, "depth {}", point.depth);
+        assert!((2.0..=8.0).contains(&point.position.x));
+        assert!((1.0..=7.0).contains(&point.position.z));
+    }
+
+    // A box turned on its vertical axis still rests on its face.
+    let mut turned: CollisionShape = cube(-3.0, 9.0, -3.0, 6.0);
+    turned.rotate(&Vector3D::new(0.0, 1.0, 0.0), 0.3);
+    turned.translate(&Vector3D::new(5.0, 0.0, 5.0));
+    let manifold: ContactManifold = get_contact(&lower, &turned);
+    assert_vector(manifold.normal, Vector3D::new(0.0, 1.0, 0.0), 1e-9);
+    assert!((manifold.get_max_depth() - 1.0).abs() < 1e-9);
+    assert!(lower.get_contact(&cube(0.0, 10.5, 0.0, 6.0)).is_none());
+}
+
+#[test]
+fn planes_hold_every_shape() {
+    let ground: CollisionShape =
+        CollisionShape::from_plane(Vector3D::new(0.0, 1.0, 0.0), Vector3D::new(0.0, 0.0, 0.0));
+
+    let block: ContactManifold = get_contact(&ground, &cube(-1.0, -0.25, -1.0, 2.0));
+    assert_vector(block.normal, Vector3D::new(0.0, 1.0, 0.0), 1e-12);
+    assert_eq!(block.points.len(), 4);
+    assert!((block.get_max_depth() - 0.25).abs() < 1e-12);
+
+    let capsule: CollisionShape = CollisionShape::Capsule {
+        start: Vector3D::new(-3.0, 0.5, 0.0),
+        end: Vector3D::new(3.0, 0.5, 0.0),
+        radius: 1.0,
+    };
+    let lying: ContactManifold = get_contact(&ground, &capsule);
+    assert_eq!(lying.points.len(), 2);
+    assert!((lying.get_max_depth() - 0.5).abs() < 1e-12);
+
+    let hull: CollisionShape = CollisionShape::ConvexHull {
+        vertices: get_box_corners(0.0, -0.5, 0.0, 1.0),
+    };
+    assert_eq!(get_contact(&ground, &hull).points.len(), 4);
+    assert!(ground.get_contact(&sphere(0.0, 1.5, 0.0, 1.0)).is_none());
+    assert!(ground.get_contact(&ground).is_none());
+}
+
+#[test]
+fn convex_hull_of_a_box_matches_the_box() {
+    let block: CollisionShape = cube(0.0, 0.0, 0.0, 10.0);
+    let hull: CollisionShape = CollisionShape::ConvexHull {
+        vertices: get_box_corners(0.0, 0.0, 0.0, 10.0),
+    };
+
+    for other in [sphere(5.0, 12.0, 5.0, 3.0), sphere(11.0, 11.0, 11.0, 2.0)] {
+        let expected: ContactManifold = get_contact(&block, &other);
+        let actual: ContactManifold = get_contact(&hull, &other);
+        assert_vector(actual.normal, expected.normal, 1e-6);
+        assert!((actual.get_max_depth() - expected.get_max_depth()).abs() < 1e-6);
+    }
+
+    let upper: CollisionShape = cube(2.0, 9.0, 2.0, 4.0);
+    let expected: ContactManifold = get_contact(&block, &upper);
+    let actual: ContactManifold = get_contact(&hull, &upper);
+    assert_vector(actual.normal, expected.normal, 1e-6);
+    assert!((actual.get_max_depth() - expected.get_max_depth()).abs() < 1e-6);
+    assert_eq!(actual.points.len(), expected.points.len());
+}
+
+#[test]
+fn primitive_bodies_get_analytic_shapes() {
+    let mut ball: Sphere = Sphere::new(4.0, 10, 10);
+    ball.set_offset(10.0, 0.0, 0.0);
+    let mut crate_box: Cuboid = Cuboid::new(2.0, 4.0, 6.0);
+    crate_box.set_offset(-20.0, 0.0, 0.0);
+    let ball_mesh: Mesh = ball.get_triangle_mesh();
+    assert_eq!(ball_mesh.collision_shape, Some(ball.get_collision_shape()));
+
+    let mut body: Shape = Shape::new(crate_box.get_triangle_mesh(), None);
+    match &body.physics().mesh.collision_shape {
+        Some(CollisionShape::OrientedBox {
+            center,
+            half_extents,
+            ..
+        }) => {
+            assert_vector(*center, Vector3D::new(-19.0, 2.0, 3.0), 1e-9);
+            assert_vector(*half_extents, Vector3D::new(1.0, 2.0, 3.0), 1e-12);
+        }
+        shape => panic!("expected a box, got {:?}", shape),
+    }
+
+    // The shape follows the body while the render mesh keeps its polygons.
+    let physics = body.physics_mut();
+    physics.translate(&Vector3D::new(0.0, 5.0, 0.0));
+    physics
+        .transform
+        .rotate(&Vector3D::new(0.0, 0.0, 1.0), std::f64::consts::FRAC_PI_2);
+    physics.update_world_geometry();
+    assert_eq!(physics.mesh.polygons.len(), 12);
+    match &physics.mesh.collision_shape {
+        Some(CollisionShape::OrientedBox { center, axes, .. }) => {
+            assert_vector(*center, Vector3D::new(-19.0, 7.0, 3.0), 1e-9);
+            assert!(axes[0].y.abs() > 1.0 - 1e-9, "axes {:?}", axes);
+        }
+        shape => panic!("expected a box, got {:?}", shape),
+    }
+}
@@ -0,0 +1,76 @@
+// Fixtures shared by the integration tests, every test file uses only some
+// of them.
+#![allow(dead_code)]
+
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::bodies::Shape;
+use rust_g_engine::camera::Camera;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::Cuboid;
+use rust_g_engine::meshes::Mesh;
+use rust_g_engine::meshes::Sphere;
+use rust_g_engine::physics::BodyFilter;
+use rust_g_engine::physics::ForceGeneratorType;
+use rust_g_engine::physics::GravitySolver;
+use rust_g_engine::physics::UniformGravity;
+use rust_g_engine::scene::body_configurations;
+use rust_g_engine::scene::Simulation;
+
+pub fn sphere_mesh(radius: f64, x: f64, y: f64, z: f64) -> Mesh {
+    let mut sphere: Sphere = Sphere::new(radius, 12, 12);
+    sphere.set_offset(x, y, z);
+    sphere.get_triangle_mesh()
+}
+
+pub fn cuboid_mesh(size: f64, x: f64, y: f64, z: f64) -> Mesh {
+    let mut cuboid: Cuboid = Cuboid::new(size, size, size);
+    cuboid.set_offset(x, y, z);
+    cuboid.get_triangle_mesh()
+}
+
+pub fn assert_vector(actual: Vector3D, expected: Vector3D, tolerance: f64) {
+    assert!(
+        actual.get_distance(&expected) < tolerance,
+        "expected {:?}, got {:?}",
+        expected,
+        actual
+    );
+}
+
+// A simulation without a window or pairwise gravity.
+pub fn get_headless_simulation(timestep_hz: f64) -> Simulation {
+    let mut simulation: Simulation = Simulation::new_headless(Camera::new(100, 100));
+    simulation.gravity.set_solver(GravitySolver::Disabled);
+    simulation.set_timestep_hz(timestep_hz);
+    simulation
+}
+
+// Pulls every body down along -y.
+pub fn add_uniform_gravity(simulation: &mut Simulation) {
+    let gravity: ForceGeneratorType = ForceGeneratorType::UniformGravity(UniformGravity::new());
+    simulation.forces.add(gravity, BodyFilter::All);
+}
+
+pub fn sphere(position: Vector3D, radius: f64, mass: f64, velocity: Vector3D) -> BodyType {
+    let mut body: BodyType = body_configurations::get_material_sphere(position, radius, "stone");
+    body.physics_mut().set_mass(mass);
+    body.physics_mut()
+        .set_velocity(velocity.x, velocity.y, velocity.z);
+    body
+}
+
+pub fn cuboid(min: Vector3D, size: Vector3D, is_stationary: bool) -> BodyType {
+    let mut cuboid: Cuboid = Cuboid::new(size.x, size.y, size.z);
+    cuboid.set_offset(min.x, min.y, min.z);
+    let mut body: Shape = Shape::new(cuboid.get_triangle_mesh(), None);
+    body.physics_mut().set_mass(1.0);
+    body.physics_mut().is_stationary = is_stationary;
+    BodyType::Shape(body)
+}
+
+// A stationary slab whose top face is the plane y = 0.
+pub fn ground() -> BodyType {
+    let min: Vector3D = Vector3D::new(-10.0, -1.0, -10.0);
+    cuboid(min, Vector3D::new(20.0, 1.0, 20.0), true)
+}
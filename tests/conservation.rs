@@ -0,0 +1,179 @@
+mod common;
+
+use common::get_headless_simulation;
+use common::sphere;
+use rust_g_engine::camera::Camera;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::BodyFilter;
+use rust_g_engine::physics::ConservationDrift;
+use rust_g_engine::physics::Drag;
+use rust_g_engine::physics::ForceGeneratorType;
+use rust_g_engine::physics::GravitySolver;
+use rust_g_engine::physics::HookeSpring;
+use rust_g_engine::physics::IntegratorType;
+use rust_g_engine::physics::RadialField;
+use rust_g_engine::physics::UniformGravity;
+use rust_g_engine::scene::Simulation;
+
+fn run(simulation: &mut Simulation, steps: usize) -> ConservationDrift {
+    for _ in 0..steps {
+        simulation.compute_objects();
+    }
+    simulation
+        .diagnostics
+        .get_drift()
+        .expect("diagnostics should have a drift after stepping")
+}
+
+fn get_orbit_simulation() -> Simulation {
+    let mut simulation: Simulation = Simulation::new_headless(Camera::new(100, 100));
+    simulation.diagnostics.enabled = true;
+    simulation.gravity.set_solver(GravitySolver::Pairwise);
+    simulation.gravity.set_softening(1.0);
+    simulation.integrator = IntegratorType::from_name("verlet").unwrap();
+
+    // A light body on a circular orbit around a heavy one, the heavy body
+    // gets the opposite momentum so the system is at rest.
+    let (central_mass, mass, distance): (f64, f64, f64) = (1_000_000.0, 10.0, 1_000.0);
+    let speed: f64 = (0.8 * central_mass / distance).sqrt();
+    let recoil: f64 = -speed * mass / central_mass;
+    let zero: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+    simulation.add_object(sphere(
+        zero,
+        20.0,
+        central_mass,
+        Vector3D::new(0.0, 0.0, recoil),
+    ));
+    let position: Vector3D = Vector3D::new(distance, 0.0, 0.0);
+    simulation.add_object(sphere(position, 5.0, mass, Vector3D::new(0.0, 0.0, speed)));
+    simulation
+}
+
+#[test]
+fn orbit_conserves_energy_and_momentum() {
+    let mut simulation: Simulation = get_orbit_simulation();
+    let drift: ConservationDrift = run(&mut simulation, 1_200);
+
+    let initial = simulation.diagnostics.initial.unwrap();
+    assert!(initial.potential_energy < 0.0);
+    assert!(drift.energy.abs() < 1e-3, "energy drift {}", drift.energy);
+    assert!(
+        drift.linear_momentum < 1e-6,
+        "momentum drift {}",
+        drift.linear_momentum
+    );
+
+    let angular_momentum: f64 = initial.angular_momentum.get_length();
+    let angular_drift: f64 = drift.angular_momentum / angular_momentum;
+    assert!(
+        angular_drift < 1e-3,
+        "angular momentum drift {}",
+        angular_drift
+    );
+    assert!(
+        drift.center_of_mass < 1e-3,
+        "center of mass drift {}",
+        drift.center_of_mass
+    );
+}
+
+#[test]
+fn energy_history_tracks_every_step() {
+    let mut simulation: Simulation = get_orbit_simulation();
+    simulation.diagnostics.history_length = 10;
+    run(&mut simulation, 25);
+
+    let history: Vec<f64> = simulation.diagnostics.get_energy_history();
+    assert_eq!(history.len(), 10);
+    assert!(history.iter().all(|drift| drift.abs() < 1e-3));
+
+    let (points, zero_y): (Vec<(f64, f64)>, f64) = simulation
+        .diagnostics
+        .get_sparkline((0.0, 0.0), (90.0, 20.0));
+    assert_eq!(points.len(), 10);
+    assert!(points
+        .iter()
+        .all(|(x, y)| (0.0..=90.0).contains(x) && (0.0..=20.0).contains(y)));
+    assert!((0.0..=20.0).contains(&zero_y));
+}
+
+#[test]
+fn head_on_collision_conserves_momentum() {
+    let mut simulation: Simulation = get_headless_simulation(60.0);
+    simulation.diagnostics.enabled = true;
+
+    let left: Vector3D = Vector3D::new(-3.0, 0.0, 0.0);
+    let right: Vector3D = Vector3D::new(3.0, 0.0, 0.5);
+    simulation.add_object(sphere(left, 1.0, 2.0, Vector3D::new(4.0, 0.0, 0.0)));
+    simulation.add_object(sphere(right, 1.0, 1.0, Vector3D::new(-4.0, 0.0, 0.0)));
+    let drift: ConservationDrift = run(&mut simulation, 120);
+
+    let current = simulation.diagnostics.current.unwrap();
+    let initial = simulation.diagnostics.initial.unwrap();
+    assert!(
+        current.kinetic_energy < initial.kinetic_energy,
+        "the bodies should have collided"
+    );
+    assert!(
+        drift.energy <= 1e-9,
+        "a collision must not add energy: {}",
+        drift.energy
+    );
+    assert!(
+        drift.linear_momentum < 1e-6,
+        "momentum drift {}",
+        drift.linear_momentum
+    );
+    assert!(
+        drift.angular_momentum < 1e-6,
+        "angular momentum drift {}",
+        drift.angular_momentum
+    );
+}
+
+#[test]
+fn diagnostics_are_off_until_toggled() {
+    let mut simulation: Simulation = Simulation::new_headless(Camera::new(100, 100));
+    simulation.gravity.set_solver(GravitySolver::Pairwise);
+    let zero: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+    simulation.add_object(sphere(zero, 1.0, 1.0, zero));
+    simulation.compute_objects();
+    assert!(simulation.diagnostics.current.is_none());
+
+    simulation.toggle_diagnostics();
+    run(&mut simulation, 2);
+    assert!(simulation.diagnostics.current.is_some());
+}
+
+#[test]
+fn generator_potentials_count_in_the_energy() {
+    let mut simulation: Simulation = get_headless_simulation(240.0);
+    simulation.diagnostics.enabled = true;
+    simulation.integrator = IntegratorType::from_name("verlet").unwrap();
+
+    let left: Vector3D = Vector3D::new(-3.0, 0.0, 0.0);
+    let right: Vector3D = Vector3D::new(3.0, 1.0, 0.0);
+    simulation.add_object(sphere(left, 0.5, 1.0, Vector3D::new(0.0, 2.0, 1.0)));
+    simulation.add_object(sphere(right, 0.5, 2.0, Vector3D::new(-1.0, 0.0, 0.0)));
+    let generators: Vec<ForceGeneratorType> = vec![
+        ForceGeneratorType::UniformGravity(UniformGravity::new()),
+        ForceGeneratorType::HookeSpring(HookeSpring::new(0, 1, 4.0, 5.0, 0.0)),
+        ForceGeneratorType::RadialField(RadialField::attractor(left, 3.0, 8.0)),
+    ];
+    for generator in generators {
+        simulation.forces.add(generator, BodyFilter::All);
+    }
+    assert!(simulation.forces.get_unconserved_names().is_empty());
+
+    // The bodies fall and swing, so most of the energy changes form.
+    let drift: ConservationDrift = run(&mut simulation, 1_200);
+    let current = simulation.diagnostics.current.unwrap();
+    let initial = simulation.diagnostics.initial.unwrap();
+    let change: f64 = (current.potential_energy - initial.potential_energy).abs();
+    assert!(change > 0.5 * initial.get_total_energy().abs());
+    assert!(drift.energy.abs() < 1e-3, "energy drift {}", drift.energy);
+
+    let drag: ForceGeneratorType = ForceGeneratorType::Drag(Drag::new(0.1, 0.0));
+    simulation.forces.add(drag, BodyFilter::All);
+    assert_eq!(simulation.forces.get_unconserved_names(), vec!["Drag"]);
+}
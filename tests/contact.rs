@@ -0,0 +1,143 @@
+mod common;
+
+use common::assert_vector;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::ContactManifold;
+use rust_g_engine::physics::ContactPoint;
+
+// Corners of a unit box centered on the origin, turned by an angle about z.
+fn get_box_corners(angle: f64) -> Vec<Vector3D> {
+    let (sin, cos): (f64, f64) = angle.sin_cos();
+    let mut corners: Vec<Vector3D> = Vec::new();
+    for i in 0..8 {
+        let x: f64 = (i & 1) as f64 - 0.5;
+        let y: f64 = ((i >> 1) & 1) as f64 - 0.5;
+        let z: f64 = ((i >> 2) & 1) as f64 - 0.5;
+        corners.push(Vector3D::new(x * cos - y * sin, x * sin + y * cos, z));
+    }
+    corners
+}
+
+fn translate(vertices: &[Vector3D], offset: Vector3D) -> Vec<Vector3D> {
+    vertices.iter().map(|v| v.add_vector(&offset)).collect()
+}
+
+// A slab whose top face is the plane y = 0.
+fn get_ground_corners() -> Vec<Vector3D> {
+    let slab: Vec<Vector3D> = get_box_corners(0.0)
+        .iter()
+        .map(|v| Vector3D::new(v.x * 4.0, v.y, v.z * 4.0))
+        .collect();
+    translate(&slab, Vector3D::new(0.0, -0.5, 0.0))
+}
+
+fn get_ordered_points(manifold: &ContactManifold) -> Vec<ContactPoint> {
+    let mut points: Vec<ContactPoint> = manifold.points.clone();
+    points.sort_by(|a, b| {
+        let key_a: (f64, f64) = (a.position.x, a.position.z);
+        let key_b: (f64, f64) = (b.position.x, b.position.z);
+        key_a.partial_cmp(&key_b).unwrap()
+    });
+    points
+}
+
+#[test]
+fn box_resting_face_down_gives_four_points() {
+    // Turned just enough that its bottom still counts as one face, with the
+    // left side sunk deeper than the right.
+    let angle: f64 = 0.004;
+    let corners: Vec<Vector3D> = get_box_corners(angle);
+    let bottom: f64 = corners.iter().map(|v| v.y).fold(f64::MAX, f64::min);
+    let corners: Vec<Vector3D> = translate(&corners, Vector3D::new(0.0, -0.1 - bottom, 0.0));
+    let mtv: Vector3D = Vector3D::new(0.0, 0.1, 0.0);
+    let far: Vector3D = Vector3D::new(0.0, 100.0, 0.0);
+
+    let manifold: ContactManifold =
+        ContactManifold::from_vertices((0, 1), &get_ground_corners(), &corners, mtv, far);
+    assert_eq!(manifold.mesh_pair, (0, 1));
+    assert_vector(manifold.normal, Vector3D::new(0.0, 1.0, 0.0), 1e-12);
+    assert_eq!(manifold.points.len(), 4);
+
+    let mut lowest: Vec<Vector3D> = corners.clone();
+    lowest.sort_by(|a, b| a.y.total_cmp(&b.y));
+    lowest.truncate(4);
+    lowest.sort_by(|a, b| (a.x, a.z).partial_cmp(&(b.x, b.z)).unwrap());
+    for (point, corner) in get_ordered_points(&manifold).iter().zip(&lowest) {
+        assert!((point.depth + corner.y).abs() < 1e-9, "{:?}", point);
+        // The point lies halfway between the ground and the sunk corner.
+        let expected: Vector3D = Vector3D::new(corner.x, corner.y / 2.0, corner.z);
+        assert_vector(point.position, expected, 1e-9);
+        assert_eq!(point.normal_impulse, 0.0);
+    }
+    let depths: Vec<f64> = get_ordered_points(&manifold)
+        .iter()
+        .map(|p| p.depth)
+        .collect();
+    assert!(depths[0] > depths[3] + 0.003);
+    assert!((manifold.get_max_depth() - 0.1).abs() < 1e-9);
+}
+
+#[test]
+fn tilted_box_uses_the_ground_as_reference() {
+    // The box is the first body, so its bottom edge is tried as the
+    // reference first and the ground face is used instead.
+    let corners: Vec<Vector3D> = get_box_corners(10f64.to_radians());
+    let bottom: f64 = corners.iter().map(|v| v.y).fold(f64::MAX, f64::min);
+    let corners: Vec<Vector3D> = translate(&corners, Vector3D::new(0.0, -0.05 - bottom, 0.0));
+    let mtv: Vector3D = Vector3D::new(0.0, -0.05, 0.0);
+    let far: Vector3D = Vector3D::new(0.0, 100.0, 0.0);
+
+    let manifold: ContactManifold =
+        ContactManifold::from_vertices((1, 0), &corners, &get_ground_corners(), mtv, far);
+    assert_vector(manifold.normal, Vector3D::new(0.0, -1.0, 0.0), 1e-12);
+    assert_eq!(manifold.points.len(), 2);
+
+    let edge: Vec<&Vector3D> = corners.iter().filter(|v| v.y < -0.049).collect();
+    assert_eq!(edge.len(), 2);
+    for (point, corner) in get_ordered_points(&manifold).iter().zip(edge) {
+        assert!((point.depth - 0.05).abs() < 1e-9);
+        let expected: Vector3D = Vector3D::new(corner.x, -0.025, corner.z);
+        assert_vector(point.position, expected, 1e-9);
+    }
+}
+
+fn get_square_manifold(normal: Vector3D, shift: f64) -> ContactManifold {
+    let points: Vec<ContactPoint> = [(-0.5, -0.5), (-0.5, 0.5), (0.5, -0.5), (0.5, 0.5)]
+        .iter()
+        .map(|(x, z)| ContactPoint::new(Vector3D::new(x + shift, 0.0, *z), 0.01))
+        .collect();
+    ContactManifold::from_points((0, 1), normal, points)
+}
+
+#[test]
+fn warm_start_carries_impulses_of_matching_points() {
+    let up: Vector3D = Vector3D::new(0.0, 1.0, 0.0);
+    let mut previous: ContactManifold = get_square_manifold(up, 0.0);
+    for (idx, point) in previous.points.iter_mut().enumerate() {
+        point.normal_impulse = idx as f64 + 1.0;
+        point.tangent_impulse = Vector3D::new(0.0, 0.0, idx as f64 + 1.0);
+    }
+
+    // Slightly shifted points keep their impulses, one that moved to the
+    // middle matches none of the previous points.
+    let mut current: ContactManifold = get_square_manifold(up, 0.01);
+    current.points[3].position = Vector3D::new(0.0, 0.0, 0.0);
+    current.warm_start(&previous);
+    for idx in 0..3 {
+        assert_eq!(current.points[idx].normal_impulse, idx as f64 + 1.0);
+        let tangent_impulse: Vector3D = Vector3D::new(0.0, 0.0, idx as f64 + 1.0);
+        assert_vector(current.points[idx].tangent_impulse, tangent_impulse, 1e-12);
+    }
+    assert_eq!(current.points[3].normal_impulse, 0.0);
+    assert_vector(
+        current.points[3].tangent_impulse,
+        Vector3D::default(0.0),
+        1e-12,
+    );
+
+    // A contact that turned over starts from nothing.
+    let turned: Vector3D = Vector3D::new(1.0, 1.0, 0.0).normalize();
+    let mut current: ContactManifold = get_square_manifold(turned, 0.0);
+    current.warm_start(&previous);
+    assert!(current.points.iter().all(|p| p.normal_impulse == 0.0));
+}
@@ -0,0 +1,255 @@
+mod common;
+
+use common::add_uniform_gravity;
+use common::cuboid;
+use common::get_headless_simulation;
+use common::ground;
+use common::sphere;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::BodyFilter;
+use rust_g_engine::physics::ForceGeneratorType;
+use rust_g_engine::physics::PhysicsMaterial;
+use rust_g_engine::physics::UniformGravity;
+use rust_g_engine::scene::Simulation;
+
+const STACK_HEIGHT: usize = 5;
+const GRAVITY: f64 = 9.8;
+const STATIC_FRICTION: f64 = 0.5;
+const DYNAMIC_FRICTION: f64 = 0.4;
+
+fn get_simulation(iterations: usize, warm_starting: bool) -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(60.0);
+    simulation.sleep.enabled = false;
+    simulation.contact_solver.set_iterations(iterations);
+    simulation.contact_solver.warm_starting = warm_starting;
+    add_uniform_gravity(&mut simulation);
+    simulation
+}
+
+// Unit boxes resting on each other, the lowest one on the ground at y = 0.
+fn get_stack() -> Vec<BodyType> {
+    let mut bodies: Vec<BodyType> = Vec::new();
+    for level in 0..STACK_HEIGHT {
+        let min: Vector3D = Vector3D::new(-0.5, level as f64, -0.5);
+        bodies.push(cuboid(min, Vector3D::new(1.0, 1.0, 1.0), false));
+    }
+    bodies
+}
+
+fn get_stack_simulation(iterations: usize, warm_starting: bool) -> Simulation {
+    let mut simulation: Simulation = get_simulation(iterations, warm_starting);
+    simulation.add_object(ground());
+    for body in get_stack() {
+        simulation.add_object(body);
+    }
+    simulation
+}
+
+// Runs ten seconds and returns whether the stack came to rest where it was built.
+fn is_settled(simulation: &mut Simulation) -> bool {
+    let mut max_drift: f64 = 0.0;
+    for step in 0..600 {
+        simulation.compute_objects();
+        if step < 300 {
+            continue;
+        }
+        for body in simulation.objects.iter().skip(1) {
+            let position: Vector3D = body.physics().get_position();
+            max_drift = max_drift.max(position.x.hypot(position.z));
+        }
+    }
+    let max_speed: f64 = simulation
+        .objects
+        .iter()
+        .map(|body| body.physics().velocity.get_length())
+        .fold(0.0, f64::max);
+    let top: f64 = simulation.objects[STACK_HEIGHT].physics().get_position().y;
+    max_drift < 0.05 && max_speed < 0.01 && (top - (STACK_HEIGHT as f64 - 0.5)).abs() < 0.1
+}
+
+#[test]
+fn box_stack_settles_without_drift() {
+    let mut simulation: Simulation = get_stack_simulation(10, true);
+    assert!(is_settled(&mut simulation));
+    for level in 0..STACK_HEIGHT {
+        let position: Vector3D = simulation.objects[level + 1].physics().get_position();
+        assert!((position.y - (level as f64 + 0.5)).abs() < 0.05);
+        assert!(position.x.hypot(position.z) < 0.02);
+    }
+}
+
+#[test]
+fn stack_does_not_depend_on_insertion_order() {
+    let mut forward: Simulation = get_stack_simulation(10, true);
+    let mut reversed: Simulation = get_simulation(10, true);
+    let mut bodies: Vec<BodyType> = get_stack();
+    bodies.reverse();
+    for body in bodies {
+        reversed.add_object(body);
+    }
+    reversed.add_object(ground());
+
+    for _ in 0..300 {
+        forward.compute_objects();
+        reversed.compute_objects();
+    }
+    for level in 0..STACK_HEIGHT {
+        let a: Vector3D = forward.objects[level + 1].physics().get_position();
+        let b: Vector3D = reversed.objects[STACK_HEIGHT - 1 - level]
+            .physics()
+            .get_position();
+        assert!(a.get_distance(&b) < 0.05);
+    }
+}
+
+#[test]
+fn penetration_is_resolved_without_teleports() {
+    let mut simulation: Simulation = get_simulation(10, true);
+    simulation.add_object(ground());
+    let depth: f64 = 0.4;
+    let min: Vector3D = Vector3D::new(-0.5, -depth, -0.5);
+    simulation.add_object(cuboid(min, Vector3D::new(1.0, 1.0, 1.0), false));
+
+    // The bias pushes out a fraction of the depth per step, never all of it at once.
+    let max_step: f64 = simulation.contact_solver.bias_factor * depth + 0.01;
+    let mut last: Vector3D = simulation.objects[1].physics().get_position();
+    for _ in 0..300 {
+        simulation.compute_objects();
+        let position: Vector3D = simulation.objects[1].physics().get_position();
+        assert!(position.get_distance(&last) <= max_step);
+        last = position;
+    }
+    assert!((last.y - 0.5).abs() < 0.02);
+    assert!(simulation.objects[1].physics().velocity.get_length() < 0.01);
+}
+
+#[test]
+fn warm_starting_reduces_iterations_needed() {
+    assert!(is_settled(&mut get_stack_simulation(8, true)));
+    assert!(!is_settled(&mut get_stack_simulation(8, false)));
+}
+
+// A frictional, inelastic surface for both bodies of a contact, so the
+// combined coefficients are the ones given.
+fn set_surface(body: &mut BodyType) {
+    let material: PhysicsMaterial =
+        PhysicsMaterial::new(0.0, STATIC_FRICTION, DYNAMIC_FRICTION, None);
+    body.physics_mut().set_material(material);
+}
+
+// The ground and a unit box resting on it, with gravity turned by a slope
+// angle about z, which stands in for tilting the ground.
+fn get_friction_simulation(slope: f64, body: BodyType) -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(120.0);
+    simulation.sleep.enabled = false;
+    simulation.contact_solver.set_iterations(10);
+    let mut gravity: UniformGravity = UniformGravity::new();
+    gravity.set_up(Vector3D::new(slope.sin(), slope.cos(), 0.0));
+    let gravity: ForceGeneratorType = ForceGeneratorType::UniformGravity(gravity);
+    simulation.forces.add(gravity, BodyFilter::All);
+
+    let mut ground: BodyType = ground();
+    set_surface(&mut ground);
+    simulation.add_object(ground);
+    let mut body: BodyType = body;
+    set_surface(&mut body);
+    simulation.add_object(body);
+    simulation
+}
+
+fn unit_box() -> BodyType {
+    cuboid(
+        Vector3D::new(-0.5, 0.0, -0.5),
+        Vector3D::new(1.0, 1.0, 1.0),
+        false,
+    )
+}
+
+#[test]
+fn sliding_box_stops_after_friction_distance() {
+    let speed: f64 = 3.0;
+    let mut simulation: Simulation = get_friction_simulation(0.0, unit_box());
+    simulation.objects[1]
+        .physics_mut()
+        .set_velocity(speed, 0.0, 0.0);
+
+    let mut stop_time: Option<f64> = None;
+    for _ in 0..240 {
+        simulation.compute_objects();
+        let velocity: Vector3D = simulation.objects[1].physics().velocity;
+        if stop_time.is_none() && velocity.get_length() < 1e-3 {
+            stop_time = Some(simulation.sim_time);
+        }
+    }
+
+    // Sliding friction decelerates at mu g, and static friction holds the
+    // box once it has stopped.
+    let expected_distance: f64 = speed * speed / (2.0 * DYNAMIC_FRICTION * GRAVITY);
+    let expected_time: f64 = speed / (DYNAMIC_FRICTION * GRAVITY);
+    let position: Vector3D = simulation.objects[1].physics().get_position();
+    assert!(
+        (position.x - expected_distance).abs() < 0.05 * expected_distance,
+        "stopped after {}, expected {}",
+        position.x,
+        expected_distance
+    );
+    let stop_time: f64 = stop_time.expect("the box should come to rest");
+    assert!((stop_time - expected_time).abs() < 0.05 * expected_time);
+    assert!(simulation.objects[1].physics().velocity.get_length() < 1e-3);
+    assert!((position.y - 0.5).abs() < 0.02);
+}
+
+// Returns how far the box moved along the slope in two seconds.
+fn get_slope_distance(slope: f64) -> f64 {
+    let mut simulation: Simulation = get_friction_simulation(slope, unit_box());
+    let start: Vector3D = simulation.objects[1].physics().get_position();
+    for _ in 0..240 {
+        simulation.compute_objects();
+    }
+    let position: Vector3D = simulation.objects[1].physics().get_position();
+    position.get_distance(&start)
+}
+
+#[test]
+fn box_holds_on_a_slope_below_the_friction_angle() {
+    let friction_angle: f64 = STATIC_FRICTION.atan();
+    assert!(get_slope_distance(friction_angle - 0.1) < 0.01);
+
+    // Past the friction angle the box slides with the dynamic coefficient.
+    let slope: f64 = friction_angle + 0.1;
+    let acceleration: f64 = GRAVITY * (slope.sin() - DYNAMIC_FRICTION * slope.cos());
+    let expected: f64 = acceleration * 2.0 * 2.0 / 2.0;
+    let distance: f64 = get_slope_distance(slope);
+    assert!((distance - expected).abs() < 0.1 * expected, "{}", distance);
+}
+
+#[test]
+fn pushed_sphere_starts_rolling() {
+    let radius: f64 = 0.5;
+    let zero: Vector3D = Vector3D::default(0.0);
+    let ball: BodyType = sphere(Vector3D::new(0.0, radius, 0.0), radius, 1.0, zero);
+    let mut simulation: Simulation = get_friction_simulation(0.0, ball);
+    simulation.objects[1]
+        .physics_mut()
+        .set_velocity(3.0, 0.0, 0.0);
+
+    for _ in 0..240 {
+        simulation.compute_objects();
+    }
+
+    // Rolling along +x turns the ball about -z, without slipping.
+    let velocity: Vector3D = simulation.objects[1].physics().velocity;
+    let angular_velocity: Vector3D = simulation.objects[1].physics().angular_velocity;
+    let slip: f64 = velocity.x + angular_velocity.z * radius;
+    assert!(slip.abs() < 0.01 * velocity.x, "slip {}", slip);
+    // Friction spends two sevenths of the speed of a solid ball on the spin,
+    // and then stops acting.
+    let expected: f64 = 5.0 / 7.0 * 3.0;
+    assert!(
+        (velocity.x - expected).abs() < 0.05 * expected,
+        "{}",
+        velocity.x
+    );
+}
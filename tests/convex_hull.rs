@@ -0,0 +1,189 @@
+mod common;
+
+use common::assert_vector;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use rust_g_engine::math::Quaternion;
+use rust_g_engine::math::Transform;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::Mesh;
+use rust_g_engine::meshes::Polygon;
+use rust_g_engine::meshes::QuickHull;
+
+use std::collections::HashMap;
+
+fn get_random_points(rng: &mut StdRng, count: usize) -> Vec<Vector3D> {
+    (0..count)
+        .map(|_| {
+            Vector3D::new(
+                rng.gen_range(-5.0..5.0),
+                rng.gen_range(-2.0..2.0),
+                rng.gen_range(-3.0..3.0),
+            )
+        })
+        .collect()
+}
+
+fn get_cube_corners(size: f64) -> Vec<Vector3D> {
+    (0..8)
+        .map(|i| {
+            let corner: [f64; 3] = [(i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64];
+            Vector3D::from_array(corner).multiply(size)
+        })
+        .collect()
+}
+
+// The hull point indices of every face, as the triangles store them.
+fn get_faces(mesh: &Mesh) -> Vec<(usize, usize, usize)> {
+    mesh.polygons
+        .iter()
+        .map(|polygon| match polygon {
+            Polygon::Triangle(triangle) => triangle.face,
+            Polygon::Quad(_) => panic!("hull faces are triangles"),
+        })
+        .collect()
+}
+
+// Every directed edge is used once, and once in the opposite direction by
+// the neighbouring face.
+fn assert_closed(mesh: &Mesh) {
+    let mut edge_counts: HashMap<(usize, usize), usize> = HashMap::new();
+    for (a, b, c) in get_faces(mesh) {
+        for edge in [(a, b), (b, c), (c, a)] {
+            *edge_counts.entry(edge).or_default() += 1;
+        }
+    }
+    for (&(a, b), &count) in &edge_counts {
+        assert_eq!(count, 1, "edge ({}, {}) is used {} times", a, b, count);
+        assert_eq!(
+            edge_counts.get(&(b, a)),
+            Some(&1),
+            "edge ({}, {}) has no twin",
+            a,
+            b
+        );
+    }
+}
+
+// Every face normal points away from all the points.
+fn assert_wound_outwards(mesh: &Mesh, points: &[Vector3D]) {
+    for polygon in &mesh.polygons {
+        let normal: Vector3D = polygon.get_normal();
+        let corner: Vector3D = polygon.get_vertices()[0];
+        for point in points {
+            let distance: f64 = normal.dot_product(&point.subtract_vector(&corner));
+            assert!(distance < 1e-9, "a point is {} outside a face", distance);
+        }
+    }
+}
+
+#[test]
+fn hull_is_closed_and_wound_outwards() {
+    let mut rng: StdRng = StdRng::seed_from_u64(3);
+    for count in [4, 10, 100, 1_000] {
+        let points: Vec<Vector3D> = get_random_points(&mut rng, count);
+        let hull: QuickHull = QuickHull::new(points.clone());
+        let mesh: Mesh = hull.get_mesh().expect("random points span a volume");
+
+        assert_closed(&mesh);
+        assert_wound_outwards(&mesh, &points);
+        // A closed triangle mesh of genus zero has V - E + F = 2.
+        let vertex_count: usize = hull.get_hull().len();
+        let face_count: usize = mesh.polygons.len();
+        assert_eq!(vertex_count + face_count - 3 * face_count / 2, 2);
+        for (normal, offset) in hull.get_planes() {
+            assert!(points.iter().all(|p| normal.dot_product(p) - offset < 1e-9));
+        }
+    }
+}
+
+#[test]
+fn coplanar_points_give_a_flat_closed_hull() {
+    // A tilted five by five grid, whose hull is the outer square.
+    let u: Vector3D = Vector3D::new(1.0, 0.5, 0.0);
+    let v: Vector3D = Vector3D::new(0.0, 0.5, 1.0);
+    let mut points: Vec<Vector3D> = Vec::new();
+    for i in 0..5 {
+        for j in 0..5 {
+            points.push(u.multiply(i as f64).add_vector(&v.multiply(j as f64)));
+        }
+    }
+    let hull: QuickHull = QuickHull::new(points.clone());
+    let mesh: Mesh = hull.get_mesh().expect("the points span a plane");
+
+    assert_eq!(hull.get_hull().len(), 4);
+    assert_closed(&mesh);
+    // Both sides are covered, so every face has a twin facing the other way.
+    assert_eq!(mesh.polygons.len(), 4);
+    let normal: Vector3D = u.cross_product(&v).normalize();
+    let up: usize = mesh
+        .polygons
+        .iter()
+        .filter(|polygon| polygon.get_normal().dot_product(&normal) > 0.999)
+        .count();
+    assert_eq!(up, 2);
+    // The diagonal splitting the square is not an edge of the outline.
+    assert_eq!(hull.get_edges().len(), 4);
+}
+
+#[test]
+fn collinear_points_have_no_hull() {
+    let direction: Vector3D = Vector3D::new(1.0, 2.0, -1.0);
+    let points: Vec<Vector3D> = (0..10).map(|i| direction.multiply(i as f64)).collect();
+    let hull: QuickHull = QuickHull::new(points);
+
+    assert!(hull.get_mesh().is_none());
+    assert!(hull.get_hull().is_empty());
+    assert!(hull.get_planes().is_empty());
+    assert!(hull.get_edges().is_empty());
+}
+
+#[test]
+fn duplicate_points_are_merged() {
+    // Every corner as often as a cube mesh repeats it, plus inner points.
+    let mut points: Vec<Vector3D> = Vec::new();
+    for _ in 0..6 {
+        points.extend(get_cube_corners(2.0));
+    }
+    points.push(Vector3D::new(1.0, 1.0, 1.0));
+    points.push(Vector3D::new(0.5, 1.5, 1.0));
+    let hull: QuickHull = QuickHull::new(points.clone());
+    let mesh: Mesh = hull.get_mesh().expect("a cube spans a volume");
+
+    assert_eq!(hull.get_hull().len(), 8);
+    assert_eq!(mesh.polygons.len(), 12);
+    assert_closed(&mesh);
+    assert_wound_outwards(&mesh, &points);
+    // The face diagonals are left out of the outline.
+    assert_eq!(hull.get_edges().len(), 12);
+}
+
+#[test]
+fn hull_mesh_edges_follow_the_transform() {
+    let mut rng: StdRng = StdRng::seed_from_u64(5);
+    let points: Vec<Vector3D> = get_random_points(&mut rng, 50);
+    let local: Mesh = QuickHull::new(points).get_mesh().unwrap();
+
+    let mut transform: Transform = Transform::new(Vector3D::default(0.0), Quaternion::identity());
+    transform.rotate(&Vector3D::new(0.2, 1.0, 0.4).normalize(), 0.8);
+    transform.translate(&Vector3D::new(3.0, -1.0, 7.0));
+    let world: Mesh = local.get_transformed(&transform);
+
+    // The cached edges match the edges of a hull built from the moved mesh.
+    let rebuilt: Vec<(Vector3D, Vector3D)> =
+        QuickHull::new(world.bvh_node.vertices.clone()).get_edges();
+    let cached: Vec<(Vector3D, Vector3D)> = world.get_hull_edges();
+    assert_eq!(cached.len(), rebuilt.len());
+    for (a, b) in cached {
+        let twin: &(Vector3D, Vector3D) = rebuilt
+            .iter()
+            .find(|(c, d)| {
+                let same: f64 = a.get_distance(c) + b.get_distance(d);
+                let swapped: f64 = a.get_distance(d) + b.get_distance(c);
+                same.min(swapped) < 1e-9
+            })
+            .expect("every cached edge is a hull edge");
+        assert_vector(a.add_vector(&b), twin.0.add_vector(&twin.1), 1e-9);
+    }
+}
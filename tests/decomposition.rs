@@ -0,0 +1,154 @@
+mod common;
+
+use common::sphere_mesh;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::ConvexDecomposition;
+use rust_g_engine::meshes::Cuboid;
+use rust_g_engine::meshes::Mesh;
+use rust_g_engine::meshes::MeshDecompose;
+use rust_g_engine::meshes::Polygon;
+use rust_g_engine::meshes::QuickHull;
+
+use std::fs::read_to_string;
+use std::fs::remove_file;
+
+fn get_box_polygons(min: Vector3D, size: Vector3D) -> Vec<Polygon> {
+    let mut cuboid: Cuboid = Cuboid::new(size.x, size.y, size.z);
+    cuboid.set_offset(min.x, min.y, min.z);
+    cuboid.get_triangle_mesh().polygons
+}
+
+// Two unit thick bars meeting at a right angle.
+fn get_l_shape() -> Mesh {
+    let mut polygons: Vec<Polygon> =
+        get_box_polygons(Vector3D::new(0.0, 0.0, 0.0), Vector3D::new(4.0, 1.0, 1.0));
+    polygons.extend(get_box_polygons(
+        Vector3D::new(0.0, 1.0, 0.0),
+        Vector3D::new(1.0, 3.0, 1.0),
+    ));
+    Mesh::new(polygons)
+}
+
+// A row of teeth standing on a bar, every gap between them is concave.
+fn get_comb(teeth: usize) -> Mesh {
+    let length: f64 = 2.0 * teeth as f64 - 1.0;
+    let mut polygons: Vec<Polygon> = get_box_polygons(
+        Vector3D::new(0.0, 0.0, 0.0),
+        Vector3D::new(length, 1.0, 1.0),
+    );
+    for tooth in 0..teeth {
+        let min: Vector3D = Vector3D::new(2.0 * tooth as f64, 1.0, 0.0);
+        polygons.extend(get_box_polygons(min, Vector3D::new(1.0, 3.0, 1.0)));
+    }
+    Mesh::new(polygons)
+}
+
+fn get_hull_point_count(hull: &Mesh) -> usize {
+    QuickHull::new(hull.bvh_node.vertices.clone())
+        .get_hull()
+        .len()
+}
+
+// Points on hull edges may be triangulated differently, so the hulls are
+// compared as shapes: each one lies within the other.
+fn assert_same_hull(a: &Mesh, b: &Mesh) {
+    for (inner, outer) in [(a, b), (b, a)] {
+        let planes: Vec<(Vector3D, f64)> =
+            QuickHull::new(outer.bvh_node.vertices.clone()).get_planes();
+        for vertex in &inner.bvh_node.vertices {
+            assert!(planes
+                .iter()
+                .all(|(normal, offset)| normal.dot_product(vertex) - offset < 1e-9));
+        }
+    }
+}
+
+fn get_cache_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!(
+        "decomposition_{}_{}.hulls",
+        name,
+        std::process::id()
+    ));
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn sphere_stays_a_single_hull() {
+    let decomposition: ConvexDecomposition =
+        MeshDecompose::new(0.02).get_decomposition(&sphere_mesh(10.0, 5.0, -3.0, 2.0));
+    assert_eq!(decomposition.hulls.len(), 1);
+    assert!(
+        decomposition.concavity < 1e-9,
+        "{}",
+        decomposition.concavity
+    );
+}
+
+#[test]
+fn l_shape_splits_into_its_bars() {
+    let decomposition: ConvexDecomposition =
+        MeshDecompose::new(0.02).get_decomposition(&get_l_shape());
+    assert_eq!(decomposition.hulls.len(), 2);
+    assert!(
+        decomposition.concavity < 0.02,
+        "{}",
+        decomposition.concavity
+    );
+    for hull in &decomposition.hulls {
+        assert_eq!(get_hull_point_count(hull), 8);
+    }
+
+    // A loose enough tolerance keeps the whole shape in one hull.
+    let coarse: ConvexDecomposition = MeshDecompose::new(0.9).get_decomposition(&get_l_shape());
+    assert_eq!(coarse.hulls.len(), 1);
+    assert!(coarse.concavity > 0.02);
+}
+
+#[test]
+fn limits_are_respected() {
+    let comb: Mesh = get_comb(5);
+    let unlimited: ConvexDecomposition = MeshDecompose::new(0.01).get_decomposition(&comb);
+    assert!(unlimited.hulls.len() > 3);
+
+    let mut decompose: MeshDecompose = MeshDecompose::new(0.01);
+    decompose.set_max_hulls(3);
+    let limited: ConvexDecomposition = decompose.get_decomposition(&comb);
+    assert_eq!(limited.hulls.len(), 3);
+    assert!(limited.concavity > unlimited.concavity);
+
+    let mut decompose: MeshDecompose = MeshDecompose::new(0.02);
+    decompose.set_max_vertices_per_hull(12);
+    let sphere: Mesh = sphere_mesh(10.0, 0.0, 0.0, 0.0);
+    let decomposition: ConvexDecomposition = decompose.get_decomposition(&sphere);
+    for hull in &decomposition.hulls {
+        let count: usize = get_hull_point_count(hull);
+        assert!(count <= 12, "a hull has {} points", count);
+    }
+}
+
+#[test]
+fn cache_round_trips() {
+    let path: String = get_cache_path("round_trip");
+    let mesh: Mesh = get_l_shape();
+    let decompose: MeshDecompose = MeshDecompose::new(0.02);
+    let written: ConvexDecomposition = decompose.get_cached_decomposition(&mesh, &path);
+    let contents: String = read_to_string(&path).expect("the cache should be written");
+    let key: &str = contents.lines().next().unwrap();
+    assert_eq!(key.len(), 16);
+    assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+
+    let read: ConvexDecomposition = decompose.get_cached_decomposition(&mesh, &path);
+    assert_eq!(read_to_string(&path).unwrap(), contents);
+    assert_eq!(read.concavity, written.concavity);
+    assert_eq!(read.hulls.len(), written.hulls.len());
+    for (a, b) in read.hulls.iter().zip(&written.hulls) {
+        assert_eq!(a.bvh_node.get_bounds(), b.bvh_node.get_bounds());
+        assert_same_hull(a, b);
+    }
+
+    // Other settings give another key, so the cache is written again.
+    MeshDecompose::new(0.05).get_cached_decomposition(&mesh, &path);
+    let rewritten: String = read_to_string(&path).unwrap();
+    assert_ne!(rewritten.lines().next().unwrap(), key);
+    remove_file(&path).unwrap();
+}
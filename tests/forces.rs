@@ -0,0 +1,211 @@
+mod common;
+
+use common::add_uniform_gravity;
+use common::cuboid;
+use common::get_headless_simulation;
+use common::ground;
+use common::sphere;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::BodyFilter;
+use rust_g_engine::physics::BuoyancyVolume;
+use rust_g_engine::physics::Drag;
+use rust_g_engine::physics::ForceGeneratorType;
+use rust_g_engine::physics::ForceRegistry;
+use rust_g_engine::physics::HookeSpring;
+use rust_g_engine::physics::IntegratorType;
+use rust_g_engine::physics::LinearState;
+use rust_g_engine::physics::Wind;
+use rust_g_engine::scene::Simulation;
+
+use std::f64::consts::PI;
+
+fn get_simulation() -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(120.0);
+    simulation.integrator = IntegratorType::from_name("verlet").unwrap();
+    simulation.sleep.enabled = false;
+    simulation
+}
+
+// A ball at rest that the generators act on.
+fn ball(position: Vector3D, mass: f64) -> BodyType {
+    sphere(position, 0.5, mass, Vector3D::new(0.0, 0.0, 0.0))
+}
+
+fn get_state(objects: &[BodyType]) -> LinearState {
+    let positions: Vec<Vector3D> = objects.iter().map(|o| o.physics().get_position()).collect();
+    let velocities: Vec<Vector3D> = objects.iter().map(|o| o.physics().velocity).collect();
+    LinearState::new(positions, velocities)
+}
+
+#[test]
+fn linear_drag_decays_velocity_exponentially() {
+    let mut simulation: Simulation = get_simulation();
+    let (linear, mass): (f64, f64) = (0.5, 2.0);
+    let drag: ForceGeneratorType = ForceGeneratorType::Drag(Drag::new(linear, 0.0));
+    simulation.forces.add(drag, BodyFilter::All);
+    let mut body: BodyType = ball(Vector3D::new(0.0, 0.0, 0.0), mass);
+    body.physics_mut().set_velocity(10.0, 0.0, 0.0);
+    simulation.add_object(body);
+
+    for _ in 0..240 {
+        simulation.compute_objects();
+    }
+    let expected: f64 = 10.0 * (-linear / mass * 2.0).exp();
+    let speed: f64 = simulation.objects[0].physics().velocity.x;
+    assert!(
+        (speed - expected).abs() < 1e-3 * expected,
+        "{} != {}",
+        speed,
+        expected
+    );
+}
+
+#[test]
+fn hooke_spring_oscillates_with_its_natural_period() {
+    let mut simulation: Simulation = get_simulation();
+    let (rest_length, stiffness): (f64, f64) = (4.0, 8.0);
+    simulation.add_object(ball(Vector3D::new(-2.5, 0.0, 0.0), 1.0));
+    simulation.add_object(ball(Vector3D::new(2.5, 0.0, 0.0), 1.0));
+    let spring: HookeSpring = HookeSpring::new(0, 1, rest_length, stiffness, 0.0);
+    simulation
+        .forces
+        .add(ForceGeneratorType::HookeSpring(spring), BodyFilter::All);
+
+    // Two unit masses oscillate with the reduced mass of one half.
+    let period: f64 = 2.0 * PI / (stiffness / 0.5).sqrt();
+    let get_stretch = |simulation: &Simulation| -> f64 {
+        let a: Vector3D = simulation.objects[0].physics().get_position();
+        let b: Vector3D = simulation.objects[1].physics().get_position();
+        a.get_distance(&b) - rest_length
+    };
+    let mut crossings: Vec<f64> = Vec::new();
+    let mut max_stretch: f64 = 0.0;
+    let mut last: f64 = get_stretch(&simulation);
+    for step in 1..=1_200 {
+        simulation.compute_objects();
+        let stretch: f64 = get_stretch(&simulation);
+        if last > 0.0 && stretch <= 0.0 {
+            crossings.push(step as f64 - stretch / (stretch - last));
+        }
+        max_stretch = max_stretch.max(stretch.abs());
+        last = stretch;
+    }
+
+    assert!(crossings.len() >= 4);
+    let measured: f64 =
+        (crossings[crossings.len() - 1] - crossings[0]) / (crossings.len() - 1) as f64 / 120.0;
+    assert!(
+        (measured - period).abs() < 0.01 * period,
+        "{} != {}",
+        measured,
+        period
+    );
+    assert!((max_stretch - 1.0).abs() < 0.01);
+    let momentum: Vector3D = simulation.objects[0]
+        .physics()
+        .velocity
+        .add_vector(&simulation.objects[1].physics().velocity);
+    assert!(momentum.get_length() < 1e-9);
+}
+
+#[test]
+fn buoyancy_floats_a_body_at_its_equilibrium_depth() {
+    let mut simulation: Simulation = get_simulation();
+    add_uniform_gravity(&mut simulation);
+    let density: f64 = 1.0;
+    let min: Vector3D = Vector3D::new(-10.0, -10.0, -10.0);
+    let max: Vector3D = Vector3D::new(10.0, 0.0, 10.0);
+    let mut water: BuoyancyVolume = BuoyancyVolume::new(min, max, density);
+    water.drag = 2.0;
+    simulation
+        .forces
+        .add(ForceGeneratorType::BuoyancyVolume(water), BodyFilter::All);
+
+    // Half of the bounding sphere displaces the whole weight, so the body
+    // floats with its center on the surface.
+    let mut body: BodyType = ball(Vector3D::new(0.0, 2.0, 0.0), 1.0);
+    let radius: f64 = body.physics().get_bounding_radius();
+    body.physics_mut()
+        .set_mass(density * 2.0 / 3.0 * PI * radius.powi(3));
+    simulation.add_object(body);
+
+    for _ in 0..3_000 {
+        simulation.compute_objects();
+    }
+    let physics = simulation.objects[0].physics();
+    assert!(
+        physics.get_position().y.abs() < 0.01 * radius,
+        "{}",
+        physics.get_position().y
+    );
+    assert!(physics.velocity.get_length() < 1e-3);
+}
+
+#[test]
+fn body_filters_mask_the_generators() {
+    let objects: Vec<BodyType> = (0..3)
+        .map(|idx| ball(Vector3D::new(idx as f64 * 3.0, 0.0, 0.0), 1.0))
+        .collect();
+    let state: LinearState = get_state(&objects);
+    let get_pushed = |filter: BodyFilter| -> Vec<bool> {
+        let mut forces: ForceRegistry = ForceRegistry::new();
+        let wind: Wind = Wind::new(Vector3D::new(1.0, 0.0, 0.0), 1.0);
+        forces.add(ForceGeneratorType::Wind(wind), filter);
+        forces
+            .get_accelerations(&objects, &state)
+            .iter()
+            .map(|acceleration| acceleration.x > 0.0)
+            .collect()
+    };
+
+    assert_eq!(get_pushed(BodyFilter::All), vec![true, true, true]);
+    assert_eq!(
+        get_pushed(BodyFilter::Only(vec![1])),
+        vec![false, true, false]
+    );
+    assert_eq!(
+        get_pushed(BodyFilter::Except(vec![1])),
+        vec![true, false, true]
+    );
+    // Indices past the last body are ignored.
+    assert_eq!(
+        get_pushed(BodyFilter::Only(vec![2, 7])),
+        vec![false, false, true]
+    );
+    assert_eq!(
+        get_pushed(BodyFilter::Except(vec![7])),
+        vec![true, true, true]
+    );
+}
+
+#[test]
+fn new_wind_wakes_a_sleeping_body() {
+    let mut simulation: Simulation = get_simulation();
+    simulation.set_timestep_hz(60.0);
+    simulation.integrator = IntegratorType::from_name("euler").unwrap();
+    simulation.sleep.enabled = true;
+    add_uniform_gravity(&mut simulation);
+
+    simulation.add_object(ground());
+    let min: Vector3D = Vector3D::new(-0.5, 0.0, -0.5);
+    simulation.add_object(cuboid(min, Vector3D::new(1.0, 1.0, 1.0), false));
+
+    // The uniform gravity held by the ground lets the box fall asleep.
+    for _ in 0..300 {
+        simulation.compute_objects();
+    }
+    assert!(simulation.objects[1].physics().is_sleeping);
+
+    let wind: Wind = Wind::new(Vector3D::new(50.0, 0.0, 0.0), 5.0);
+    simulation
+        .forces
+        .add(ForceGeneratorType::Wind(wind), BodyFilter::All);
+    for _ in 0..30 {
+        simulation.compute_objects();
+    }
+    let physics = simulation.objects[1].physics();
+    assert!(!physics.is_sleeping);
+    assert!(physics.get_position().x > 0.1);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::assert_vector;
+use common::sphere;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::camera::Camera;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::Gravity;
+use rust_g_engine::physics::GravitySolver;
+use rust_g_engine::scene::Simulation;
+
+// Bodies of mixed mass scattered through a cube, a few thousand units wide.
+fn get_cloud(seed: u64, count: usize) -> Vec<BodyType> {
+    let mut rng: StdRng = StdRng::seed_from_u64(seed);
+    let zero: Vector3D = Vector3D::default(0.0);
+    (0..count)
+        .map(|_| {
+            let position: Vector3D = Vector3D::new(
+                rng.gen_range(-2_000.0..2_000.0),
+                rng.gen_range(-2_000.0..2_000.0),
+                rng.gen_range(-2_000.0..2_000.0),
+            );
+            let mass: f64 = rng.gen_range(1.0..1_000.0);
+            sphere(position, 5.0, mass, zero)
+        })
+        .collect()
+}
+
+#[test]
+fn barnes_hut_is_the_default() {
+    let simulation: Simulation = Simulation::new_headless(Camera::new(100, 100));
+    assert_eq!(simulation.gravity.solver, GravitySolver::BarnesHut);
+    assert!(simulation.gravity.theta > 0.0);
+}
+
+#[test]
+fn error_shrinks_with_theta() {
+    let cloud: Vec<BodyType> = get_cloud(11, 300);
+    let mut gravity: Gravity = Gravity::new(GravitySolver::BarnesHut, 1.0, 1.0);
+
+    let mut previous: (f64, f64) = (f64::INFINITY, f64::INFINITY);
+    for theta in [1.0, 0.5, 0.25, 0.1] {
+        gravity.set_theta(theta);
+        let (max_error, mean_error) = gravity.compare_solvers(&cloud);
+        assert!(
+            max_error < previous.0 && mean_error < previous.1,
+            "theta {}: max {}, mean {}",
+            theta,
+            max_error,
+            mean_error
+        );
+        assert!(mean_error <= max_error);
+        previous = (max_error, mean_error);
+    }
+
+    // The default opening angle keeps every body within a few percent.
+    gravity.set_theta(0.5);
+    let (max_error, _) = gravity.compare_solvers(&cloud);
+    assert!(max_error < 0.05, "{}", max_error);
+}
+
+#[test]
+fn zero_theta_matches_pairwise() {
+    let cloud: Vec<BodyType> = get_cloud(12, 100);
+    let mut gravity: Gravity = Gravity::new(GravitySolver::BarnesHut, 0.0, 1.0);
+    let (max_error, _) = gravity.compare_solvers(&cloud);
+    assert!(max_error < 1e-9, "{}", max_error);
+
+    let barnes_hut: Vec<Vector3D> = gravity.get_accelerations(&cloud);
+    gravity.set_solver(GravitySolver::Pairwise);
+    let pairwise: Vec<Vector3D> = gravity.get_accelerations(&cloud);
+    for (approx, exact) in barnes_hut.iter().zip(&pairwise) {
+        assert_vector(*approx, *exact, 1e-9 * exact.get_length());
+    }
+}
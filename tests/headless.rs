@@ -0,0 +1,91 @@
+mod common;
+
+use common::assert_vector;
+use common::get_headless_simulation;
+use common::sphere;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::scene::BodyState;
+use rust_g_engine::scene::HeadlessRunner;
+use rust_g_engine::scene::Simulation;
+
+// Two spheres drifting apart at a constant velocity, far from each other.
+fn get_runner(timestep_hz: f64) -> HeadlessRunner {
+    let mut simulation: Simulation = get_headless_simulation(timestep_hz);
+    simulation.sleep.enabled = false;
+    let left: Vector3D = Vector3D::new(-100.0, 0.0, 0.0);
+    simulation.add_object(sphere(left, 1.0, 2.0, Vector3D::new(-1.0, 0.0, 0.0)));
+    let right: Vector3D = Vector3D::new(100.0, 0.0, 0.0);
+    simulation.add_object(sphere(right, 1.0, 3.0, Vector3D::new(0.0, 2.0, 0.0)));
+    HeadlessRunner::new(simulation)
+}
+
+#[test]
+fn run_steps_advances_step_count_and_time() {
+    let mut runner: HeadlessRunner = get_runner(4.0);
+    runner.run_steps(10);
+
+    assert_eq!(runner.simulation.step_count, 10);
+    assert!((runner.simulation.sim_time - 2.5).abs() < 1e-12);
+
+    let body_states: Vec<BodyState> = runner.get_body_states();
+    assert_eq!(body_states.len(), 2);
+    assert_eq!(body_states[0].index, 0);
+    assert_eq!(body_states[1].index, 1);
+    assert_eq!(body_states[0].mass, 2.0);
+    assert_eq!(body_states[1].mass, 3.0);
+    assert_vector(
+        body_states[0].position,
+        Vector3D::new(-102.5, 0.0, 0.0),
+        1e-9,
+    );
+    assert_vector(
+        body_states[1].position,
+        Vector3D::new(100.0, 5.0, 0.0),
+        1e-9,
+    );
+    assert_vector(body_states[1].velocity, Vector3D::new(0.0, 2.0, 0.0), 1e-12);
+    assert_vector(
+        body_states[1].angular_velocity,
+        Vector3D::default(0.0),
+        1e-12,
+    );
+}
+
+#[test]
+fn run_for_stops_at_the_end_time() {
+    let mut runner: HeadlessRunner = get_runner(4.0);
+    runner.run_for(2.0);
+    assert_eq!(runner.simulation.step_count, 8);
+    assert!((runner.simulation.sim_time - 2.0).abs() < 1e-12);
+
+    // The run continues from the current time, and a partial step still
+    // carries it past the end.
+    runner.run_for(0.1);
+    assert_eq!(runner.simulation.step_count, 9);
+    assert!((runner.simulation.sim_time - 2.25).abs() < 1e-12);
+
+    let body_states: Vec<BodyState> = runner.get_body_states();
+    assert_vector(
+        body_states[0].position,
+        Vector3D::new(-102.25, 0.0, 0.0),
+        1e-9,
+    );
+    assert_vector(
+        body_states[1].position,
+        Vector3D::new(100.0, 4.5, 0.0),
+        1e-9,
+    );
+}
+
+#[test]
+fn invalid_timestep_rates_are_ignored() {
+    let mut runner: HeadlessRunner = get_runner(4.0);
+    for timestep_hz in [0.0, -5.0, f64::NAN, f64::INFINITY] {
+        runner.simulation.set_timestep_hz(timestep_hz);
+        assert_eq!(runner.simulation.get_timestep(), 0.25);
+    }
+
+    // A rejected rate leaves a step that still reaches the end time.
+    runner.run_for(1.0);
+    assert_eq!(runner.simulation.step_count, 4);
+}
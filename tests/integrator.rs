@@ -0,0 +1,91 @@
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::Integrator;
+use rust_g_engine::physics::IntegratorType;
+use rust_g_engine::physics::LinearState;
+
+// A unit mass on an eccentric Kepler orbit around a fixed unit mass.
+fn get_kepler_accelerations(state: &LinearState) -> Vec<Vector3D> {
+    state
+        .positions
+        .iter()
+        .map(|position| {
+            let distance: f64 = position.get_length();
+            position.multiply(-1.0 / distance.powi(3))
+        })
+        .collect()
+}
+
+fn get_energy(state: &LinearState) -> f64 {
+    let position: Vector3D = state.positions[0];
+    let velocity: Vector3D = state.velocities[0];
+    velocity.get_length_squared() / 2.0 - 1.0 / position.get_length()
+}
+
+// Returns the largest relative energy error and the final position error
+// after whole periods of the orbit.
+fn run_orbit(name: &str, steps_per_period: usize, periods: usize) -> (f64, f64) {
+    let mut integrator: IntegratorType = IntegratorType::from_name(name).unwrap();
+    let start: Vector3D = Vector3D::new(1.0, 0.0, 0.0);
+    // Speed at perihelion of an orbit with eccentricity 0.5 and a = 2.
+    let speed: f64 = 1.5_f64.sqrt();
+    let mut state: LinearState =
+        LinearState::new(vec![start], vec![Vector3D::new(0.0, speed, 0.0)]);
+
+    let period: f64 = std::f64::consts::TAU * 2.0_f64.powf(1.5);
+    let timestep: f64 = period / steps_per_period as f64;
+    let initial_energy: f64 = get_energy(&state);
+    let mut max_error: f64 = 0.0;
+    for _ in 0..steps_per_period * periods {
+        integrator.integrate(&mut state, &get_kepler_accelerations, timestep);
+        let error: f64 = ((get_energy(&state) - initial_energy) / initial_energy).abs();
+        max_error = max_error.max(error);
+    }
+    (max_error, state.positions[0].get_distance(&start))
+}
+
+#[test]
+fn kepler_orbit_error_shrinks_with_the_integrator_order() {
+    let (euler, euler_position): (f64, f64) = run_orbit("euler", 2_000, 3);
+    let (verlet, verlet_position): (f64, f64) = run_orbit("verlet", 2_000, 3);
+    let (rk4, rk4_position): (f64, f64) = run_orbit("rk4", 2_000, 3);
+
+    assert!(euler > verlet, "euler {} verlet {}", euler, verlet);
+    assert!(verlet > rk4, "verlet {} rk4 {}", verlet, rk4);
+    assert!(euler_position > verlet_position);
+    assert!(verlet_position > rk4_position);
+    assert!(rk4 < 1e-8, "rk4 energy error {}", rk4);
+    assert!(rk4_position < 1e-6, "rk4 position error {}", rk4_position);
+}
+
+#[test]
+fn errors_follow_the_order_of_each_integrator() {
+    // Halving the step should cut the error by 2, 4 and 16.
+    for (name, order) in [("euler", 1), ("verlet", 2), ("rk4", 4)] {
+        let (coarse, _): (f64, f64) = run_orbit(name, 1_000, 1);
+        let (fine, _): (f64, f64) = run_orbit(name, 2_000, 1);
+        let ratio: f64 = (coarse / fine).log2();
+        assert!(
+            (ratio - order as f64).abs() < 0.5,
+            "{} converges with order {}",
+            name,
+            ratio
+        );
+    }
+}
+
+#[test]
+fn symplectic_integrators_do_not_drift_secularly() {
+    // Euler and Verlet oscillate around the true energy, so many orbits
+    // stay within the error of the first one.
+    for name in ["euler", "verlet"] {
+        let (one, _): (f64, f64) = run_orbit(name, 1_000, 1);
+        let (many, _): (f64, f64) = run_orbit(name, 1_000, 20);
+        assert!(
+            many < 1.5 * one,
+            "{} drifted from {} to {}",
+            name,
+            one,
+            many
+        );
+    }
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use common::add_uniform_gravity;
+use common::cuboid;
+use common::get_headless_simulation;
+use common::sphere;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::math::Quaternion;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::BallSocketJoint;
+use rust_g_engine::physics::DistanceJoint;
+use rust_g_engine::physics::FixedJoint;
+use rust_g_engine::physics::HingeJoint;
+use rust_g_engine::physics::Joint;
+use rust_g_engine::physics::JointType;
+use rust_g_engine::scene::Simulation;
+
+// Bodies fall along -y under uniform gravity and never fall asleep.
+fn get_simulation() -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(60.0);
+    simulation.sleep.enabled = false;
+    add_uniform_gravity(&mut simulation);
+    simulation
+}
+
+// A unit mass ball to hang from or to pin the joints to.
+fn bob(position: Vector3D, is_stationary: bool) -> BodyType {
+    let mut body: BodyType = sphere(position, 0.5, 1.0, Vector3D::new(0.0, 0.0, 0.0));
+    body.physics_mut().is_stationary = is_stationary;
+    body
+}
+
+// Adds a joint between the first two bodies, anchored at the given points.
+fn add_joint(
+    simulation: &mut Simulation,
+    anchor_a: Vector3D,
+    anchor_b: Vector3D,
+    joint_type: JointType,
+) -> usize {
+    let joint: Joint = Joint::new(&simulation.objects, 0, 1, anchor_a, anchor_b, joint_type);
+    simulation.joint_solver.add_joint(joint)
+}
+
+fn get_distance(simulation: &Simulation) -> f64 {
+    let a: Vector3D = simulation.objects[0].physics().get_position();
+    let b: Vector3D = simulation.objects[1].physics().get_position();
+    a.get_distance(&b)
+}
+
+#[test]
+fn pendulums_hold_their_length() {
+    for joint_type in [
+        JointType::Distance(DistanceJoint::new(10.0)),
+        JointType::BallSocket(BallSocketJoint::new()),
+    ] {
+        let mut simulation: Simulation = get_simulation();
+        let pivot: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+        let start: Vector3D = Vector3D::new(10.0, 0.0, 0.0);
+        simulation.add_object(bob(pivot, true));
+        simulation.add_object(bob(start, false));
+        // A ball socket pins the bob to the pivot through its own anchor.
+        let anchor_b: Vector3D = match joint_type {
+            JointType::BallSocket(_) => pivot,
+            _ => start,
+        };
+        add_joint(&mut simulation, pivot, anchor_b, joint_type);
+
+        let mut lowest: f64 = 0.0;
+        for _ in 0..240 {
+            simulation.compute_objects();
+            let distance: f64 = get_distance(&simulation);
+            assert!((distance - 10.0).abs() < 0.1, "length {}", distance);
+            lowest = lowest.min(simulation.objects[1].physics().get_position().y);
+        }
+        assert!(lowest < -9.0, "the pendulum should swing down: {}", lowest);
+    }
+}
+
+#[test]
+fn ropes_go_slack() {
+    let mut simulation: Simulation = get_simulation();
+    simulation.add_object(bob(Vector3D::new(0.0, 0.0, 0.0), true));
+    simulation.add_object(bob(Vector3D::new(4.0, 0.0, 0.0), false));
+    let rope: JointType = JointType::Distance(DistanceJoint::rope(10.0));
+    let anchor: Vector3D = Vector3D::new(4.0, 0.0, 0.0);
+    add_joint(&mut simulation, Vector3D::new(0.0, 0.0, 0.0), anchor, rope);
+
+    // While slack the bob falls freely.
+    for _ in 0..30 {
+        simulation.compute_objects();
+    }
+    let physics = simulation.objects[1].physics();
+    assert!((physics.get_position().x - 4.0).abs() < 1e-9);
+    assert!(
+        (physics.velocity.y + 9.8 * 0.5).abs() < 1e-6,
+        "{:?}",
+        physics.velocity
+    );
+
+    // Once taut it can not move further than the rope length.
+    for _ in 0..240 {
+        simulation.compute_objects();
+        let distance: f64 = get_distance(&simulation);
+        assert!(distance < 10.1, "length {}", distance);
+    }
+    assert!(get_distance(&simulation) > 9.0);
+}
+
+#[test]
+fn hinge_limits_clamp_the_angle() {
+    let mut simulation: Simulation = get_simulation();
+    simulation.add_object(bob(Vector3D::new(0.0, 0.0, 0.0), true));
+    simulation.add_object(cuboid(
+        Vector3D::new(1.0, -0.25, -0.25),
+        Vector3D::new(8.0, 0.5, 0.5),
+        false,
+    ));
+    let axis: Vector3D = Vector3D::new(0.0, 0.0, 1.0);
+    let hinge: JointType = JointType::Hinge(HingeJoint::new(axis, Some((-0.5, 0.5))));
+    let pivot: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+    let idx: usize = add_joint(&mut simulation, pivot, pivot, hinge);
+
+    let mut lowest_angle: f64 = 0.0;
+    for _ in 0..240 {
+        simulation.compute_objects();
+        let JointType::Hinge(hinge) = &simulation.joint_solver.joints[idx].joint_type else {
+            panic!("the joint should be a hinge");
+        };
+        assert!(hinge.angle > -0.55, "angle {}", hinge.angle);
+        lowest_angle = lowest_angle.min(hinge.angle);
+
+        // The bar only turns about the hinge axis.
+        let axis_b: Vector3D = simulation.objects[1]
+            .physics()
+            .transform
+            .apply_direction(&axis);
+        assert!(axis_b.dot_product(&axis) > 0.999);
+    }
+    assert!(lowest_angle < -0.45, "the bar should reach its limit");
+
+    // The bar hangs at the limit, its far end below the pivot by sin(0.5).
+    let center: Vector3D = simulation.objects[1].physics().get_position();
+    let expected: Vector3D = Vector3D::new(5.0 * 0.5_f64.cos(), -5.0 * 0.5_f64.sin(), 0.0);
+    assert!(center.get_distance(&expected) < 0.2, "center {:?}", center);
+}
+
+#[test]
+fn fixed_joints_hold_the_relative_orientation() {
+    let mut simulation: Simulation = get_simulation();
+    simulation.add_object(cuboid(
+        Vector3D::new(-1.0, -1.0, -1.0),
+        Vector3D::new(2.0, 2.0, 2.0),
+        true,
+    ));
+    simulation.add_object(cuboid(
+        Vector3D::new(1.0, -0.5, -0.5),
+        Vector3D::new(4.0, 1.0, 1.0),
+        false,
+    ));
+    let anchor: Vector3D = Vector3D::new(1.0, 0.0, 0.0);
+    add_joint(
+        &mut simulation,
+        anchor,
+        anchor,
+        JointType::Fixed(FixedJoint::new()),
+    );
+
+    let start: Vector3D = simulation.objects[1].physics().get_position();
+    simulation.objects[1]
+        .physics_mut()
+        .set_angular_velocity(1.0, 0.5, 2.0);
+    for _ in 0..240 {
+        simulation.compute_objects();
+        let orientation: Quaternion = simulation.objects[1].physics().transform.orientation;
+        assert!(orientation.dot(&Quaternion::identity()).abs() > 0.999);
+    }
+    let position: Vector3D = simulation.objects[1].physics().get_position();
+    assert!(
+        position.get_distance(&start) < 0.1,
+        "drifted to {:?}",
+        position
+    );
+}
+
+#[test]
+fn joints_break_above_their_break_force() {
+    for (break_force, is_broken) in [(5.0, true), (100.0, false)] {
+        let mut simulation: Simulation = get_simulation();
+        let pivot: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+        let start: Vector3D = Vector3D::new(0.0, -10.0, 0.0);
+        simulation.add_object(bob(pivot, true));
+        simulation.add_object(bob(start, false));
+        let rod: JointType = JointType::Distance(DistanceJoint::new(10.0));
+        let idx: usize = add_joint(&mut simulation, pivot, start, rod);
+        simulation.joint_solver.joints[idx].set_break_force(break_force);
+
+        // The hanging unit mass pulls with its weight of 9.8.
+        for _ in 0..60 {
+            simulation.compute_objects();
+        }
+        let joint: &Joint = &simulation.joint_solver.joints[idx];
+        assert_eq!(joint.is_broken, is_broken, "force {}", joint.applied_force);
+        assert_eq!(
+            simulation.joint_solver.get_broken_count(),
+            is_broken as usize
+        );
+        let y: f64 = simulation.objects[1].physics().get_position().y;
+        assert_eq!(y < -11.0, is_broken, "the bob is at {}", y);
+    }
+}
+
+#[test]
+fn fixed_joint_breaks_under_pure_torque() {
+    for (break_torque, is_broken) in [(5.0, true), (50.0, false)] {
+        let mut simulation: Simulation = get_headless_simulation(60.0);
+        simulation.sleep.enabled = false;
+        let center: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+        simulation.add_object(bob(center, true));
+        simulation.add_object(bob(center, false));
+        let weld: JointType = JointType::Fixed(FixedJoint::new());
+        let idx: usize = add_joint(&mut simulation, center, center, weld);
+        simulation.joint_solver.joints[idx].set_break_force(1.0);
+        simulation.joint_solver.joints[idx].set_break_torque(break_torque);
+
+        // Anchored at its center, the ball is only twisted, so the joint holds
+        // a torque of about I alpha and no force.
+        let inertia: f64 = 0.4 * 0.5 * 0.5;
+        for _ in 0..60 {
+            simulation.objects[1].physics_mut().angular_acceleration =
+                Vector3D::new(0.0, 0.0, 100.0);
+            simulation.compute_objects();
+        }
+        let joint: &Joint = &simulation.joint_solver.joints[idx];
+        assert_eq!(
+            joint.is_broken, is_broken,
+            "torque {}",
+            joint.applied_torque
+        );
+        assert!(joint.applied_force < 1.0, "force {}", joint.applied_force);
+        if !is_broken {
+            assert!((joint.applied_torque - 100.0 * inertia).abs() < 0.2 * 100.0 * inertia);
+        }
+        let spin: f64 = simulation.objects[1].physics().angular_velocity.z;
+        assert_eq!(spin > 1.0, is_broken, "the ball spins at {}", spin);
+    }
+}
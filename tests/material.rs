@@ -0,0 +1,63 @@
+use rust_g_engine::physics::CombineRule;
+use rust_g_engine::physics::PhysicsMaterial;
+
+const RULES: [CombineRule; 4] = [
+    CombineRule::Average,
+    CombineRule::Min,
+    CombineRule::Multiply,
+    CombineRule::Max,
+];
+
+#[test]
+fn higher_priority_rules_win_in_either_order() {
+    for (i, a) in RULES.iter().enumerate() {
+        for (j, b) in RULES.iter().enumerate() {
+            let expected: CombineRule = RULES[i.max(j)];
+            assert_eq!(
+                a.resolve(b),
+                expected,
+                "{} and {}",
+                a.get_name(),
+                b.get_name()
+            );
+            assert_eq!(b.resolve(a), expected);
+        }
+        assert_eq!(CombineRule::from_name(a.get_name()), Some(*a));
+    }
+
+    assert_eq!(CombineRule::Average.combine(0.2, 0.6), 0.4);
+    assert_eq!(CombineRule::Min.combine(0.2, 0.6), 0.2);
+    assert!((CombineRule::Multiply.combine(0.2, 0.6) - 0.12).abs() < 1e-12);
+    assert_eq!(CombineRule::Max.combine(0.2, 0.6), 0.6);
+}
+
+#[test]
+fn material_pairs_use_the_resolved_rule() {
+    let ice: PhysicsMaterial = PhysicsMaterial::ice();
+    let stone: PhysicsMaterial = PhysicsMaterial::stone();
+    let rubber: PhysicsMaterial = PhysicsMaterial::rubber();
+
+    // Ice takes the lower friction of an average material.
+    assert_eq!(ice.get_friction(&stone), (0.05, 0.03));
+    assert_eq!(stone.get_friction(&ice), (0.05, 0.03));
+    assert_eq!(ice.get_restitution(&stone), (0.1 + 0.3) / 2.0);
+
+    // Rubber bounces with its own restitution against anything lower.
+    assert_eq!(rubber.get_restitution(&stone), 0.85);
+    assert_eq!(stone.get_restitution(&rubber), 0.85);
+    assert_eq!(rubber.get_friction(&stone), (0.9, 0.7));
+
+    // A max friction rule overrides the min rule of ice.
+    let mut grip: PhysicsMaterial = PhysicsMaterial::new(0.2, 0.9, 0.7, None);
+    grip.friction_combine = CombineRule::Max;
+    assert_eq!(ice.get_friction(&grip), (0.9, 0.7));
+
+    // Dynamic friction never exceeds static friction after combining.
+    let mut sticky: PhysicsMaterial = PhysicsMaterial::new(0.0, 0.2, 0.2, None);
+    sticky.friction_combine = CombineRule::Multiply;
+    let mut sliding: PhysicsMaterial = PhysicsMaterial::new(0.0, 0.5, 0.5, None);
+    sliding.static_friction = 0.4;
+    let (static_friction, dynamic_friction): (f64, f64) = sticky.get_friction(&sliding);
+    assert!(dynamic_friction <= static_friction);
+    assert!((static_friction - 0.08).abs() < 1e-12);
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use common::assert_vector;
+use rust_g_engine::bodies::Physics;
+use rust_g_engine::math::Matrix3;
+use rust_g_engine::math::Quaternion;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::Cuboid;
+
+fn assert_matrix(actual: &Matrix3, expected: &Matrix3, tolerance: f64) {
+    for i in 0..3 {
+        for j in 0..3 {
+            let error: f64 = (actual.m[i][j] - expected.m[i][j]).abs();
+            assert!(
+                error < tolerance,
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+}
+
+#[test]
+fn quaternion_matrix_matches_the_rotated_vectors() {
+    let axis: Vector3D = Vector3D::new(1.0, 2.0, -0.5);
+    let rotation: Quaternion = Quaternion::from_axis_angle(&axis, 1.1);
+    let matrix: Matrix3 = rotation.to_matrix();
+
+    for vector in [
+        Vector3D::new(1.0, 0.0, 0.0),
+        Vector3D::new(0.0, -3.0, 2.0),
+        Vector3D::new(4.0, 1.0, 7.0),
+    ] {
+        assert_vector(
+            matrix.multiply_vector(&vector),
+            rotation.rotate_vector(&vector),
+            1e-12,
+        );
+    }
+    assert_matrix(
+        &matrix.multiply(&matrix.transpose()),
+        &Matrix3::identity(),
+        1e-12,
+    );
+    assert!((matrix.get_determinant() - 1.0).abs() < 1e-12);
+
+    let quarter_turn: Quaternion =
+        Quaternion::from_axis_angle(&Vector3D::new(0.0, 0.0, 1.0), std::f64::consts::FRAC_PI_2);
+    assert_vector(
+        quarter_turn
+            .to_matrix()
+            .multiply_vector(&Vector3D::new(1.0, 0.0, 0.0)),
+        Vector3D::new(0.0, 1.0, 0.0),
+        1e-12,
+    );
+
+    let (found_axis, angle): (Vector3D, f64) = rotation.to_axis_angle();
+    assert_vector(found_axis, axis.normalize(), 1e-12);
+    assert!((angle - 1.1).abs() < 1e-12);
+}
+
+#[test]
+fn slerp_follows_the_short_arc_at_constant_speed() {
+    let axis: Vector3D = Vector3D::new(0.0, 1.0, 0.0);
+    let start: Quaternion = Quaternion::from_axis_angle(&axis, 0.2);
+    let end: Quaternion = Quaternion::from_axis_angle(&axis, 1.4);
+
+    assert!((start.slerp(&end, 0.0).dot(&start) - 1.0).abs() < 1e-12);
+    assert!((start.slerp(&end, 1.0).dot(&end) - 1.0).abs() < 1e-12);
+    for t in [0.25, 0.5, 0.75] {
+        let (_, angle): (Vector3D, f64) = start.slerp(&end, t).to_axis_angle();
+        assert!(
+            (angle - (0.2 + 1.2 * t)).abs() < 1e-12,
+            "angle {} at {}",
+            angle,
+            t
+        );
+    }
+
+    // The negated end is the same rotation and must not take the long way.
+    let negated: Quaternion = Quaternion::new(-end.w, -end.x, -end.y, -end.z);
+    let halfway: Quaternion = start.slerp(&negated, 0.5);
+    let (_, angle): (Vector3D, f64) = halfway.to_axis_angle();
+    assert!((angle - 0.8).abs() < 1e-12, "angle {}", angle);
+
+    // Nearly equal rotations fall back to a normalized lerp.
+    let close: Quaternion = Quaternion::from_axis_angle(&axis, 0.2 + 1e-8);
+    assert!((start.slerp(&close, 0.5).get_length() - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn matrix_inverse_undoes_the_matrix() {
+    let matrix: Matrix3 = Matrix3::new([[4.0, 1.0, -2.0], [0.5, 3.0, 1.0], [2.0, -1.0, 5.0]]);
+    let inverse: Matrix3 = matrix.inverse();
+    assert_matrix(&matrix.multiply(&inverse), &Matrix3::identity(), 1e-12);
+    assert_matrix(&inverse.multiply(&matrix), &Matrix3::identity(), 1e-12);
+
+    let diagonal: Matrix3 = Matrix3::from_diagonal(&Vector3D::new(2.0, 4.0, 8.0));
+    assert_vector(
+        diagonal.inverse().get_diagonal(),
+        Vector3D::new(0.5, 0.25, 0.125),
+        1e-15,
+    );
+
+    // A singular matrix has no inverse and comes back as zero.
+    let singular: Matrix3 = Matrix3::new([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 1.0]]);
+    assert_eq!(singular.inverse(), Matrix3::zero());
+}
+
+#[test]
+fn cuboid_inertia_matches_the_closed_form() {
+    let (width, height, depth, mass): (f64, f64, f64, f64) = (2.0, 4.0, 6.0, 3.0);
+    let mesh = Cuboid::new(width, height, depth).get_triangle_mesh();
+    let inertia: Matrix3 = Physics::get_inertia_tensor(&mesh, mass);
+
+    let expected: Vector3D = Vector3D::new(
+        mass * (height * height + depth * depth) / 12.0,
+        mass * (width * width + depth * depth) / 12.0,
+        mass * (width * width + height * height) / 12.0,
+    );
+    assert_matrix(&inertia, &Matrix3::from_diagonal(&expected), 1e-9);
+}
+
+#[test]
+fn tumbling_body_conserves_angular_momentum() {
+    let mut physics: Physics = Physics::new(Cuboid::new(2.0, 4.0, 6.0).get_triangle_mesh(), None);
+    physics.set_mass(3.0);
+
+    // A spin off every principal axis makes the angular velocity precess.
+    physics.set_angular_velocity(0.3, 1.5, 0.2);
+    let initial_momentum: Vector3D = physics.get_angular_momentum();
+    let initial_velocity: Vector3D = physics.angular_velocity;
+    let energy = |physics: &Physics| -> f64 {
+        physics
+            .angular_velocity
+            .dot_product(&physics.get_angular_momentum())
+            / 2.0
+    };
+    let initial_energy: f64 = energy(&physics);
+
+    for _ in 0..2_000 {
+        physics.update_rotation(0.01);
+    }
+
+    let drift: f64 = physics
+        .get_angular_momentum()
+        .subtract_vector(&initial_momentum)
+        .get_length();
+    assert!(
+        drift < 1e-9 * initial_momentum.get_length(),
+        "momentum drift {}",
+        drift
+    );
+    assert!((energy(&physics) - initial_energy).abs() < 1e-3 * initial_energy);
+    assert!(physics.angular_velocity.get_distance(&initial_velocity) > 0.1);
+}
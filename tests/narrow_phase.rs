@@ -0,0 +1,95 @@
+mod common;
+
+use common::cuboid_mesh;
+use common::sphere_mesh;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::Mesh;
+use rust_g_engine::physics::NarrowPhase;
+
+type Intersection = Option<(Vector3D, Vector3D)>;
+
+fn compare(a: &Mesh, b: &Mesh) -> (Intersection, Intersection) {
+    let sat = a.is_intersecting_bvh(b, NarrowPhase::SeparatingAxis);
+    let gjk = a.is_intersecting_bvh(b, NarrowPhase::GJK);
+    (sat, gjk)
+}
+
+fn assert_matches_sat(a: &Mesh, b: &Mesh, depth_tolerance: f64) {
+    let (sat, gjk) = compare(a, b);
+    let (sat_mtv, _) = sat.expect("SAT should report an intersection");
+    let (gjk_mtv, gjk_contact) = gjk.expect("GJK should report an intersection");
+
+    let depth_error: f64 = (sat_mtv.get_length() - gjk_mtv.get_length()).abs();
+    assert!(
+        depth_error <= depth_tolerance,
+        "depths differ: SAT {} GJK {}",
+        sat_mtv.get_length(),
+        gjk_mtv.get_length()
+    );
+
+    // Tessellated spheres have several equally shallow axes, so both normals
+    // are checked against the center line rather than against each other.
+    let center_a: Vector3D = a.bvh_node.get_center();
+    let center_b: Vector3D = b.bvh_node.get_center();
+    let center_line: Vector3D = center_b.subtract_vector(&center_a).normalize();
+    for mtv in [sat_mtv, gjk_mtv] {
+        let alignment: f64 = mtv.normalize().dot_product(&center_line);
+        assert!(alignment > 0.9, "normal off the center line: {alignment}");
+    }
+
+    let midpoint: Vector3D = center_a.get_midpoint(&center_b);
+    assert!(gjk_contact.get_distance(&midpoint) < center_a.get_distance(&midpoint));
+}
+
+#[test]
+fn separated_shapes_do_not_intersect() {
+    let spheres: (Mesh, Mesh) = (
+        sphere_mesh(10.0, 0.0, 0.0, 0.0),
+        sphere_mesh(10.0, 25.0, 0.0, 0.0),
+    );
+    let cuboids: (Mesh, Mesh) = (
+        cuboid_mesh(10.0, 0.0, 0.0, 0.0),
+        cuboid_mesh(10.0, 0.0, 12.0, 0.0),
+    );
+
+    for (a, b) in [spheres, cuboids] {
+        let (sat, gjk) = compare(&a, &b);
+        assert!(sat.is_none());
+        assert!(gjk.is_none());
+        assert!(a.bvh_node.get_gjk_distance(&b.bvh_node) > 0.0);
+    }
+}
+
+#[test]
+fn overlapping_cuboids_match_sat() {
+    let a: Mesh = cuboid_mesh(10.0, 0.0, 0.0, 0.0);
+    let b: Mesh = cuboid_mesh(10.0, 8.0, 1.0, -1.0);
+    assert_matches_sat(&a, &b, 1e-6);
+
+    let (_, gjk) = compare(&a, &b);
+    let (mtv, _) = gjk.unwrap();
+    assert!((mtv.get_length() - 2.0).abs() < 1e-6);
+    assert!(mtv.x > 0.0);
+}
+
+#[test]
+fn overlapping_spheres_match_sat() {
+    let a: Mesh = sphere_mesh(10.0, 0.0, 0.0, 0.0);
+    let b: Mesh = sphere_mesh(10.0, 17.0, 0.0, 0.0);
+    assert_matches_sat(&a, &b, 0.5);
+}
+
+#[test]
+fn sphere_resting_in_cuboid_face_matches_sat() {
+    let a: Mesh = cuboid_mesh(20.0, 0.0, 0.0, 0.0);
+    let b: Mesh = sphere_mesh(5.0, 10.0, 24.0, 10.0);
+    assert_matches_sat(&a, &b, 0.5);
+}
+
+#[test]
+fn gjk_distance_between_cuboids() {
+    let a: Mesh = cuboid_mesh(10.0, 0.0, 0.0, 0.0);
+    let b: Mesh = cuboid_mesh(10.0, 0.0, 0.0, 15.0);
+    let distance: f64 = a.bvh_node.get_gjk_distance(&b.bvh_node);
+    assert!((distance - 5.0).abs() < 1e-6, "distance {distance}");
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::add_uniform_gravity;
+use common::cuboid;
+use common::get_headless_simulation;
+use common::ground;
+use common::sphere;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::get_islands;
+use rust_g_engine::physics::BodyFilter;
+use rust_g_engine::physics::ForceGeneratorType;
+use rust_g_engine::physics::SleepManager;
+use rust_g_engine::physics::Wind;
+use rust_g_engine::scene::Simulation;
+
+fn unit_box(x: f64, y: f64) -> BodyType {
+    let size: Vector3D = Vector3D::new(1.0, 1.0, 1.0);
+    cuboid(Vector3D::new(x - 0.5, y, -0.5), size, false)
+}
+
+// The ground, a pile of three boxes and a lone box beside it.
+fn get_pile_simulation() -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(60.0);
+    add_uniform_gravity(&mut simulation);
+    simulation.add_object(ground());
+    for level in 0..3 {
+        simulation.add_object(unit_box(0.0, level as f64));
+    }
+    simulation.add_object(unit_box(4.0, 0.0));
+    simulation
+}
+
+fn is_sleeping(simulation: &Simulation, idx: usize) -> bool {
+    simulation.objects[idx].physics().is_sleeping
+}
+
+// Steps until every dynamic body sleeps, returning the number of steps.
+fn run_until_asleep(simulation: &mut Simulation, max_steps: usize) -> Option<usize> {
+    for step in 1..=max_steps {
+        simulation.compute_objects();
+        if (1..simulation.objects.len()).all(|idx| is_sleeping(simulation, idx)) {
+            return Some(step);
+        }
+    }
+    None
+}
+
+#[test]
+fn body_sleeps_after_sleep_steps_at_rest() {
+    let mut manager: SleepManager = SleepManager::new(true);
+    manager.set_sleep_steps(10);
+    let mut objects: Vec<BodyType> = vec![ground(), unit_box(0.0, 0.0)];
+    let contact_pairs: Vec<(usize, usize)> = vec![(0, 1)];
+
+    // Just above the threshold the timer never runs.
+    let radius: f64 = objects[1].physics().get_bounding_radius();
+    let threshold: f64 = manager.linear_threshold * radius;
+    objects[1]
+        .physics_mut()
+        .set_velocity(1.01 * threshold, 0.0, 0.0);
+    for _ in 0..20 {
+        manager.update(&mut objects, &contact_pairs);
+    }
+    assert!(!objects[1].physics().is_sleeping);
+    assert_eq!(objects[1].physics().sleep_timer, 0);
+
+    objects[1]
+        .physics_mut()
+        .set_velocity(0.99 * threshold, 0.0, 0.0);
+    for _ in 0..9 {
+        manager.update(&mut objects, &contact_pairs);
+    }
+    assert!(!objects[1].physics().is_sleeping);
+    manager.update(&mut objects, &contact_pairs);
+    assert!(objects[1].physics().is_sleeping);
+    assert_eq!(objects[1].physics().velocity.get_length(), 0.0);
+    assert_eq!(manager.sleeping_count, 1);
+}
+
+#[test]
+fn free_body_at_rest_stays_awake() {
+    let mut manager: SleepManager = SleepManager::new(true);
+    manager.set_sleep_steps(10);
+    let mut objects: Vec<BodyType> = vec![unit_box(0.0, 10.0)];
+    for _ in 0..20 {
+        manager.update(&mut objects, &[]);
+    }
+    assert!(!objects[0].physics().is_sleeping);
+    assert_eq!(manager.awake_count, 1);
+}
+
+#[test]
+fn resting_pile_sleeps_as_one_island() {
+    let mut simulation: Simulation = get_pile_simulation();
+    let steps: Option<usize> = run_until_asleep(&mut simulation, 600);
+    assert!(steps.is_some(), "the pile never fell asleep");
+
+    // The ground joins no island, so the lone box is an island of its own.
+    assert_eq!(simulation.sleep.island_count, 2);
+    assert_eq!(simulation.sleep.sleeping_count, 4);
+    assert_eq!(simulation.sleep.awake_count, 0);
+    for level in 0..3 {
+        let position: Vector3D = simulation.objects[level + 1].physics().get_position();
+        assert!((position.y - (level as f64 + 0.5)).abs() < 0.05);
+    }
+}
+
+#[test]
+fn sleeping_pile_wakes_on_contact() {
+    let mut simulation: Simulation = get_pile_simulation();
+    run_until_asleep(&mut simulation, 600).expect("the pile should fall asleep");
+
+    let falling: Vector3D = Vector3D::new(0.0, -5.0, 0.0);
+    simulation.add_object(sphere(Vector3D::new(0.0, 4.0, 0.0), 0.5, 1.0, falling));
+    let mut woken: bool = false;
+    for _ in 0..60 {
+        simulation.compute_objects();
+        if (1..4).all(|idx| !is_sleeping(&simulation, idx)) {
+            woken = true;
+            break;
+        }
+    }
+    assert!(woken, "the pile slept through the impact");
+    // The lone box is on another island and is not disturbed.
+    assert!(is_sleeping(&simulation, 4));
+    assert_eq!(simulation.objects[4].physics().velocity.get_length(), 0.0);
+}
+
+#[test]
+fn sleeping_body_wakes_on_applied_force() {
+    let mut simulation: Simulation = get_pile_simulation();
+    run_until_asleep(&mut simulation, 600).expect("the pile should fall asleep");
+
+    // The lone box is pushed directly.
+    simulation.objects[4].physics_mut().acceleration = Vector3D::new(2.0, 0.0, 0.0);
+    simulation.compute_objects();
+    assert!(!is_sleeping(&simulation, 4));
+    assert!(is_sleeping(&simulation, 1));
+
+    // A wind that comes up later wakes the pile as well.
+    let wind: Wind = Wind::new(Vector3D::new(0.0, 0.0, 20.0), 1.0);
+    let filter: BodyFilter = BodyFilter::Only(vec![1, 2, 3]);
+    simulation
+        .forces
+        .add(ForceGeneratorType::Wind(wind), filter);
+    simulation.compute_objects();
+    assert!((1..4).all(|idx| !is_sleeping(&simulation, idx)));
+}
+
+#[test]
+fn islands_follow_the_contact_graph() {
+    // 0 and 5 are static, 1-2-3 form a chain, 4 touches only static bodies
+    // and 6 touches nothing.
+    let is_static: Vec<bool> = vec![true, false, false, false, false, true, false];
+    let contact_pairs: Vec<(usize, usize)> = vec![(0, 1), (1, 2), (3, 2), (0, 4), (4, 5), (5, 3)];
+    let islands: Vec<Vec<usize>> = get_islands(7, &contact_pairs, &is_static);
+
+    assert_eq!(islands, vec![vec![1, 2, 3], vec![4], vec![6]]);
+    assert!(get_islands(3, &[], &[true, true, true]).is_empty());
+}
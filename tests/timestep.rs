@@ -0,0 +1,143 @@
+mod common;
+
+use common::add_uniform_gravity;
+use common::get_headless_simulation;
+use common::sphere;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::physics::GravitySolver;
+use rust_g_engine::physics::IntegratorType;
+use rust_g_engine::physics::LinearState;
+use rust_g_engine::physics::TimestepMode;
+use rust_g_engine::scene::Simulation;
+
+const TIMESTEP: f64 = 0.5;
+
+// A light body swings around a heavy one, close enough that a fixed step of
+// half a second jumps across most of the encounter.
+fn get_flyby_simulation(mode: TimestepMode) -> Simulation {
+    let mut simulation: Simulation = get_headless_simulation(1.0 / TIMESTEP);
+    simulation.diagnostics.enabled = true;
+    simulation.gravity.set_solver(GravitySolver::Pairwise);
+    simulation.gravity.set_softening(0.0);
+    simulation.integrator = IntegratorType::from_name("verlet").unwrap();
+    simulation.adaptive_timestep.set_mode(mode);
+
+    let zero: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+    simulation.add_object(sphere(zero, 20.0, 1_000_000.0, zero));
+    let start: Vector3D = Vector3D::new(-2_000.0, 400.0, 0.0);
+    simulation.add_object(sphere(start, 5.0, 1.0, Vector3D::new(40.0, 0.0, 0.0)));
+    // A distant body that never needs a fine step.
+    let far: Vector3D = Vector3D::new(0.0, 0.0, 50_000.0);
+    simulation.add_object(sphere(far, 20.0, 1.0, zero));
+    simulation
+}
+
+// Runs through the encounter and returns the largest energy error, the
+// finest step taken and the finest level each body asked for.
+fn run_flyby(mode: TimestepMode) -> (f64, f64, Vec<u32>) {
+    let mut simulation: Simulation = get_flyby_simulation(mode);
+    let mut max_drift: f64 = 0.0;
+    let mut finest_timestep: f64 = f64::INFINITY;
+    let mut finest_levels: Vec<u32> = vec![0; simulation.objects.len()];
+    let zero: Vector3D = Vector3D::default(0.0);
+
+    for _ in 0..200 {
+        simulation.compute_objects();
+        let drift: f64 = simulation.diagnostics.get_drift().unwrap().energy;
+        max_drift = max_drift.max(drift.abs());
+        finest_timestep = finest_timestep.min(simulation.adaptive_timestep.effective_timestep);
+
+        let force_accelerations: Vec<Vector3D> = vec![zero; simulation.objects.len()];
+        let levels: Vec<u32> = simulation.adaptive_timestep.get_levels(
+            &simulation.objects,
+            &simulation.gravity,
+            &force_accelerations,
+            TIMESTEP,
+            simulation.sim_time,
+            None,
+        );
+        for (finest, level) in finest_levels.iter_mut().zip(levels) {
+            *finest = (*finest).max(level);
+        }
+    }
+    (max_drift, finest_timestep, finest_levels)
+}
+
+#[test]
+fn close_encounters_refine_the_step() {
+    let (fixed, fixed_timestep, _) = run_flyby(TimestepMode::Fixed);
+    let (global, global_timestep, _) = run_flyby(TimestepMode::Global);
+    let (block, block_timestep, _) = run_flyby(TimestepMode::Block);
+
+    assert_eq!(fixed_timestep, TIMESTEP);
+    assert!(
+        global_timestep <= TIMESTEP / 8.0,
+        "global step {}",
+        global_timestep
+    );
+    assert!(
+        block_timestep <= TIMESTEP / 8.0,
+        "block step {}",
+        block_timestep
+    );
+    assert!(global < fixed / 50.0, "global {} fixed {}", global, fixed);
+    assert!(block < fixed / 50.0, "block {} fixed {}", block, fixed);
+}
+
+#[test]
+fn block_steps_only_refine_the_encounter() {
+    let (_, _, levels) = run_flyby(TimestepMode::Block);
+    assert!(levels[1] >= 3, "levels {:?}", levels);
+    assert_eq!(levels[0], 0);
+    assert_eq!(levels[2], 0);
+
+    // Coarser blocks only start where they line up with the finer ones.
+    let simulation: Simulation = get_flyby_simulation(TimestepMode::Block);
+    let adaptive_timestep = &simulation.adaptive_timestep;
+    assert_eq!(adaptive_timestep.get_aligned_level(0, 3), 10);
+    assert_eq!(adaptive_timestep.get_aligned_level(0, 512), 1);
+    assert_eq!(adaptive_timestep.get_aligned_level(2, 256), 2);
+}
+
+#[test]
+fn force_registry_accelerations_refine_the_step() {
+    // Gravity between the bodies is off, a strong uniform pull on the first
+    // body is the only thing that asks for a finer step.
+    let mut simulation: Simulation = get_headless_simulation(1.0 / TIMESTEP);
+    simulation.adaptive_timestep.set_mode(TimestepMode::Block);
+    add_uniform_gravity(&mut simulation);
+    let zero: Vector3D = Vector3D::new(0.0, 0.0, 0.0);
+    simulation.add_object(sphere(zero, 1.0, 1.0, zero));
+    simulation.add_object(sphere(Vector3D::new(100.0, 0.0, 0.0), 1.0, 1.0, zero));
+    simulation.objects[0].physics_mut().gravity = -1_000.0;
+    simulation.objects[1].physics_mut().gravity = 0.0;
+
+    let positions: Vec<Vector3D> = simulation
+        .objects
+        .iter()
+        .map(|o| o.physics().get_position())
+        .collect();
+    let state: LinearState = LinearState::new(positions, vec![zero; 2]);
+    let force_accelerations: Vec<Vector3D> = simulation
+        .forces
+        .get_accelerations(&simulation.objects, &state);
+    let levels: Vec<u32> = simulation.adaptive_timestep.get_levels(
+        &simulation.objects,
+        &simulation.gravity,
+        &force_accelerations,
+        TIMESTEP,
+        0.0,
+        None,
+    );
+    assert!(levels[0] >= 3, "levels {:?}", levels);
+    assert_eq!(levels[1], 0);
+
+    simulation.compute_objects();
+    let effective_timestep: f64 = simulation.adaptive_timestep.effective_timestep;
+    assert!(
+        effective_timestep <= TIMESTEP / 8.0,
+        "step {}",
+        effective_timestep
+    );
+}
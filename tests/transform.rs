@@ -0,0 +1,81 @@
+mod common;
+
+use common::assert_vector;
+use common::cuboid_mesh;
+use rust_g_engine::bodies::Body;
+use rust_g_engine::bodies::BodyType;
+use rust_g_engine::bodies::Shape;
+use rust_g_engine::math::Vector3D;
+use rust_g_engine::meshes::Sphere;
+use rust_g_engine::physics::Gravity;
+use rust_g_engine::physics::GravitySolver;
+
+fn cuboid_body(size: f64, x: f64, y: f64, z: f64) -> Shape {
+    Shape::new(cuboid_mesh(size, x, y, z), None)
+}
+
+#[test]
+fn bodies_start_at_their_center_of_mass() {
+    let body: Shape = cuboid_body(2.0, 10.0, 0.0, -4.0);
+    assert_vector(
+        body.physics().get_position(),
+        Vector3D::new(11.0, 1.0, -3.0),
+        1e-9,
+    );
+}
+
+#[test]
+fn set_position_moves_the_world_geometry() {
+    let mut body: Shape = cuboid_body(2.0, 10.0, 0.0, -4.0);
+    body.physics_mut().set_position(0.0, 5.0, 0.0);
+
+    let physics = body.physics();
+    assert_vector(
+        physics.transform.position,
+        Vector3D::new(0.0, 5.0, 0.0),
+        1e-12,
+    );
+    let (min, max): ([f64; 3], [f64; 3]) = physics.get_aabb();
+    assert_vector(
+        Vector3D::from_array(min),
+        Vector3D::new(-1.0, 4.0, -1.0),
+        1e-9,
+    );
+    assert_vector(
+        Vector3D::from_array(max),
+        Vector3D::new(1.0, 6.0, 1.0),
+        1e-9,
+    );
+
+    match &physics.mesh.collision_shape {
+        Some(shape) => {
+            let (shape_min, _) = shape.get_aabb().unwrap();
+            assert_vector(
+                Vector3D::from_array(shape_min),
+                Vector3D::new(-1.0, 4.0, -1.0),
+                1e-9,
+            );
+        }
+        None => panic!("a cuboid should have a box shape"),
+    }
+}
+
+#[test]
+fn gravity_pulls_towards_the_placed_bodies() {
+    // Neither body is given a position, they sit where their meshes are.
+    let mut objects: Vec<BodyType> = Vec::new();
+    for x in [100.0, 200.0] {
+        let mut sphere: Sphere = Sphere::new(5.0, 10, 10);
+        sphere.set_offset(x, 50.0, 0.0);
+        let mut body: Shape = Shape::new(sphere.get_triangle_mesh(), None);
+        body.physics_mut().set_mass(1_000.0);
+        objects.push(BodyType::Shape(body));
+    }
+
+    let gravity: Gravity = Gravity::new(GravitySolver::Pairwise, 0.5, 0.0);
+    let accelerations: Vec<Vector3D> = gravity.get_accelerations(&objects);
+    let expected: f64 = 0.8 * 1_000.0 / 100.0_f64.powi(2);
+    assert!((accelerations[0].x - expected).abs() < 1e-3 * expected);
+    assert!((accelerations[1].x + expected).abs() < 1e-3 * expected);
+    assert!(accelerations[0].y.abs() < 1e-6 * expected);
+}